@@ -1,6 +1,7 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use std::rc::Rc;
+use std::time::Instant;
 
 use anyhow::{Context, anyhow};
 use cid::Cid;
@@ -113,8 +114,15 @@ where
         gas_premium: TokenAmount,
     ) -> Self {
         let limits = machine.new_limiter();
-        let gas_tracker =
+        let mut gas_tracker =
             GasTracker::new(Gas::new(gas_limit), Gas::zero(), machine.context().tracing);
+        if let Some(deadline) = machine
+            .context()
+            .execution_time_limit
+            .and_then(|limit| Instant::now().checked_add(limit))
+        {
+            gas_tracker.set_deadline(deadline);
+        }
 
         let state_access_tracker =
             StateAccessTracker::new(&machine.context().price_list.preloaded_actors);
@@ -204,8 +212,12 @@ where
             self.gas_tracker.push_limit(limit);
         }
 
+        let mut memory = None;
         let mut result = self.with_stack_frame(|s| {
-            s.call_actor_unchecked::<K>(from, to, entrypoint, params, value, read_only)
+            let initial = s.limits.memory_used();
+            let res = s.call_actor_unchecked::<K>(from, to, entrypoint, params, value, read_only);
+            memory = Some((initial, s.limits.peak_memory_used()));
+            res
         });
 
         // If we pushed a limit, pop it.
@@ -225,6 +237,9 @@ where
         }
 
         if self.machine.context().tracing && matches!(entrypoint, Entrypoint::Invoke(_)) {
+            if let Some((initial, peak)) = memory {
+                self.trace(ExecutionEvent::CallMemory { initial, peak });
+            }
             self.trace(match &result {
                 Ok(InvocationResult { exit_code, value }) => {
                     ExecutionEvent::CallReturn(*exit_code, value.as_ref().map(Into::into))
@@ -718,6 +733,13 @@ where
         // registry and passing them to wasmtime
         let additional_params = entrypoint.into_params(&mut block_registry)?;
 
+        // The target actor holds its own references to the parameters, which it may close.
+        if !self.limits.grow_block_memory(block_registry.memory_used()) {
+            return Err(
+                syscall_error!(LimitExceeded; "out of memory storing the parameters").into(),
+            );
+        }
+
         // Increment invocation count
         self.invocation_count += 1;
 
@@ -734,6 +756,7 @@ where
         log::trace!("calling {} -> {}::{}", from, to, entrypoint);
         self.map_mut(|cm| {
             let engine = cm.engine.clone(); // reference the RC.
+            let deadline = cm.gas_tracker.deadline();
 
             // Make the kernel.
             let kernel = K::new(
@@ -747,7 +770,8 @@ where
             );
 
             // Make a store.
-            let mut store = engine.new_store(kernel);
+            let mut store = engine.new_store(kernel, deadline);
+            let mut profile = None;
 
             // From this point on, there are no more syscall errors, only aborts.
             let result: std::result::Result<BlockId, Abort> = (|| {
@@ -782,6 +806,9 @@ where
 
                 // Set the available gas.
                 update_gas_available(&mut store)?;
+                engine
+                    .start_profile(&mut store, &instance, code)
+                    .map_err(Abort::Fatal)?;
 
                 let mut out = [wasmtime::Val::I32(0)];
                 let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
@@ -789,6 +816,10 @@ where
                 }))
                 .map_err(|panic| Abort::Fatal(anyhow!("panic within actor: {:?}", panic)))?;
 
+                profile = engine
+                    .collect_profile(&mut store, &instance, code)
+                    .map_err(Abort::Fatal)?;
+
                 // Charge for any remaining uncharged execution gas, returning an error if we run
                 // out.
                 charge_for_exec(&mut store)?;
@@ -807,6 +838,12 @@ where
             let last_error = invocation_data.last_error;
             let (mut cm, block_registry) = invocation_data.kernel.into_inner();
 
+            if let Some(profile) = profile
+                && cm.machine.context().tracing
+            {
+                cm.trace(ExecutionEvent::WasmProfile(profile));
+            }
+
             // Resolve the return block's ID into an actual block, converting to an abort if it
             // doesn't exist.
             let result = result.and_then(|ret_id| {
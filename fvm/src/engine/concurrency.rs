@@ -1,6 +1,7 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 /// An engine concurrency manages the concurrency available for a single engine. It's basically a
 /// semaphore that also assigns IDs to new engines.
@@ -12,6 +13,17 @@ pub(super) struct EngineConcurrency {
 struct EngineConcurrencyInner {
     next_id: u64,
     limit: u32,
+    concurrency: u32,
+    waits: u64,
+    wait_time: Duration,
+}
+
+/// A snapshot of the usage of an [`EngineConcurrency`].
+pub(super) struct ConcurrencyStats {
+    pub in_use: u32,
+    pub concurrency: u32,
+    pub waits: u64,
+    pub wait_time: Duration,
 }
 
 impl EngineConcurrency {
@@ -20,6 +32,9 @@ impl EngineConcurrency {
             inner: Mutex::new(EngineConcurrencyInner {
                 next_id: 0,
                 limit: concurrency,
+                concurrency,
+                waits: 0,
+                wait_time: Duration::ZERO,
             }),
             condv: Condvar::new(),
         }
@@ -28,10 +43,16 @@ impl EngineConcurrency {
     /// Acquire a new engine (well, an engine ID). This function blocks until we're below the
     /// maximum engine concurrency limit.
     pub fn acquire(&self) -> u64 {
-        let mut guard = self
-            .condv
-            .wait_while(self.inner.lock().unwrap(), |inner| inner.limit == 0)
-            .unwrap();
+        let mut guard = self.inner.lock().unwrap();
+        if guard.limit == 0 {
+            let start = Instant::now();
+            guard = self
+                .condv
+                .wait_while(guard, |inner| inner.limit == 0)
+                .unwrap();
+            guard.waits += 1;
+            guard.wait_time += start.elapsed();
+        }
         let id = guard.next_id;
 
         guard.limit -= 1;
@@ -47,6 +68,16 @@ impl EngineConcurrency {
         guard.limit += 1;
         self.condv.notify_one();
     }
+
+    pub fn stats(&self) -> ConcurrencyStats {
+        let guard = self.inner.lock().unwrap();
+        ConcurrencyStats {
+            in_use: guard.concurrency - guard.limit,
+            concurrency: guard.concurrency,
+            waits: guard.waits,
+            wait_time: guard.wait_time,
+        }
+    }
 }
 
 #[test]
@@ -69,6 +100,9 @@ fn test_engine_concurrency() {
         ids.sort();
         assert_eq!(ids, (2..12).collect::<Vec<_>>());
         assert_eq!(concurrency.inner.lock().unwrap().limit, 0);
+        let stats = concurrency.stats();
+        assert_eq!(stats.in_use, 2);
+        assert_eq!(stats.concurrency, 2);
         concurrency.release();
         assert_eq!(concurrency.inner.lock().unwrap().limit, 1);
     });
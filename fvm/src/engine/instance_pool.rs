@@ -16,6 +16,8 @@ pub(super) struct InstancePool {
 }
 
 struct InstancePoolInner {
+    /// The total number of instances in the pool.
+    size: u32,
     /// The number of instances available in the pool.
     available: u32,
     /// The maximum number of instances that can be in-use by any given engine. If available drops
@@ -31,6 +33,7 @@ impl InstancePool {
     pub fn new(available: u32, per_engine_limit: u32) -> InstancePool {
         InstancePool {
             inner: Mutex::new(InstancePoolInner {
+                size: available,
                 available,
                 per_engine_limit,
                 locked: None,
@@ -79,6 +82,12 @@ impl InstancePool {
             guard.locked = Some(id);
         }
     }
+
+    /// The number of instances currently taken out of the pool.
+    pub fn in_use(&self) -> u32 {
+        let guard = self.inner.lock().unwrap();
+        guard.size - guard.available
+    }
 }
 
 #[test]
@@ -105,6 +114,7 @@ fn test_instance_pool() {
         }
         assert_eq!(pool.inner.lock().unwrap().available, 1);
         assert_eq!(pool.inner.lock().unwrap().locked, Some(1));
+        assert_eq!(pool.in_use(), 11);
         // Put them all back for engine 1.
         for _ in 0..10 {
             pool.put();
@@ -3,11 +3,16 @@
 
 mod concurrency;
 mod instance_pool;
+mod module_cache;
+mod precompiled;
+mod profile;
+mod stats;
 
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, anyhow};
 use cid::Cid;
@@ -23,6 +28,7 @@ use wasmtime::{
 
 use crate::Kernel;
 use crate::gas::{Gas, GasTimer, WasmGasPrices};
+use crate::kernel::DeadlineExceeded;
 use crate::machine::limiter::MemoryLimiter;
 use crate::machine::{Machine, NetworkConfig};
 use crate::syscalls::error::Abort;
@@ -30,14 +36,24 @@ use crate::syscalls::{
     InvocationData, Linker, charge_for_exec, charge_for_init, record_init_time,
     update_gas_available,
 };
+use crate::trace::WasmProfile;
 
 use self::concurrency::EngineConcurrency;
 use self::instance_pool::InstancePool;
+use self::module_cache::ModuleCache;
+pub use self::module_cache::ModuleCacheLimits;
+pub use self::precompiled::PrecompiledWriter;
+use self::profile::ProfiledFunctions;
+use self::stats::Counters;
+pub use self::stats::{EngineStats, ModuleStats};
 
 /// The expected max stack depth used to determine the number of instances needed for a given
 /// concurrency level.
 const EXPECTED_MAX_STACK_DEPTH: u32 = 20;
 
+/// How often Wasm execution is interrupted to check the execution deadline, when enabled.
+const EPOCH_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Container managing [`Engine`]s with different consensus-affecting configurations.
 pub struct MultiEngine {
     engines: Mutex<HashMap<EngineConfig, EnginePool>>,
@@ -51,8 +67,12 @@ pub struct EngineConfig {
     pub max_wasm_stack: u32,
     pub max_inst_memory_bytes: u64,
     pub concurrency: u32,
+    /// Compared and hashed by value, so custom price lists with equal Wasm prices share engines.
     pub wasm_prices: &'static WasmGasPrices,
     pub actor_redirect: Vec<(Cid, Cid)>,
+    pub wasm_profiling: bool,
+    pub wasm_interruption: bool,
+    pub module_cache_limits: ModuleCacheLimits,
 }
 
 impl EngineConfig {
@@ -77,6 +97,9 @@ impl From<&NetworkConfig> for EngineConfig {
             wasm_prices: &nc.price_list.wasm_rules,
             actor_redirect: nc.actor_redirect.clone(),
             concurrency: 1,
+            wasm_profiling: nc.wasm_profiling,
+            wasm_interruption: nc.wasm_interruption,
+            module_cache_limits: nc.module_cache_limits,
         }
     }
 }
@@ -233,7 +256,8 @@ fn wasmtime_config(ec: &EngineConfig) -> anyhow::Result<wasmtime::Config> {
 
     // Execution cost accouting is done through wasm instrumentation,
     c.consume_fuel(false);
-    c.epoch_interruption(false);
+    // Epoch interruption is only used to enforce the (non-consensus) execution deadline.
+    c.epoch_interruption(ec.wasm_interruption);
 
     // Disable debug-related things, wasm-instrument doesn't fix debug info
     // yet, so those aren't useful, just add overhead
@@ -290,8 +314,10 @@ fn wasmtime_config(ec: &EngineConfig) -> anyhow::Result<wasmtime::Config> {
 #[derive(Clone)]
 struct ModuleRecord {
     module: Module,
-    /// Byte size of the original Wasm.
-    size: usize,
+    /// Byte size and load times of the module.
+    stats: ModuleStats,
+    /// Set if the module was instrumented for profiling.
+    profile: Option<Arc<ProfiledFunctions>>,
 }
 
 struct EngineInner {
@@ -308,11 +334,40 @@ struct EngineInner {
     dummy_gas_global: Global,
     dummy_memory: Memory,
 
-    module_cache: Mutex<HashMap<Cid, ModuleRecord>>,
+    module_cache: Mutex<ModuleCache>,
     instance_cache: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
     config: EngineConfig,
 
     actor_redirect: HashMap<Cid, Cid>,
+
+    counters: Counters,
+}
+
+impl EngineInner {
+    fn stats(&self) -> EngineStats {
+        let modules = self
+            .module_cache
+            .lock()
+            .expect("module_cache poisoned")
+            .iter()
+            .map(|(k, v)| (*k, v.stats))
+            .collect();
+        let concurrency = self.concurrency_limit.stats();
+        let instance_pool_size = self.config.instance_pool_size();
+        let mut stats = EngineStats {
+            modules,
+            instances_in_use: self.instance_limit.in_use(),
+            instance_pool_size,
+            engines_in_use: concurrency.in_use,
+            concurrency: concurrency.concurrency,
+            concurrency_waits: concurrency.waits,
+            concurrency_wait_time: concurrency.wait_time,
+            memory_reserved: instance_pool_size as u64 * self.config.max_inst_memory_bytes,
+            ..Default::default()
+        };
+        self.counters.snapshot(&mut stats);
+        stats
+    }
 }
 
 /// EnginePool represents a limited pool of engines.
@@ -329,6 +384,16 @@ impl EnginePool {
         }
     }
 
+    /// Take a snapshot of the metrics of the engines in this pool.
+    pub fn stats(&self) -> EngineStats {
+        self.0.stats()
+    }
+
+    /// The configuration of the engines in this pool.
+    pub fn config(&self) -> &EngineConfig {
+        &self.0.config
+    }
+
     /// Create a new [`EnginePool`].
     pub fn new(ec: EngineConfig) -> anyhow::Result<Self> {
         let c = wasmtime_config(&ec)?;
@@ -344,20 +409,44 @@ impl EnginePool {
 
         let actor_redirect = ec.actor_redirect.iter().cloned().collect();
 
+        if ec.wasm_interruption {
+            spawn_epoch_ticker(&engine)?;
+        }
+
         Ok(EnginePool(Arc::new(EngineInner {
             concurrency_limit: EngineConcurrency::new(ec.concurrency),
             instance_limit: InstancePool::new(ec.instance_pool_size(), ec.max_call_depth),
             engine,
             dummy_memory,
             dummy_gas_global: dummy_gg,
-            module_cache: Default::default(),
+            module_cache: Mutex::new(ModuleCache::new(ec.module_cache_limits)),
             instance_cache: Mutex::new(HashMap::new()),
             config: ec,
             actor_redirect,
+            counters: Default::default(),
         })))
     }
 }
 
+/// Periodically increments the engine's epoch so that running Wasm code is interrupted to check
+/// the execution deadline. The thread exits once the engine has been dropped.
+fn spawn_epoch_ticker(engine: &wasmtime::Engine) -> anyhow::Result<()> {
+    let engine = engine.weak();
+    std::thread::Builder::new()
+        .name("fvm-epoch-ticker".into())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(EPOCH_INTERVAL);
+                match engine.upgrade() {
+                    Some(engine) => engine.increment_epoch(),
+                    None => break,
+                }
+            }
+        })
+        .context("failed to spawn the epoch ticker thread")?;
+    Ok(())
+}
+
 struct Cache<K> {
     linker: wasmtime::Linker<InvocationData<K>>,
 }
@@ -379,31 +468,96 @@ impl Drop for Engine {
 }
 
 impl Engine {
+    /// Take a snapshot of the metrics of the [`EnginePool`] this engine belongs to.
+    pub fn stats(&self) -> EngineStats {
+        self.inner.stats()
+    }
+
     /// Loads an actor's Wasm code from the blockstore by CID, and prepares
     /// it for execution by instantiating and caching the Wasm module. This
     /// method errors if the code CID is not found in the store.
     ///
     /// Return the original byte code size.
     pub fn preload(&self, code_cid: &Cid, blockstore: &impl Blockstore) -> anyhow::Result<usize> {
+        self.preload_module(code_cid, blockstore, false)
+    }
+
+    /// Like [`Engine::preload`], optionally pinning the module. The module is pinned under the
+    /// same lock it's inserted with, so that loading one pinned module can't evict another.
+    fn preload_module(
+        &self,
+        code_cid: &Cid,
+        blockstore: &impl Blockstore,
+        pin: bool,
+    ) -> anyhow::Result<usize> {
         let code_cid = self.with_redirect(code_cid);
-        match self
+        let mut cache = self
             .inner
             .module_cache
             .lock()
-            .expect("module_cache poisoned")
-            .entry(*code_cid)
-        {
-            Occupied(e) => Ok(e.get().size),
-            Vacant(e) => {
-                let wasm = blockstore.get(code_cid)?.ok_or_else(|| {
-                    anyhow!(
-                        "no wasm bytecode in blockstore for CID {}",
-                        &code_cid.to_string()
-                    )
-                })?;
-                Ok(e.insert(self.load_raw(&wasm)?).size)
+            .expect("module_cache poisoned");
+        if let Some(record) = cache.get(code_cid) {
+            self.inner.counters.record_lookup(true);
+            let size = record.stats.size;
+            if pin {
+                cache.pin(*code_cid);
             }
+            return Ok(size);
+        }
+        self.inner.counters.record_lookup(false);
+        let wasm = blockstore.get(code_cid)?.ok_or_else(|| {
+            anyhow!(
+                "no wasm bytecode in blockstore for CID {}",
+                &code_cid.to_string()
+            )
+        })?;
+        let record = self.load_raw(&wasm)?;
+        let size = record.stats.size;
+        self.insert_module(&mut cache, *code_cid, record);
+        if pin {
+            cache.pin(*code_cid);
         }
+        Ok(size)
+    }
+
+    /// Like [`Engine::preload_all`], but also pins the modules so that they're never evicted from
+    /// the module cache (see [`ModuleCacheLimits`]). Used for the builtin actors.
+    pub fn preload_pinned<'a>(
+        &self,
+        blockstore: &impl Blockstore,
+        cids: impl IntoIterator<Item = &'a Cid>,
+    ) -> anyhow::Result<usize> {
+        self.preload_modules(blockstore, cids, true)
+    }
+
+    /// Removes an actor's compiled module from the module cache, unpinning it. The module will be
+    /// reloaded from the blockstore if the actor is invoked again.
+    ///
+    /// Returns true if the module was cached.
+    pub fn unload(&self, code_cid: &Cid) -> bool {
+        let code_cid = self.with_redirect(code_cid);
+        self.inner
+            .module_cache
+            .lock()
+            .expect("module_cache poisoned")
+            .remove(code_cid)
+    }
+
+    /// Removes all the modules that aren't pinned from the module cache.
+    ///
+    /// Returns the number of removed modules.
+    pub fn clear_cache(&self) -> usize {
+        self.inner
+            .module_cache
+            .lock()
+            .expect("module_cache poisoned")
+            .clear()
+    }
+
+    /// Inserts a module into the cache, recording any modules evicted to make room for it.
+    fn insert_module(&self, cache: &mut ModuleCache, k: Cid, record: ModuleRecord) {
+        let evicted = cache.insert(k, record);
+        self.inner.counters.record_evictions(evicted);
     }
 
     /// Instantiates and caches the Wasm modules for the bytecodes addressed by
@@ -416,13 +570,24 @@ impl Engine {
         &self,
         blockstore: &impl Blockstore,
         cids: impl IntoIterator<Item = &'a Cid>,
+    ) -> anyhow::Result<usize> {
+        self.preload_modules(blockstore, cids, false)
+    }
+
+    fn preload_modules<'a>(
+        &self,
+        blockstore: &impl Blockstore,
+        cids: impl IntoIterator<Item = &'a Cid>,
+        pin: bool,
     ) -> anyhow::Result<usize> {
         let mut total_size = 0usize;
         for cid in cids {
             log::trace!("preloading code CID {cid}");
-            let size = self.preload(cid, &blockstore).with_context(|| {
-                anyhow!("could not prepare actor with code CID {}", &cid.to_string())
-            })?;
+            let size = self
+                .preload_module(cid, &blockstore, pin)
+                .with_context(|| {
+                    anyhow!("could not prepare actor with code CID {}", &cid.to_string())
+                })?;
             total_size += size;
         }
         Ok(total_size)
@@ -437,9 +602,11 @@ impl Engine {
     }
 
     /// Load the specified wasm module with the internal Engine instance.
-    fn load_raw(&self, raw_wasm: &[u8]) -> anyhow::Result<ModuleRecord> {
+    fn load_raw(&self, original_wasm: &[u8]) -> anyhow::Result<ModuleRecord> {
+        let start = Instant::now();
+
         // First make sure that non-instrumented wasm is valid
-        Module::validate(&self.inner.engine, raw_wasm)
+        Module::validate(&self.inner.engine, original_wasm)
             .map_err(anyhow::Error::msg)
             .with_context(|| "failed to validate actor wasm")?;
 
@@ -451,7 +618,7 @@ impl Engine {
         // stack limiter adds post/pre-ambles to call instructions; We want to do that
         // before injecting gas accounting calls to avoid this overhead in every single
         // block of code.
-        let raw_wasm = stack_limiter::inject(raw_wasm, self.inner.config.max_wasm_stack)
+        let raw_wasm = stack_limiter::inject(original_wasm, self.inner.config.max_wasm_stack)
             .map_err(anyhow::Error::msg)?;
 
         // inject gas metering based on a price list. This function will
@@ -469,41 +636,71 @@ impl Engine {
         let raw_wasm = gas_metering::inject(&raw_wasm, self.inner.config.wasm_prices, "gas")
             .map_err(|_| anyhow::Error::msg("injecting gas counter failed"))?;
 
+        // Attribute the metered gas to functions, if profiling.
+        let (raw_wasm, profile) = if self.inner.config.wasm_profiling {
+            let (raw_wasm, functions) = profile::instrument(original_wasm, &raw_wasm)
+                .context("failed to instrument actor wasm for profiling")?;
+            (raw_wasm, Some(Arc::new(functions)))
+        } else {
+            (raw_wasm, None)
+        };
+
+        let instrumentation_time = start.elapsed();
+        let start = Instant::now();
         let module = Module::from_binary(&self.inner.engine, &raw_wasm)?;
+        self.inner.counters.record_compile();
 
         Ok(ModuleRecord {
             module,
-            size: raw_wasm.len(),
+            stats: ModuleStats {
+                size: raw_wasm.len(),
+                instrumentation_time,
+                compile_time: start.elapsed(),
+            },
+            profile,
         })
     }
 
-    /// Load compiled wasm code into the engine.
+    /// Load compiled wasm code into the engine, optionally pinning it under the same lock it's
+    /// inserted with (see [`Engine::preload_module`]).
     ///
     /// # Safety
     ///
     /// See [`wasmtime::Module::deserialize`] for safety information.
-    #[allow(dead_code)]
-    unsafe fn load_compiled(&self, k: &Cid, compiled: &[u8]) -> anyhow::Result<Module> {
+    unsafe fn load_compiled(&self, k: &Cid, compiled: &[u8], pin: bool) -> anyhow::Result<Module> {
         let k = self.with_redirect(k);
         let mut cache = self
             .inner
             .module_cache
             .lock()
             .expect("module_cache poisoned");
-        let module = match cache.get(k) {
-            Some(m) => m.module.clone(),
+        let cached = cache.get(k).map(|m| m.module.clone());
+        self.inner.counters.record_lookup(cached.is_some());
+        let module = match cached {
+            Some(module) => module,
             None => {
+                let start = Instant::now();
                 let module = unsafe { Module::deserialize(&self.inner.engine, compiled)? };
-                cache.insert(
+                self.inner.counters.record_compile();
+                self.insert_module(
+                    &mut cache,
                     *k,
                     ModuleRecord {
                         module: module.clone(),
-                        size: compiled.len(),
+                        stats: ModuleStats {
+                            size: compiled.len(),
+                            instrumentation_time: Duration::ZERO,
+                            compile_time: start.elapsed(),
+                        },
+                        profile: None,
                     },
                 );
                 module
             }
         };
+        if pin {
+            cache.pin(*k);
+        }
         Ok(module)
     }
 
@@ -587,9 +784,11 @@ impl Engine {
             Ok(Some(inst))
         };
 
-        match module_cache.entry(*k) {
-            Occupied(v) => instantiate(store, &v.get().module),
-            Vacant(v) => match store
+        let cached = module_cache.get(k).map(|m| m.module.clone());
+        self.inner.counters.record_lookup(cached.is_some());
+        match cached {
+            Some(module) => instantiate(store, &module),
+            None => match store
                 .data()
                 .kernel
                 .machine()
@@ -598,18 +797,76 @@ impl Engine {
                 .context("failed to lookup wasm module in blockstore")
                 .map_err(Abort::Fatal)?
             {
-                Some(raw_wasm) => instantiate(
-                    store,
-                    &v.insert(self.load_raw(&raw_wasm).map_err(Abort::Fatal)?)
-                        .module,
-                ),
+                Some(raw_wasm) => {
+                    let record = self.load_raw(&raw_wasm).map_err(Abort::Fatal)?;
+                    let module = record.module.clone();
+                    self.insert_module(&mut module_cache, *k, record);
+                    instantiate(store, &module)
+                }
                 None => Ok(None),
             },
         }
     }
 
+    /// Returns the profiled functions of the given actor code, if it was loaded with profiling
+    /// enabled.
+    fn profiled_functions(&self, k: &Cid) -> Option<Arc<ProfiledFunctions>> {
+        // Don't contend on the module cache lock on every invocation when profiling is disabled.
+        if !self.inner.config.wasm_profiling {
+            return None;
+        }
+        let k = self.with_redirect(k);
+        let cache = self
+            .inner
+            .module_cache
+            .lock()
+            .expect("module_cache poisoned");
+        cache.peek(k).and_then(|m| m.profile.clone())
+    }
+
+    /// Starts profiling an instance of the given actor code, before calling into it. Does nothing
+    /// unless Wasm profiling is enabled.
+    pub(crate) fn start_profile<K: Kernel>(
+        &self,
+        store: &mut wasmtime::Store<InvocationData<K>>,
+        instance: &wasmtime::Instance,
+        k: &Cid,
+    ) -> anyhow::Result<()> {
+        match self.profiled_functions(k) {
+            Some(functions) => {
+                let gas_counter = store.data().avail_gas_global;
+                functions.start(store, instance, gas_counter)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the gas charged to each function of an instance of the given actor code since
+    /// [`Engine::start_profile`], if Wasm profiling is enabled.
+    pub(crate) fn collect_profile<K: Kernel>(
+        &self,
+        store: &mut wasmtime::Store<InvocationData<K>>,
+        instance: &wasmtime::Instance,
+        k: &Cid,
+    ) -> anyhow::Result<Option<WasmProfile>> {
+        match self.profiled_functions(k) {
+            Some(functions) => {
+                let gas_counter = store.data().avail_gas_global;
+                functions.collect(store, instance, gas_counter).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Construct a new wasmtime "store" from the given kernel.
-    pub(crate) fn new_store<K: Kernel>(&self, mut kernel: K) -> wasmtime::Store<InvocationData<K>> {
+    ///
+    /// If Wasm interruption is enabled, the execution is aborted with a [`DeadlineExceeded`] error
+    /// once the given deadline passes.
+    pub(crate) fn new_store<K: Kernel>(
+        &self,
+        mut kernel: K,
+        deadline: Option<Instant>,
+    ) -> wasmtime::Store<InvocationData<K>> {
         // Take a new instance and put it into a drop-guard that removes the reservation when
         // we're done.
         #[must_use]
@@ -635,6 +892,7 @@ impl Engine {
             last_charge_time: GasTimer::start(),
             memory: self.inner.dummy_memory,
             wasm_prices: self.inner.config.wasm_prices,
+            deadline,
         };
 
         let mut store = wasmtime::Store::new(&self.inner.engine, id);
@@ -643,6 +901,14 @@ impl Engine {
             .expect("failed to create available_gas global");
         store.data_mut().avail_gas_global = gg;
 
+        if self.inner.config.wasm_interruption {
+            store.set_epoch_deadline(1);
+            store.epoch_deadline_callback(|ctx| match ctx.data().deadline {
+                Some(deadline) if Instant::now() >= deadline => Err(DeadlineExceeded.into()),
+                _ => Ok(wasmtime::UpdateDeadline::Continue(1)),
+            });
+        }
+
         store.limiter(move |data| {
             // Keep the reservation alive as long as the limiter is alive. The limiter limits the
             // store to one instance and one memory, which is covered by the reservation.
@@ -710,9 +976,18 @@ impl<L: MemoryLimiter> wasmtime::ResourceLimiter for WasmtimeLimiter<L> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use cid::Cid;
+    use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+    use fvm_shared::IPLD_RAW;
+    use fvm_shared::version::NetworkVersion;
+    use multihash_codetable::{Code, MultihashDigest};
     use wasmtime::ResourceLimiter;
 
-    use crate::engine::WasmtimeLimiter;
+    use crate::engine::{EnginePool, ModuleCacheLimits, MultiEngine, WasmtimeLimiter};
+    use crate::gas::{Gas, price_list_by_network_version};
+    use crate::machine::NetworkConfig;
     use crate::machine::limiter::MemoryLimiter;
 
     #[derive(Default)]
@@ -767,4 +1042,114 @@ mod tests {
         assert!(limits.table_growing(2, 4, None).unwrap());
         assert_eq!(limits.0.memory, 5 * 8);
     }
+
+    /// `(module (func (export "invoke")))`: the engine rejects modules without any functions.
+    pub(super) const WASM: &[u8] = b"\0asm\x01\0\0\0\
+        \x01\x04\x01\x60\0\0\
+        \x03\x02\x01\0\
+        \x07\x0a\x01\x06invoke\0\0\
+        \x0a\x04\x01\x02\0\x0b";
+
+    #[test]
+    fn stats() {
+        let bs = MemoryBlockstore::default();
+        let cid = Cid::new_v1(IPLD_RAW, Code::Blake2b256.digest(WASM));
+        bs.put_keyed(&cid, WASM).unwrap();
+
+        let nc = NetworkConfig::new(NetworkVersion::V25);
+        let pool = EnginePool::new((&nc).into()).unwrap();
+        let engine = pool.acquire();
+
+        engine.preload(&cid, &bs).unwrap();
+        engine.preload(&cid, &bs).unwrap();
+
+        let stats = engine.stats();
+        assert_eq!(stats.modules_compiled, 1);
+        assert_eq!(stats.module_cache_hits, 1);
+        assert_eq!(stats.module_cache_misses, 1);
+        assert_eq!(stats.module_cache_hit_rate(), Some(0.5));
+        assert!(stats.modules[&cid].size > 0);
+        assert_eq!(stats.engines_in_use, 1);
+        assert_eq!(stats.concurrency, 1);
+        assert_eq!(stats.instances_in_use, 0);
+        assert_eq!(
+            stats.memory_reserved,
+            stats.instance_pool_size as u64 * nc.max_inst_memory_bytes
+        );
+
+        drop(engine);
+        assert_eq!(pool.stats().engines_in_use, 0);
+    }
+
+    #[test]
+    fn unload_and_clear_cache() {
+        let bs = MemoryBlockstore::default();
+        let cid = Cid::new_v1(IPLD_RAW, Code::Blake2b256.digest(WASM));
+        bs.put_keyed(&cid, WASM).unwrap();
+
+        let nc = NetworkConfig::new(NetworkVersion::V25);
+        let engine = EnginePool::new((&nc).into()).unwrap().acquire();
+
+        engine.preload(&cid, &bs).unwrap();
+        assert!(engine.unload(&cid));
+        assert!(!engine.unload(&cid));
+        assert!(engine.stats().modules.is_empty());
+
+        // Unloaded modules get reloaded.
+        engine.preload(&cid, &bs).unwrap();
+        assert_eq!(engine.stats().modules_compiled, 2);
+
+        // Pinned modules survive clearing the cache.
+        engine.preload_pinned(&bs, &[cid]).unwrap();
+        assert_eq!(engine.clear_cache(), 0);
+        assert!(engine.stats().modules.contains_key(&cid));
+    }
+
+    #[test]
+    fn preload_pinned_beyond_limits() {
+        let bs = MemoryBlockstore::default();
+        // Append an empty custom section to get a second, distinct module.
+        let cids = [WASM.to_vec(), [WASM, b"\0\x02\x01a"].concat()].map(|wasm| {
+            let cid = Cid::new_v1(IPLD_RAW, Code::Blake2b256.digest(&wasm));
+            bs.put_keyed(&cid, &wasm).unwrap();
+            cid
+        });
+
+        let mut nc = NetworkConfig::new(NetworkVersion::V25);
+        nc.limit_module_cache(ModuleCacheLimits {
+            max_modules: Some(1),
+            max_bytes: None,
+        });
+        let engine = EnginePool::new((&nc).into()).unwrap().acquire();
+
+        engine.preload_pinned(&bs, &cids).unwrap();
+        let stats = engine.stats();
+        assert_eq!(stats.modules.len(), 2);
+        assert_eq!(stats.modules_evicted, 0);
+    }
+
+    #[test]
+    fn engines_keyed_on_wasm_prices() {
+        let engines = MultiEngine::new(1);
+        let base = price_list_by_network_version(NetworkVersion::V25);
+
+        // An equal price list at a different address shares the engine.
+        let mut nc = NetworkConfig::new(NetworkVersion::V25);
+        let a = engines.get(&nc).unwrap();
+        nc.override_price_list(Box::leak(Box::new(base.clone())));
+        let b = engines.get(&nc).unwrap();
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+
+        // Different Wasm prices get a different engine.
+        let wasm_rules = base
+            .wasm_rules
+            .builder()
+            .instruction_default(Gas::new(5))
+            .build();
+        nc.override_price_list(Box::leak(Box::new(
+            base.builder().wasm_rules(wasm_rules).build(),
+        )));
+        let c = engines.get(&nc).unwrap();
+        assert!(!Arc::ptr_eq(&a.0, &c.0));
+    }
 }
@@ -0,0 +1,216 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::{HashMap, HashSet};
+
+use cid::Cid;
+
+use super::ModuleRecord;
+
+/// Bounds on the number and total size of the modules an engine keeps compiled in memory. Once
+/// exceeded, the least recently used modules are evicted, except for pinned modules (the builtin
+/// actors). Evicted modules are transparently reloaded from the blockstore when next needed, so
+/// these limits don't affect execution results, only performance.
+///
+/// DEFAULT: unbounded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModuleCacheLimits {
+    /// The maximum number of cached modules.
+    pub max_modules: Option<usize>,
+    /// The maximum total byte size of the (instrumented) code of the cached modules.
+    pub max_bytes: Option<usize>,
+}
+
+struct CacheEntry {
+    record: ModuleRecord,
+    last_used: u64,
+}
+
+/// The cache of compiled modules of an engine, keyed on the code CID.
+#[derive(Default)]
+pub(super) struct ModuleCache {
+    limits: ModuleCacheLimits,
+    entries: HashMap<Cid, CacheEntry>,
+    pinned: HashSet<Cid>,
+    total_size: usize,
+    /// Incremented on every access, to find the least recently used modules.
+    clock: u64,
+}
+
+impl ModuleCache {
+    pub fn new(limits: ModuleCacheLimits) -> Self {
+        ModuleCache {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Look up a module, marking it as recently used.
+    pub fn get(&mut self, k: &Cid) -> Option<&ModuleRecord> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(k)?;
+        entry.last_used = self.clock;
+        Some(&entry.record)
+    }
+
+    /// Look up a module without marking it as used.
+    pub fn peek(&self, k: &Cid) -> Option<&ModuleRecord> {
+        self.entries.get(k).map(|e| &e.record)
+    }
+
+    /// Insert a module, evicting the least recently used unpinned modules if the cache exceeds its
+    /// limits. Returns the number of evicted modules. The inserted module itself is never evicted.
+    pub fn insert(&mut self, k: Cid, record: ModuleRecord) -> usize {
+        self.clock += 1;
+        self.total_size += record.stats.size;
+        if let Some(old) = self.entries.insert(
+            k,
+            CacheEntry {
+                record,
+                last_used: self.clock,
+            },
+        ) {
+            self.total_size -= old.record.stats.size;
+        }
+
+        let mut evicted = 0;
+        while self.over_limits() {
+            let lru = self
+                .entries
+                .iter()
+                .filter(|(cid, _)| **cid != k && !self.pinned.contains(cid))
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(cid, _)| *cid);
+            match lru {
+                Some(cid) => {
+                    self.remove(&cid);
+                    evicted += 1;
+                }
+                // Everything else is pinned.
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    fn over_limits(&self) -> bool {
+        self.limits
+            .max_modules
+            .is_some_and(|max| self.entries.len() > max)
+            || self
+                .limits
+                .max_bytes
+                .is_some_and(|max| self.total_size > max)
+    }
+
+    /// Remove a module, unpinning it. Returns true if it was cached.
+    pub fn remove(&mut self, k: &Cid) -> bool {
+        self.pinned.remove(k);
+        match self.entries.remove(k) {
+            Some(entry) => {
+                self.total_size -= entry.record.stats.size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove all unpinned modules. Returns the number of removed modules.
+    pub fn clear(&mut self) -> usize {
+        let before = self.entries.len();
+        let pinned = &self.pinned;
+        self.entries.retain(|k, _| pinned.contains(k));
+        self.total_size = self.entries.values().map(|e| e.record.stats.size).sum();
+        before - self.entries.len()
+    }
+
+    /// Exempt a module from eviction. It can still be removed explicitly.
+    pub fn pin(&mut self, k: Cid) {
+        self.pinned.insert(k);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Cid, &ModuleRecord)> {
+        self.entries.iter().map(|(k, e)| (k, &e.record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use multihash_codetable::{Code, MultihashDigest};
+    use wasmtime::Module;
+
+    use super::*;
+    use crate::engine::ModuleStats;
+
+    fn record(engine: &wasmtime::Engine, size: usize) -> ModuleRecord {
+        ModuleRecord {
+            module: Module::new(engine, b"\0asm\x01\0\0\0").unwrap(),
+            stats: ModuleStats {
+                size,
+                ..Default::default()
+            },
+            profile: None,
+        }
+    }
+
+    fn cid(i: u8) -> Cid {
+        Cid::new_v1(fvm_shared::IPLD_RAW, Code::Blake2b256.digest(&[i]))
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let engine = wasmtime::Engine::default();
+        let mut cache = ModuleCache::new(ModuleCacheLimits {
+            max_modules: Some(2),
+            max_bytes: None,
+        });
+        assert_eq!(cache.insert(cid(1), record(&engine, 10)), 0);
+        assert_eq!(cache.insert(cid(2), record(&engine, 10)), 0);
+        assert!(cache.get(&cid(1)).is_some());
+        assert_eq!(cache.insert(cid(3), record(&engine, 10)), 1);
+        assert!(cache.peek(&cid(1)).is_some());
+        assert!(cache.peek(&cid(2)).is_none());
+        assert!(cache.peek(&cid(3)).is_some());
+    }
+
+    #[test]
+    fn evicts_by_size_and_keeps_pinned() {
+        let engine = wasmtime::Engine::default();
+        let mut cache = ModuleCache::new(ModuleCacheLimits {
+            max_modules: None,
+            max_bytes: Some(25),
+        });
+        cache.insert(cid(1), record(&engine, 10));
+        cache.pin(cid(1));
+        cache.insert(cid(2), record(&engine, 10));
+        assert_eq!(cache.insert(cid(3), record(&engine, 10)), 1);
+        assert!(cache.peek(&cid(1)).is_some());
+        assert!(cache.peek(&cid(2)).is_none());
+
+        // Nothing else can be evicted, so the cache may exceed its limits.
+        assert_eq!(cache.insert(cid(4), record(&engine, 20)), 1);
+        assert!(cache.peek(&cid(1)).is_some());
+        assert!(cache.peek(&cid(4)).is_some());
+        assert_eq!(cache.total_size, 30);
+    }
+
+    #[test]
+    fn unload_and_clear() {
+        let engine = wasmtime::Engine::default();
+        let mut cache = ModuleCache::default();
+        cache.insert(cid(1), record(&engine, 10));
+        cache.pin(cid(1));
+        cache.insert(cid(2), record(&engine, 10));
+        cache.insert(cid(3), record(&engine, 10));
+
+        assert!(cache.remove(&cid(3)));
+        assert!(!cache.remove(&cid(3)));
+        assert_eq!(cache.clear(), 1);
+        assert_eq!(cache.iter().count(), 1);
+        assert_eq!(cache.total_size, 10);
+
+        // Unloading a pinned module unpins it.
+        assert!(cache.remove(&cid(1)));
+        assert!(cache.pinned.is_empty());
+        assert_eq!(cache.total_size, 0);
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Ahead-of-time compiled actor code.
+//!
+//! A precompiled directory contains one `<code cid>.cwasm` file per actor, holding the module
+//! serialized by wasmtime after validation and instrumentation, and a `manifest.cbor` file recording
+//! the code CIDs and the parameters the code was instrumented with. Loading the directory into an
+//! [`Engine`] fails unless those parameters match the engine's, and wasmtime itself refuses to load
+//! modules compiled by a different version or with an incompatible configuration.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, anyhow};
+use cid::Cid;
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{from_slice, to_vec};
+
+use super::Engine;
+use crate::gas::WasmGasPrices;
+
+const MANIFEST_FILE: &str = "manifest.cbor";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Serialize_tuple, Deserialize_tuple)]
+struct PrecompiledManifest {
+    version: u32,
+    max_wasm_stack: u32,
+    wasm_prices: WasmGasPrices,
+    modules: Vec<Cid>,
+}
+
+fn module_path(dir: &Path, code_cid: &Cid) -> PathBuf {
+    dir.join(format!("{code_cid}.cwasm"))
+}
+
+/// Writes the precompiled code of actors to a directory, to be loaded with
+/// [`Engine::load_precompiled`]. The code is compiled with the configuration of the given engine.
+pub struct PrecompiledWriter<'a> {
+    engine: &'a Engine,
+    dir: PathBuf,
+    modules: Vec<Cid>,
+}
+
+impl<'a> PrecompiledWriter<'a> {
+    /// Create a writer, creating the output directory if it doesn't exist.
+    pub fn new(engine: &'a Engine, dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        Ok(PrecompiledWriter {
+            engine,
+            dir,
+            modules: Vec::new(),
+        })
+    }
+
+    /// Validate, instrument and compile the Wasm code of an actor, and write it to the directory.
+    ///
+    /// Returns the byte size of the compiled module.
+    pub fn add(&mut self, code_cid: &Cid, wasm: &[u8]) -> anyhow::Result<usize> {
+        if self.engine.inner.config.wasm_profiling {
+            return Err(anyhow!(
+                "can't precompile actors with Wasm profiling enabled"
+            ));
+        }
+        let compiled = self.engine.load_raw(wasm)?.module.serialize()?;
+        let path = module_path(&self.dir, code_cid);
+        std::fs::write(&path, &compiled)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        self.modules.push(*code_cid);
+        Ok(compiled.len())
+    }
+
+    /// Write the manifest of the added modules, completing the directory.
+    pub fn finish(self) -> anyhow::Result<()> {
+        let config = &self.engine.inner.config;
+        let manifest = PrecompiledManifest {
+            version: MANIFEST_VERSION,
+            max_wasm_stack: config.max_wasm_stack,
+            wasm_prices: config.wasm_prices.clone(),
+            modules: self.modules,
+        };
+        let path = self.dir.join(MANIFEST_FILE);
+        std::fs::write(&path, to_vec(&manifest)?)
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+impl Engine {
+    /// Load the actor code precompiled into a directory by a [`PrecompiledWriter`] into the module
+    /// cache, pinning it. Modules already in the cache are kept.
+    ///
+    /// Returns the code CIDs of the loaded modules.
+    ///
+    /// # Safety
+    ///
+    /// The directory must have been written by a [`PrecompiledWriter`] and not tampered with since:
+    /// the compiled code is loaded as-is, see [`wasmtime::Module::deserialize`].
+    pub unsafe fn load_precompiled(&self, dir: &Path) -> anyhow::Result<Vec<Cid>> {
+        let path = dir.join(MANIFEST_FILE);
+        let manifest: PrecompiledManifest = from_slice(
+            &std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?,
+        )
+        .context("failed to decode the precompiled manifest")?;
+
+        let config = &self.inner.config;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(anyhow!(
+                "unsupported precompiled manifest version {}",
+                manifest.version
+            ));
+        }
+        if manifest.max_wasm_stack != config.max_wasm_stack
+            || &manifest.wasm_prices != config.wasm_prices
+        {
+            return Err(anyhow!(
+                "actors were precompiled with a different instrumentation configuration"
+            ));
+        }
+        if config.wasm_profiling {
+            return Err(anyhow!(
+                "can't load precompiled actors with Wasm profiling enabled"
+            ));
+        }
+
+        for code_cid in &manifest.modules {
+            let path = module_path(dir, code_cid);
+            let compiled = std::fs::read(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            // SAFETY: the caller guarantees the directory was written by a `PrecompiledWriter`.
+            unsafe { self.load_compiled(code_cid, &compiled, true) }
+                .with_context(|| format!("failed to load precompiled actor {code_cid}"))?;
+        }
+
+        Ok(manifest.modules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::version::NetworkVersion;
+    use multihash_codetable::{Code, MultihashDigest};
+
+    use super::*;
+    use crate::engine::EnginePool;
+    use crate::engine::tests::WASM;
+    use crate::gas::Gas;
+    use crate::machine::NetworkConfig;
+
+    #[test]
+    fn write_and_load() {
+        let cid = Cid::new_v1(fvm_shared::IPLD_RAW, Code::Blake2b256.digest(WASM));
+        let dir = std::env::temp_dir().join(format!("fvm-precompiled-{}", std::process::id()));
+
+        let nc = NetworkConfig::new(NetworkVersion::V25);
+        let engine = EnginePool::new((&nc).into()).unwrap().acquire();
+        let mut writer = PrecompiledWriter::new(&engine, &dir).unwrap();
+        assert!(writer.add(&cid, WASM).unwrap() > 0);
+        assert!(writer.add(&cid, b"not wasm").is_err());
+        writer.finish().unwrap();
+
+        let engine = EnginePool::new((&nc).into()).unwrap().acquire();
+        let loaded = unsafe { engine.load_precompiled(&dir) }.unwrap();
+        assert_eq!(loaded, vec![cid]);
+        let stats = engine.stats();
+        assert!(stats.modules.contains_key(&cid));
+        assert_eq!(engine.clear_cache(), 0);
+
+        // Engines instrumenting with different prices can't use the code.
+        let mut nc = nc;
+        let base = nc.price_list;
+        let wasm_rules = base
+            .wasm_rules
+            .builder()
+            .instruction_default(Gas::new(5))
+            .build();
+        nc.override_price_list(Box::leak(Box::new(
+            base.builder().wasm_rules(wasm_rules).build(),
+        )));
+        let engine = EnginePool::new((&nc).into()).unwrap().acquire();
+        assert!(unsafe { engine.load_precompiled(&dir) }.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,629 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Per-function Wasm gas profiling.
+//!
+//! When profiling is enabled, the gas-metered module is instrumented with one mutable global per
+//! function accumulating the gas charged while that function was executing, plus a global
+//! remembering the value of the gas counter at the last function boundary. At each boundary (calls,
+//! returns, and branches out of the function body), the function "flushes" the gas charged since
+//! the last boundary into its accumulator:
+//!
+//! ```text
+//! acc[f] += last - gas_counter
+//! last = gas_counter
+//! ```
+//!
+//! After each call, `last` is reset to the gas counter so that gas charged by syscalls isn't
+//! attributed to the caller. The accumulators are exported so they can be read once the
+//! invocation completes.
+//!
+//! This changes the code executed by actors (although not the gas they're charged) and is only
+//! meant to be used by actor developers.
+
+use std::collections::HashMap;
+
+use anyhow::{Context as _, anyhow};
+use fvm_wasm_instrument::gas_metering::GAS_COUNTER_NAME;
+use wasmparser::{FunctionBody, KnownCustom, Name, Operator, Parser, Payload, TypeRef};
+use wasmtime::{AsContextMut, Global, Instance, Val};
+
+use crate::gas::Gas;
+use crate::trace::{FunctionGas, WasmProfile};
+
+const LAST_EXPORT: &str = "fvm_profile.last";
+const ACCUMULATOR_EXPORT_PREFIX: &str = "fvm_profile.";
+
+const GLOBAL_SECTION: u8 = 6;
+const EXPORT_SECTION: u8 = 7;
+const CODE_SECTION: u8 = 10;
+
+/// The functions of an instrumented module, along with their names from the module's name
+/// section.
+#[derive(Debug)]
+pub(crate) struct ProfiledFunctions {
+    functions: Vec<(u32, Option<String>)>,
+}
+
+impl ProfiledFunctions {
+    /// Resets the profiling globals of a new instance, before calling into it.
+    pub fn start(
+        &self,
+        mut store: impl AsContextMut,
+        instance: &Instance,
+        gas_counter: Global,
+    ) -> anyhow::Result<()> {
+        let mut store = store.as_context_mut();
+        let gas = gas_counter.get(&mut store);
+        profile_global(&mut store, instance, LAST_EXPORT)?.set(&mut store, gas)?;
+        for (index, _) in &self.functions {
+            accumulator(&mut store, instance, *index)?.set(&mut store, Val::I64(0))?;
+        }
+        Ok(())
+    }
+
+    /// Reads the gas charged to each function of the instance since [`ProfiledFunctions::start`].
+    pub fn collect(
+        &self,
+        mut store: impl AsContextMut,
+        instance: &Instance,
+        gas_counter: Global,
+    ) -> anyhow::Result<WasmProfile> {
+        let mut store = store.as_context_mut();
+        let read = |store: &mut wasmtime::StoreContextMut<_>, global: Global| {
+            global
+                .get(store)
+                .i64()
+                .context("profiling global is not an i64")
+        };
+
+        let mut functions = Vec::new();
+        for (index, name) in &self.functions {
+            let global = accumulator(&mut store, instance, *index)?;
+            let gas = read(&mut store, global)?;
+            if gas > 0 {
+                functions.push(FunctionGas {
+                    index: *index,
+                    name: name.clone(),
+                    gas: Gas::from_milligas(gas as u64),
+                });
+            }
+        }
+        functions.sort_by(|a, b| b.gas.cmp(&a.gas).then(a.index.cmp(&b.index)));
+
+        // Anything charged after the last boundary, e.g., by a function that trapped.
+        let last = profile_global(&mut store, instance, LAST_EXPORT)?;
+        let unattributed = read(&mut store, last)?.saturating_sub(read(&mut store, gas_counter)?);
+
+        Ok(WasmProfile {
+            functions,
+            unattributed: Gas::from_milligas(unattributed.max(0) as u64),
+        })
+    }
+}
+
+fn profile_global(
+    store: impl AsContextMut,
+    instance: &Instance,
+    name: &str,
+) -> anyhow::Result<Global> {
+    instance
+        .get_global(store, name)
+        .ok_or_else(|| anyhow!("module has no {name} export"))
+}
+
+fn accumulator(
+    store: impl AsContextMut,
+    instance: &Instance,
+    index: u32,
+) -> anyhow::Result<Global> {
+    profile_global(
+        store,
+        instance,
+        &format!("{ACCUMULATOR_EXPORT_PREFIX}{index}"),
+    )
+}
+
+/// Instruments a gas-metered module for profiling.
+///
+/// Only the functions of the `original` module are profiled: the functions added by the stack
+/// limiter and the gas metering are left untouched, so the gas they charge is attributed to their
+/// caller.
+pub(crate) fn instrument(
+    original: &[u8],
+    metered: &[u8],
+) -> anyhow::Result<(Vec<u8>, ProfiledFunctions)> {
+    let original = ModuleInfo::parse(original)?;
+    let metered_info = ModuleInfo::parse(metered)?;
+    if metered_info.imported_funcs != original.imported_funcs {
+        return Err(anyhow!("gas metering changed the imported functions"));
+    }
+    let gas_counter = metered_info
+        .gas_counter
+        .ok_or_else(|| anyhow!("module doesn't import the gas counter"))?;
+
+    // The globals we add go after all the existing ones.
+    let last = metered_info.imported_globals + metered_info.defined_globals;
+    let layout = Layout {
+        gas_counter,
+        last,
+        // Imports and the original functions.
+        profiled_funcs: original.imported_funcs + original.defined_funcs,
+        imported_funcs: original.imported_funcs,
+    };
+
+    let mut out = metered[..8].to_vec();
+    let mut globals_done = false;
+    let mut exports_done = false;
+    let mut code = None;
+    let mut func_index = layout.imported_funcs;
+    for payload in Parser::new(0).parse_all(metered) {
+        let payload = payload?;
+        if let Payload::CodeSectionEntry(body) = &payload {
+            let (count, bodies) = code
+                .as_mut()
+                .context("code entry outside of code section")?;
+            layout.rewrite_body(metered, body, func_index, bodies)?;
+            func_index += 1;
+            *count -= 1;
+            if *count == 0 {
+                let (_, bodies) = code.take().unwrap();
+                write_section(&mut out, CODE_SECTION, &bodies);
+            }
+            continue;
+        }
+        let Some((id, range)) = payload.as_section() else {
+            continue;
+        };
+
+        // Add our globals and exports, creating the sections if the module doesn't have them.
+        if id != 0 && !globals_done && section_order(id) > section_order(GLOBAL_SECTION) {
+            write_section(&mut out, GLOBAL_SECTION, &layout.globals(&[], 0));
+            globals_done = true;
+        }
+        if id != 0 && !exports_done && section_order(id) > section_order(EXPORT_SECTION) {
+            write_section(&mut out, EXPORT_SECTION, &layout.exports(&[], 0));
+            exports_done = true;
+        }
+
+        let data = &metered[range];
+        match id {
+            GLOBAL_SECTION => {
+                let (count, entries) = split_count(data)?;
+                write_section(&mut out, GLOBAL_SECTION, &layout.globals(entries, count));
+                globals_done = true;
+            }
+            EXPORT_SECTION => {
+                let (count, entries) = split_count(data)?;
+                write_section(&mut out, EXPORT_SECTION, &layout.exports(entries, count));
+                exports_done = true;
+            }
+            CODE_SECTION => {
+                let (count, _) = split_count(data)?;
+                if count == 0 {
+                    write_section(&mut out, CODE_SECTION, data);
+                } else {
+                    let mut bodies = Vec::new();
+                    write_leb(&mut bodies, count);
+                    code = Some((count, bodies));
+                }
+            }
+            _ => write_section(&mut out, id, data),
+        }
+    }
+    if !globals_done {
+        write_section(&mut out, GLOBAL_SECTION, &layout.globals(&[], 0));
+    }
+    if !exports_done {
+        write_section(&mut out, EXPORT_SECTION, &layout.exports(&[], 0));
+    }
+
+    let functions = (original.imported_funcs..layout.profiled_funcs)
+        .map(|index| (index, original.names.get(&index).cloned()))
+        .collect();
+    Ok((out, ProfiledFunctions { functions }))
+}
+
+/// What we need to know about a module to instrument it.
+#[derive(Default)]
+struct ModuleInfo {
+    imported_funcs: u32,
+    defined_funcs: u32,
+    imported_globals: u32,
+    defined_globals: u32,
+    gas_counter: Option<u32>,
+    names: HashMap<u32, String>,
+}
+
+impl ModuleInfo {
+    fn parse(wasm: &[u8]) -> anyhow::Result<Self> {
+        let mut info = ModuleInfo::default();
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        match import.ty {
+                            TypeRef::Func(_) => info.imported_funcs += 1,
+                            TypeRef::Global(_) => {
+                                if import.module == "gas" && import.name == GAS_COUNTER_NAME {
+                                    info.gas_counter = Some(info.imported_globals);
+                                }
+                                info.imported_globals += 1;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => info.defined_funcs = reader.count(),
+                Payload::GlobalSection(reader) => info.defined_globals = reader.count(),
+                Payload::CustomSection(reader) => {
+                    // Names are best-effort, ignore malformed name sections.
+                    if let KnownCustom::Name(names) = reader.as_known() {
+                        for name in names.into_iter().flatten() {
+                            if let Name::Function(map) = name {
+                                for naming in map.into_iter().flatten() {
+                                    info.names.insert(naming.index, naming.name.to_owned());
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// Indices of the globals and functions involved in profiling.
+struct Layout {
+    gas_counter: u32,
+    last: u32,
+    profiled_funcs: u32,
+    imported_funcs: u32,
+}
+
+impl Layout {
+    fn accumulator(&self, func_index: u32) -> u32 {
+        self.last + 1 + (func_index - self.imported_funcs)
+    }
+
+    fn profiled(&self) -> std::ops::Range<u32> {
+        self.imported_funcs..self.profiled_funcs
+    }
+
+    /// Encodes the global section: the existing entries followed by ours, all `(mut i64)`
+    /// initialized to zero.
+    fn globals(&self, entries: &[u8], count: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_leb(&mut out, count + 1 + self.profiled().len() as u32);
+        out.extend_from_slice(entries);
+        for _ in 0..=self.profiled().len() {
+            // i64, mutable, init: i64.const 0; end
+            out.extend_from_slice(&[0x7e, 0x01, 0x42, 0x00, 0x0b]);
+        }
+        out
+    }
+
+    /// Encodes the export section: the existing entries followed by our globals.
+    fn exports(&self, entries: &[u8], count: u32) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_leb(&mut out, count + 1 + self.profiled().len() as u32);
+        out.extend_from_slice(entries);
+        let mut export = |name: &str, global: u32| {
+            write_leb(&mut out, name.len() as u32);
+            out.extend_from_slice(name.as_bytes());
+            out.push(0x03);
+            write_leb(&mut out, global);
+        };
+        export(LAST_EXPORT, self.last);
+        for func_index in self.profiled() {
+            export(
+                &format!("{ACCUMULATOR_EXPORT_PREFIX}{func_index}"),
+                self.accumulator(func_index),
+            );
+        }
+        out
+    }
+
+    /// Rewrites a function body, flushing the gas charged by the function at each boundary.
+    fn rewrite_body(
+        &self,
+        wasm: &[u8],
+        body: &FunctionBody,
+        func_index: u32,
+        out: &mut Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let range = body.range();
+        if !self.profiled().contains(&func_index) {
+            write_leb(out, range.len() as u32);
+            out.extend_from_slice(&wasm[range]);
+            return Ok(());
+        }
+
+        let acc = self.accumulator(func_index);
+        let flush = |code: &mut Vec<u8>| {
+            // acc += last - gas; last = gas
+            global_get(code, acc);
+            global_get(code, self.last);
+            global_get(code, self.gas_counter);
+            code.push(0x7d); // i64.sub
+            code.push(0x7c); // i64.add
+            global_set(code, acc);
+            global_get(code, self.gas_counter);
+            global_set(code, self.last);
+        };
+        let reset = |code: &mut Vec<u8>| {
+            // last = gas
+            global_get(code, self.gas_counter);
+            global_set(code, self.last);
+        };
+
+        let mut ops = body.get_operators_reader()?;
+        // Copy the locals as-is.
+        let mut code = wasm[range.start..ops.original_position()].to_vec();
+        // The number of blocks we're nested in, i.e., the relative depth of the function's label.
+        let mut depth = 0u32;
+        while !ops.eof() {
+            let start = ops.original_position();
+            let op = ops.read()?;
+            let raw = &wasm[start..ops.original_position()];
+            match op {
+                Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                    depth += 1;
+                    code.extend_from_slice(raw);
+                }
+                Operator::End => {
+                    if depth == 0 {
+                        flush(&mut code);
+                    } else {
+                        depth -= 1;
+                    }
+                    code.extend_from_slice(raw);
+                }
+                Operator::Return => {
+                    flush(&mut code);
+                    code.extend_from_slice(raw);
+                }
+                Operator::Br { relative_depth } | Operator::BrIf { relative_depth } => {
+                    // Flushing is idempotent, so we can flush even if the branch isn't taken.
+                    if relative_depth == depth {
+                        flush(&mut code);
+                    }
+                    code.extend_from_slice(raw);
+                }
+                Operator::BrTable { targets } => {
+                    let exits = targets.default() == depth
+                        || targets.targets().any(|t| matches!(t, Ok(t) if t == depth));
+                    if exits {
+                        flush(&mut code);
+                    }
+                    code.extend_from_slice(raw);
+                }
+                Operator::Call { function_index } if function_index < self.profiled_funcs => {
+                    flush(&mut code);
+                    code.extend_from_slice(raw);
+                    reset(&mut code);
+                }
+                Operator::CallIndirect { .. } => {
+                    flush(&mut code);
+                    code.extend_from_slice(raw);
+                    reset(&mut code);
+                }
+                _ => code.extend_from_slice(raw),
+            }
+        }
+
+        write_leb(out, code.len() as u32);
+        out.extend_from_slice(&code);
+        Ok(())
+    }
+}
+
+/// Position of a (non-custom) section in the module, which differs from the section ID.
+fn section_order(id: u8) -> u8 {
+    match id {
+        // Tags go between memories and globals.
+        13 => 6,
+        6..=9 => id + 1,
+        // The data count section goes before the code section.
+        12 => 11,
+        10 | 11 => id + 2,
+        _ => id,
+    }
+}
+
+fn global_get(code: &mut Vec<u8>, index: u32) {
+    code.push(0x23);
+    write_leb(code, index);
+}
+
+fn global_set(code: &mut Vec<u8>, index: u32) {
+    code.push(0x24);
+    write_leb(code, index);
+}
+
+fn write_section(out: &mut Vec<u8>, id: u8, data: &[u8]) {
+    out.push(id);
+    write_leb(out, data.len() as u32);
+    out.extend_from_slice(data);
+}
+
+fn write_leb(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Splits a vector-like section into its entry count and the encoded entries.
+fn split_count(data: &[u8]) -> anyhow::Result<(u32, &[u8])> {
+    let mut value = 0u32;
+    for (i, byte) in data.iter().take(5).enumerate() {
+        value |= ((byte & 0x7f) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, &data[i + 1..]));
+        }
+    }
+    Err(anyhow!("invalid section entry count"))
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+    use wasmtime::{GlobalType, Mutability, ValType};
+
+    use super::*;
+
+    /// ```wat
+    /// (module
+    ///   (import "gas" "gas_counter" (global $gas (mut i64)))
+    ///   (func $a (export "a")
+    ///     (global.set $gas (i64.sub (global.get $gas) (i64.const 5)))
+    ///     (call $b))
+    ///   (func $b
+    ///     (global.set $gas (i64.sub (global.get $gas) (i64.const 3)))))
+    /// ```
+    const MODULE: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // types
+        0x02, 0x14, 0x01, 0x03, b'g', b'a', b's', 0x0b, b'g', b'a', b's', b'_', b'c', b'o', b'u',
+        b'n', b't', b'e', b'r', 0x03, 0x7e, 0x01, // imports
+        0x03, 0x03, 0x02, 0x00, 0x00, // functions
+        0x07, 0x05, 0x01, 0x01, b'a', 0x00, 0x00, // exports
+        0x0a, 0x17, 0x02, // code
+        0x0b, 0x00, 0x23, 0x00, 0x42, 0x05, 0x7d, 0x24, 0x00, 0x10, 0x01, 0x0b, // $a
+        0x09, 0x00, 0x23, 0x00, 0x42, 0x03, 0x7d, 0x24, 0x00, 0x0b, // $b
+        0x00, 0x0e, 0x04, b'n', b'a', b'm', b'e', 0x01, 0x07, 0x02, 0x00, 0x01, b'a', 0x01, 0x01,
+        b'b', // names
+    ];
+
+    /// ```wat
+    /// (module
+    ///   (func $a (export "a") (call $b) (call $b))
+    ///   (func $b (drop (i32.add (i32.const 1) (i32.const 2)))))
+    /// ```
+    const UNMETERED: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // types
+        0x03, 0x03, 0x02, 0x00, 0x00, // functions
+        0x07, 0x05, 0x01, 0x01, b'a', 0x00, 0x00, // exports
+        0x0a, 0x11, 0x02, // code
+        0x06, 0x00, 0x10, 0x01, 0x10, 0x01, 0x0b, // $a
+        0x08, 0x00, 0x41, 0x01, 0x41, 0x02, 0x6a, 0x1a, 0x0b, // $b
+        0x00, 0x0e, 0x04, b'n', b'a', b'm', b'e', 0x01, 0x07, 0x02, 0x00, 0x01, b'a', 0x01, 0x01,
+        b'b', // names
+    ];
+
+    #[test]
+    fn leb() {
+        for value in [0, 1, 0x7f, 0x80, 624485, u32::MAX] {
+            let mut out = Vec::new();
+            write_leb(&mut out, value);
+            out.push(0xff);
+            assert_eq!(split_count(&out).unwrap(), (value, &[0xff][..]));
+        }
+    }
+
+    #[test]
+    fn profile() {
+        assert_eq!(GAS_COUNTER_NAME, "gas_counter");
+        let (wasm, functions) = instrument(MODULE, MODULE).unwrap();
+        wasmparser::Validator::new().validate_all(&wasm).unwrap();
+
+        let engine = wasmtime::Engine::default();
+        let module = wasmtime::Module::new(&engine, &wasm).unwrap();
+        let mut store = wasmtime::Store::new(&engine, ());
+        let gas = Global::new(
+            &mut store,
+            GlobalType::new(ValType::I64, Mutability::Var),
+            Val::I64(100),
+        )
+        .unwrap();
+        let instance = Instance::new(&mut store, &module, &[gas.into()]).unwrap();
+
+        functions.start(&mut store, &instance, gas).unwrap();
+        instance
+            .get_typed_func::<(), ()>(&mut store, "a")
+            .unwrap()
+            .call(&mut store, ())
+            .unwrap();
+        let profile = functions.collect(&mut store, &instance, gas).unwrap();
+
+        assert_eq!(
+            profile,
+            WasmProfile {
+                functions: vec![
+                    FunctionGas {
+                        index: 0,
+                        name: Some("a".into()),
+                        gas: Gas::from_milligas(5),
+                    },
+                    FunctionGas {
+                        index: 1,
+                        name: Some("b".into()),
+                        gas: Gas::from_milligas(3),
+                    },
+                ],
+                unattributed: Gas::zero(),
+            }
+        );
+    }
+
+    #[test]
+    fn profile_metered() {
+        use fvm_shared::version::NetworkVersion;
+        use fvm_wasm_instrument::{gas_metering, stack_limiter};
+
+        // Instrument the module like the engine does: stack limiting and gas metering first.
+        let prices = &crate::gas::price_list_by_network_version(NetworkVersion::V21).wasm_rules;
+        let metered = stack_limiter::inject(UNMETERED, 1024).unwrap();
+        let metered = gas_metering::inject(&metered, prices, "gas").unwrap();
+        let (wasm, functions) = instrument(UNMETERED, &metered).unwrap();
+        wasmparser::Validator::new().validate_all(&wasm).unwrap();
+
+        const AVAILABLE: i64 = 1_000_000;
+        let engine = wasmtime::Engine::default();
+        let module = wasmtime::Module::new(&engine, &wasm).unwrap();
+        let mut store = wasmtime::Store::new(&engine, ());
+        let gas = Global::new(
+            &mut store,
+            GlobalType::new(ValType::I64, Mutability::Var),
+            Val::I64(AVAILABLE),
+        )
+        .unwrap();
+        let instance = Instance::new(&mut store, &module, &[gas.into()]).unwrap();
+
+        functions.start(&mut store, &instance, gas).unwrap();
+        instance
+            .get_typed_func::<(), ()>(&mut store, "a")
+            .unwrap()
+            .call(&mut store, ())
+            .unwrap();
+        let profile = functions.collect(&mut store, &instance, gas).unwrap();
+        let remaining = gas.get(&mut store).i64().unwrap();
+
+        // Only the original functions are profiled, and all the metered gas is accounted for.
+        let names: Vec<_> = profile
+            .functions
+            .iter()
+            .map(|f| f.name.as_deref())
+            .collect();
+        assert_eq!(names, [Some("a"), Some("b")]);
+        assert!(profile.functions.iter().all(|f| f.gas > Gas::zero()));
+        let attributed = profile
+            .functions
+            .iter()
+            .fold(profile.unattributed, |total, f| total + f.gas);
+        assert_eq!(
+            attributed,
+            Gas::from_milligas((AVAILABLE - remaining) as u64)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use cid::Cid;
+
+/// A snapshot of the metrics of an [`EnginePool`](super::EnginePool), shared by all of its
+/// [`Engine`](super::Engine)s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EngineStats {
+    /// The number of modules compiled (or deserialized) since the engine pool was created.
+    pub modules_compiled: u64,
+    /// The number of module lookups served from the module cache.
+    pub module_cache_hits: u64,
+    /// The number of module lookups that had to load the module.
+    pub module_cache_misses: u64,
+    /// The number of modules evicted from the module cache to stay within its limits.
+    pub modules_evicted: u64,
+    /// The modules currently in the module cache, by code CID.
+    pub modules: BTreeMap<Cid, ModuleStats>,
+    /// The number of Wasm instances currently in use, across all engines.
+    pub instances_in_use: u32,
+    /// The number of Wasm instances the pool was sized for.
+    pub instance_pool_size: u32,
+    /// The number of engines currently acquired.
+    pub engines_in_use: u32,
+    /// The maximum number of engines that can be acquired concurrently.
+    pub concurrency: u32,
+    /// The number of times acquiring an engine had to wait for another one to be released.
+    pub concurrency_waits: u64,
+    /// The total time spent waiting for engines to be released.
+    pub concurrency_wait_time: Duration,
+    /// The address space reserved for the linear memories of the instances, excluding guard pages.
+    pub memory_reserved: u64,
+}
+
+impl EngineStats {
+    /// The fraction of module lookups served from the cache, or `None` if there weren't any.
+    pub fn module_cache_hit_rate(&self) -> Option<f64> {
+        let lookups = self.module_cache_hits + self.module_cache_misses;
+        (lookups > 0).then(|| self.module_cache_hits as f64 / lookups as f64)
+    }
+}
+
+/// The metrics of a module in the module cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ModuleStats {
+    /// The byte size of the instrumented (or precompiled) code.
+    pub size: usize,
+    /// The time spent validating and instrumenting the Wasm code. Zero for precompiled modules.
+    pub instrumentation_time: Duration,
+    /// The time spent compiling the instrumented code, or deserializing precompiled code.
+    pub compile_time: Duration,
+}
+
+/// The counters updated while the engines are in use.
+#[derive(Default)]
+pub(super) struct Counters {
+    modules_compiled: AtomicU64,
+    module_cache_hits: AtomicU64,
+    module_cache_misses: AtomicU64,
+    modules_evicted: AtomicU64,
+}
+
+impl Counters {
+    pub fn record_compile(&self) {
+        self.modules_compiled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.module_cache_hits
+        } else {
+            &self.module_cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_evictions(&self, count: usize) {
+        self.modules_evicted
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Fills in the counters of a snapshot.
+    pub fn snapshot(&self, stats: &mut EngineStats) {
+        stats.modules_compiled = self.modules_compiled.load(Ordering::Relaxed);
+        stats.module_cache_hits = self.module_cache_hits.load(Ordering::Relaxed);
+        stats.module_cache_misses = self.module_cache_misses.load(Ordering::Relaxed);
+        stats.modules_evicted = self.modules_evicted.load(Ordering::Relaxed);
+    }
+}
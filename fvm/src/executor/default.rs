@@ -19,8 +19,10 @@ use super::{ApplyFailure, ApplyKind, ApplyRet, Executor};
 use crate::call_manager::{Backtrace, CallManager, Entrypoint, InvocationResult, backtrace};
 use crate::eam_actor::EAM_ACTOR_ID;
 use crate::engine::EnginePool;
-use crate::gas::{Gas, GasCharge, GasOutputs};
-use crate::kernel::{Block, ClassifyResult, Context as _, ExecutionError, Kernel};
+use crate::gas::{Gas, GasBreakdown, GasCharge, GasOutputs};
+use crate::kernel::{
+    Block, ClassifyResult, Context as _, DeadlineExceeded, ExecutionError, Kernel,
+};
 use crate::machine::{BURNT_FUNDS_ACTOR_ID, Machine, REWARD_ACTOR_ID};
 use crate::trace::ExecutionTrace;
 
@@ -233,6 +235,18 @@ where
                     None,
                 )
             }
+            Err(ExecutionError::Fatal(err)) if DeadlineExceeded::is_cause_of(&err) => {
+                // The execution time limit isn't part of consensus, so we can't produce a receipt.
+                // The machine's state must be discarded by the caller.
+                return Err(err.context(format!(
+                    "[from={}, to={}, seq={}, m={}, h={}]",
+                    msg.from,
+                    msg.to,
+                    msg.sequence,
+                    msg.method_num,
+                    self.context().epoch,
+                )));
+            }
             Err(ExecutionError::Fatal(err)) => {
                 // We produce a receipt with SYS_ASSERTION_FAILED exit code, and
                 // we consume the full gas amount so that, in case of a network-
@@ -269,7 +283,12 @@ where
             Some(ApplyFailure::MessageBacktrace(backtrace))
         };
 
-        match apply_kind {
+        let gas_breakdown = self
+            .context()
+            .tracing
+            .then(|| GasBreakdown::from_trace(sender_id, &exec_trace));
+
+        let mut ret = match apply_kind {
             ApplyKind::Explicit => self.finish_message(
                 sender_id,
                 msg,
@@ -279,8 +298,8 @@ where
                 exec_trace,
                 events,
                 return_codec,
-            ),
-            ApplyKind::Implicit => Ok(ApplyRet {
+            )?,
+            ApplyKind::Implicit => ApplyRet {
                 msg_receipt: receipt,
                 penalty: TokenAmount::zero(),
                 miner_tip: TokenAmount::zero(),
@@ -291,10 +310,14 @@ where
                 gas_burned: 0,
                 failure_info,
                 exec_trace,
+                gas_breakdown: None,
                 events,
                 return_codec,
-            }),
-        }
+            },
+        };
+        ret.gas_breakdown = gas_breakdown;
+
+        Ok(ret)
     }
 
     /// Flush the state-tree to the underlying blockstore.
@@ -313,14 +336,23 @@ where
         engine_pool: EnginePool,
         machine: <K::CallManager as CallManager>::Machine,
     ) -> anyhow::Result<Self> {
+        // Without Wasm interruption, the execution deadline wouldn't stop actors looping in Wasm.
+        if machine.context().execution_time_limit.is_some()
+            && !engine_pool.config().wasm_interruption
+        {
+            return Err(anyhow!(
+                "an execution time limit requires an engine pool with wasm interruption enabled"
+            ));
+        }
+
         // Skip preloading all builtin actors when testing.
         #[cfg(not(any(test, feature = "testing")))]
         {
-            // Preload any uncached modules.
+            // Preload any uncached modules, pinning them so they're never evicted.
             // This interface works for now because we know all actor CIDs
             // ahead of time, but with user-supplied code, we won't have that
             // guarantee.
-            engine_pool.acquire().preload_all(
+            engine_pool.acquire().preload_pinned(
                 machine.blockstore(),
                 machine.builtin_actors().builtin_actor_codes(),
             )?;
@@ -542,6 +574,7 @@ where
             gas_burned,
             failure_info,
             exec_trace,
+            gas_breakdown: None,
             events,
             return_codec,
         })
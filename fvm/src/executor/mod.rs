@@ -18,6 +18,7 @@ pub use threaded::ThreadedExecutor;
 
 use crate::Kernel;
 use crate::call_manager::Backtrace;
+use crate::gas::GasBreakdown;
 use crate::trace::ExecutionTrace;
 
 /// An executor executes messages on the underlying machine/kernel. It's responsible for:
@@ -35,6 +36,10 @@ pub trait Executor {
     ///
     /// NOTE: The "raw length" is the length of the message as it appears on-chain and is used to
     /// charge message inclusion gas.
+    ///
+    /// If the machine has an execution time limit and the message exceeds it, this fails with a
+    /// [`DeadlineExceeded`](crate::kernel::DeadlineExceeded) error and the machine's state must be
+    /// discarded.
     fn execute_message(
         &mut self,
         msg: Message,
@@ -91,6 +96,9 @@ pub struct ApplyRet {
     pub failure_info: Option<ApplyFailure>,
     /// Execution trace information, for debugging.
     pub exec_trace: ExecutionTrace,
+    /// The gas charged while executing the message, by category and by actor. Only computed when
+    /// tracing is enabled.
+    pub gas_breakdown: Option<GasBreakdown>,
     /// Events generated while applying the message.
     pub events: Vec<StampedEvent>,
     /// The IPLD codec of the return data, if any.
@@ -120,6 +128,7 @@ impl ApplyRet {
             gas_burned: 0,
             failure_info: Some(ApplyFailure::PreValidation(message.into())),
             exec_trace: vec![],
+            gas_breakdown: None,
             events: vec![],
             return_codec: None,
         }
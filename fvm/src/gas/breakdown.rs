@@ -0,0 +1,185 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::BTreeMap;
+
+use fvm_shared::ActorID;
+use serde::{Deserialize, Serialize};
+
+use super::{Gas, GasCategory, GasCharge};
+use crate::trace::ExecutionEvent;
+
+/// Gas charged, split into immediate computation and everything else (storage, memory retention,
+/// deferred computation, etc., see [`GasCharge::other_gas`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasAmount {
+    pub compute: Gas,
+    pub other: Gas,
+}
+
+impl GasAmount {
+    /// The sum of the compute and other gas.
+    pub fn total(&self) -> Gas {
+        self.compute + self.other
+    }
+
+    fn add_charge(&mut self, charge: &GasCharge) {
+        self.compute += charge.compute_gas;
+        self.other += charge.other_gas;
+    }
+}
+
+/// The gas charged while executing a message, aggregated by [`GasCategory`] and by actor.
+///
+/// This is computed from the gas charges in the execution trace, so it includes the charges that
+/// ran out of gas in full and may exceed the gas actually used by the message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasBreakdown {
+    /// The gas of all charges.
+    pub total: GasAmount,
+    /// The gas charged per category.
+    pub by_category: BTreeMap<GasCategory, GasAmount>,
+    /// The gas charged per actor. Charges are attributed to the actor executing when they were
+    /// made, and the charges for sending a message to an actor are attributed to the caller. The
+    /// message inclusion and return value charges are attributed to the sender of the message.
+    pub by_actor: BTreeMap<ActorID, GasAmount>,
+}
+
+impl GasBreakdown {
+    /// Aggregate the gas charges of an execution trace of a message sent by `sender`.
+    pub fn from_trace<'a>(
+        sender: ActorID,
+        trace: impl IntoIterator<Item = &'a ExecutionEvent>,
+    ) -> Self {
+        let mut breakdown = GasBreakdown::default();
+
+        // The stack of calls, with the actor each call was made by and, once invoked, the actor
+        // executing it.
+        let mut calls: Vec<(ActorID, Option<ActorID>)> = Vec::new();
+
+        for event in trace {
+            match event {
+                ExecutionEvent::GasCharge(charge) => {
+                    let actor = match calls.last() {
+                        Some((caller, callee)) => callee.unwrap_or(*caller),
+                        None => sender,
+                    };
+                    breakdown.total.add_charge(charge);
+                    breakdown
+                        .by_category
+                        .entry(charge.category)
+                        .or_default()
+                        .add_charge(charge);
+                    breakdown
+                        .by_actor
+                        .entry(actor)
+                        .or_default()
+                        .add_charge(charge);
+                }
+                ExecutionEvent::Call { from, .. } => calls.push((*from, None)),
+                ExecutionEvent::InvokeActor { id, .. } => {
+                    if let Some((_, callee)) = calls.last_mut() {
+                        *callee = Some(*id);
+                    }
+                }
+                ExecutionEvent::CallReturn(..) | ExecutionEvent::CallError(_) => {
+                    calls.pop();
+                }
+                _ => {}
+            }
+        }
+
+        breakdown
+    }
+
+    /// The gas charged for a category.
+    pub fn category(&self, category: GasCategory) -> GasAmount {
+        self.by_category.get(&category).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_shared::address::Address;
+    use fvm_shared::econ::TokenAmount;
+    use fvm_shared::error::ExitCode;
+    use fvm_shared::state::ActorState;
+    use fvm_shared::version::NetworkVersion;
+    use num_traits::Zero;
+
+    use super::*;
+    use crate::gas::price_list_by_network_version;
+    use crate::kernel::SupportedHashes;
+
+    #[test]
+    fn aggregates_by_category_and_actor() {
+        let pl = price_list_by_network_version(NetworkVersion::V21);
+        let call = |from| ExecutionEvent::Call {
+            from,
+            to: Address::new_id(0),
+            method: 1,
+            params: None,
+            value: TokenAmount::default(),
+            gas_limit: 0,
+            read_only: false,
+        };
+        let invoke = |id| ExecutionEvent::InvokeActor {
+            id,
+            state: ActorState::new_empty(Default::default(), None),
+        };
+        let charge = |c: GasCharge| ExecutionEvent::GasCharge(c);
+        let wasm = || {
+            GasCharge::new("wasm_exec", Gas::new(10), Gas::zero()).with_category(GasCategory::Wasm)
+        };
+
+        let inclusion = pl.on_chain_message(100);
+        let transfer = pl.on_value_transfer();
+        let link = pl.on_block_link(SupportedHashes::Blake2b256, 100);
+        let trace = vec![
+            charge(inclusion.clone()),
+            call(100),
+            charge(transfer.clone()),
+            invoke(200),
+            charge(wasm()),
+            call(200),
+            charge(transfer.clone()),
+            invoke(300),
+            charge(link.clone()),
+            ExecutionEvent::CallReturn(ExitCode::OK, None),
+            charge(wasm()),
+            ExecutionEvent::CallReturn(ExitCode::OK, None),
+        ];
+
+        let breakdown = GasBreakdown::from_trace(100, &trace);
+
+        assert_eq!(
+            breakdown.total.total(),
+            inclusion.total() + transfer.total() * 2u32 + link.total() + Gas::new(20)
+        );
+
+        assert_eq!(
+            breakdown.category(GasCategory::Wasm),
+            GasAmount {
+                compute: Gas::new(20),
+                other: Gas::zero()
+            }
+        );
+        assert_eq!(
+            breakdown.category(GasCategory::Ipld),
+            GasAmount {
+                compute: link.compute_gas,
+                other: link.other_gas
+            }
+        );
+        assert_eq!(breakdown.category(GasCategory::Proof), GasAmount::default());
+
+        assert_eq!(
+            breakdown.by_actor[&100].total(),
+            inclusion.total() + transfer.total()
+        );
+        assert_eq!(
+            breakdown.by_actor[&200].total(),
+            transfer.total() + Gas::new(20)
+        );
+        assert_eq!(breakdown.by_actor[&300].total(), link.total());
+    }
+}
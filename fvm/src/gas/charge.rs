@@ -4,6 +4,8 @@
 
 use std::borrow::Cow;
 
+use serde::{Deserialize, Serialize};
+
 use super::Gas;
 use super::timer::GasDuration;
 
@@ -12,6 +14,9 @@ use super::timer::GasDuration;
 #[derive(Clone, Debug)]
 pub struct GasCharge {
     pub name: Cow<'static, str>,
+    /// What the gas was charged for, set by the [`PriceList`](super::PriceList) method that
+    /// produced the charge.
+    pub category: GasCategory,
     /// Gas charged for immediate computation.
     pub compute_gas: Gas,
 
@@ -47,15 +52,51 @@ impl GasCharge {
         let name = name.into();
         Self {
             name,
+            category: GasCategory::Other,
             compute_gas,
             other_gas,
             elapsed: GasDuration::default(),
         }
     }
 
+    /// Sets the category of the charge.
+    pub fn with_category(mut self, category: GasCategory) -> Self {
+        self.category = category;
+        self
+    }
+
     /// Calculates total gas charge (in milligas) by summing compute and
     /// storage gas associated with this charge.
     pub fn total(&self) -> Gas {
         self.compute_gas + self.other_gas
     }
 }
+
+/// A coarse classification of gas charges, for reporting where the gas of a message went.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum GasCategory {
+    /// Including the message on chain, and storing its return value.
+    Message,
+    /// Call overhead: sends, value transfers, method invocations, return values, and syscalls.
+    Call,
+    /// Wasm execution, including memory and table initialization and growth.
+    Wasm,
+    /// State-tree access: looking up, creating, updating and deleting actors, resolving addresses,
+    /// and installing actor code.
+    State,
+    /// Opening, reading, creating and linking IPLD blocks, and getting and setting state roots.
+    Ipld,
+    /// Signature verification and public key recovery.
+    Signature,
+    /// Hashing and UTF-8 validation.
+    Hashing,
+    /// Proof verification.
+    Proof,
+    /// Emitting actor events.
+    Event,
+    /// Randomness, tipset CIDs, and the network and message contexts.
+    Chain,
+    /// Anything else, e.g., gas charged by actors themselves.
+    Other,
+}
@@ -5,16 +5,23 @@
 use std::cell::{Cell, RefCell};
 use std::fmt::{Debug, Display};
 use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use std::time::Instant;
 
 use anyhow::Context;
 use num_traits::Zero;
+use serde::{Deserialize, Serialize};
 
-pub use self::charge::GasCharge;
+pub use self::breakdown::{GasAmount, GasBreakdown};
+pub use self::charge::{GasCategory, GasCharge};
 pub use self::outputs::GasOutputs;
-pub use self::price_list::{PriceList, WasmGasPrices, price_list_by_network_version};
+pub use self::price_list::{
+    PriceList, PriceListBuilder, ScalingCost, Step, StepCost, WasmGasPrices, WasmGasPricesBuilder,
+    price_list_by_network_version, try_price_list_by_network_version,
+};
 pub use self::timer::{GasDuration, GasInstant, GasTimer};
-use crate::kernel::{ClassifyResult, ExecutionError, Result};
+use crate::kernel::{ClassifyResult, DeadlineExceeded, ExecutionError, Result};
 
+mod breakdown;
 mod charge;
 mod outputs;
 mod price_list;
@@ -28,7 +35,10 @@ pub const MILLIGAS_PRECISION: u64 = 1000;
 /// - Enforces correct units by making it impossible to, e.g., get gas squared (by multiplying gas
 ///   by gas).
 /// - Makes it harder to confuse gas and milligas.
-#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Default)]
+///
+/// Serializes as an integer number of milligas.
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Gas(u64 /* milligas */);
 
 impl Debug for Gas {
@@ -169,6 +179,7 @@ pub struct GasTracker {
     gas_used: Cell<Gas>,
     gas_snapshots: Vec<GasSnapshot>,
     trace: Option<RefCell<Vec<GasCharge>>>,
+    deadline: Option<Instant>,
 }
 
 impl GasTracker {
@@ -186,10 +197,27 @@ impl GasTracker {
             gas_used: Cell::new(gas_used),
             gas_snapshots: Vec::new(),
             trace: enable_tracing.then_some(Default::default()),
+            deadline: None,
         }
     }
 
+    /// Sets a wall-clock deadline after which all gas charges fail with a fatal
+    /// [`DeadlineExceeded`] error. Not deterministic, so it must never be set when executing
+    /// messages on-chain.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Getter for the wall-clock deadline, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
     fn charge_gas_inner(&self, to_use: Gas) -> Result<()> {
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            log::trace!("execution deadline exceeded");
+            return Err(ExecutionError::Fatal(DeadlineExceeded.into()));
+        }
         // The gas type uses saturating math.
         let gas_used = self.gas_used.get() + to_use;
         if gas_used > self.gas_limit {
@@ -208,7 +236,8 @@ impl GasTracker {
         log::trace!("charging gas: {} {}", name, to_use);
         let res = self.charge_gas_inner(to_use);
         if let Some(trace) = &self.trace {
-            let mut charge = GasCharge::new(name.to_owned(), to_use, Gas::zero());
+            let mut charge = GasCharge::new(name.to_owned(), to_use, Gas::zero())
+                .with_category(categorize_named_charge(name));
             let timer = GasTimer::new(&mut charge.elapsed);
             trace.borrow_mut().push(charge);
             res.map(|_| timer)
@@ -278,6 +307,18 @@ impl GasTracker {
     }
 }
 
+/// Categorizes the charges made by name rather than through the price list, i.e., by the Wasm
+/// runtime and by actors.
+fn categorize_named_charge(name: &str) -> GasCategory {
+    match name {
+        "wasm_exec" | "wasm_memory_grow" | "wasm_memory_init" | "wasm_table_init" => {
+            GasCategory::Wasm
+        }
+        "OnSyscall" => GasCategory::Call,
+        _ => GasCategory::Other,
+    }
+}
+
 /// Converts the specified fractional gas units into gas units
 #[inline]
 pub(crate) const fn milligas_to_gas(milligas: u64, round_up: bool) -> u64 {
@@ -309,6 +350,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn gas_tracker_deadline() {
+        let mut t = GasTracker::new(Gas::new(20), Gas::zero(), false);
+        t.set_deadline(Instant::now() + std::time::Duration::from_secs(3600));
+        assert!(
+            t.apply_charge(GasCharge::new("", Gas::new(5), Gas::zero()))
+                .is_ok()
+        );
+
+        t.set_deadline(Instant::now());
+        match t.apply_charge(GasCharge::new("", Gas::new(5), Gas::zero())) {
+            Err(ExecutionError::Fatal(e)) => assert!(DeadlineExceeded::is_cause_of(&e)),
+            other => panic!("expected the deadline to be exceeded, got {other:?}"),
+        }
+        // Nothing is charged once the deadline has passed.
+        assert_eq!(t.gas_used(), Gas::new(5));
+    }
+
     #[test]
     fn milligas_to_gas_round() {
         assert_eq!(milligas_to_gas(100, false), 0);
@@ -19,8 +19,9 @@ use fvm_shared::version::NetworkVersion;
 use fvm_wasm_instrument::gas_metering::{InstructionCost, Operator, Rules};
 use lazy_static::lazy_static;
 use num_traits::Zero;
+use serde::{Deserialize, Serialize};
 
-use super::GasCharge;
+use super::{GasCategory, GasCharge};
 use crate::gas::Gas;
 use crate::kernel::SupportedHashes;
 
@@ -114,6 +115,13 @@ lazy_static! {
                     flat: Gas::new(16598605),
                     scale: Gas::new(26),
                 },
+                // Provisional, not calibrated yet: recovering the key (`secp256k1_recover_cost`)
+                // and hashing it into an eth address (64 bytes of keccak), plus keccak over the
+                // plaintext (see `hashing_cost`).
+                Delegated => ScalingCost {
+                    flat: Gas::new(1637292 + 64 * 33),
+                    scale: Gas::new(33),
+                },
             }
         },
         secp256k1_recover_cost: Gas::new(1637292),
@@ -310,6 +318,10 @@ lazy_static! {
         ipld_cbor_scan_per_field: Gas::new(35),
         ipld_link_tracked: Gas::new(300),
         ipld_link_checked: Gas::new(300),
+
+        // Provisional, not calibrated yet: releasing a registry slot and freeing the block is
+        // comparable to tracking a link.
+        block_close: Gas::new(300),
     };
 
     static ref TEEP_PRICES: PriceList = PriceList {
@@ -396,8 +408,10 @@ lazy_static! {
     };
 }
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
-pub(crate) struct ScalingCost {
+/// A cost with a flat component and a component scaling linearly with some value (usually a size
+/// in bytes).
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScalingCost {
     pub flat: Gas,
     pub scale: Gas,
 }
@@ -428,16 +442,23 @@ impl ScalingCost {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub(crate) struct StepCost(Vec<Step>);
+/// A step function: the cost of a value is the cost of the last step starting at or before it, or
+/// zero if there is no such step. Steps must be sorted by `start`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StepCost(Vec<Step>);
 
-#[derive(Clone, Debug, Copy, PartialEq, Eq)]
-pub(crate) struct Step {
-    start: u64,
-    cost: Gas,
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub start: u64,
+    pub cost: Gas,
 }
 
 impl StepCost {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self(steps)
+    }
+
     pub(crate) fn lookup(&self, x: u64) -> Gas {
         self.0
             .iter()
@@ -450,7 +471,11 @@ impl StepCost {
 
 /// Provides prices for operations in the VM.
 /// All costs are in milligas.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// Custom price lists can be derived from an existing one with [`PriceList::builder`], or
+/// deserialized from any serde format. Maps are serialized as lists of `[key, value]` pairs so
+/// they can be represented in formats that only support string keys, like TOML.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PriceList {
     /// Gas cost charged to the originator of an on-chain message (regardless of
     /// whether it succeeds or fails in application) is given by:
@@ -488,6 +513,7 @@ pub struct PriceList {
 
     /// Gas cost for verifying a cryptographic signature.
     #[cfg(feature = "verify-signature")]
+    #[serde(with = "entries")]
     pub(crate) sig_cost: HashMap<SignatureType, ScalingCost>,
 
     /// Gas cost for recovering secp256k1 signer public key
@@ -496,6 +522,7 @@ pub struct PriceList {
     pub(crate) bls_pairing_cost: Gas,
     pub(crate) bls_hashing_cost: ScalingCost,
 
+    #[serde(with = "entries")]
     pub(crate) hashing_cost: HashMap<SupportedHashes, ScalingCost>,
 
     /// Gas cost for walking up the chain.
@@ -504,9 +531,12 @@ pub struct PriceList {
 
     pub(crate) compute_unsealed_sector_cid_base: Gas,
     pub(crate) verify_seal_base: Gas,
+    #[serde(with = "entries")]
     pub(crate) verify_aggregate_seal_per: HashMap<RegisteredSealProof, Gas>,
+    #[serde(with = "entries")]
     pub(crate) verify_aggregate_seal_steps: HashMap<RegisteredSealProof, StepCost>,
 
+    #[serde(with = "entries")]
     pub(crate) verify_post_lookup: HashMap<RegisteredPoStProof, ScalingCost>,
     pub(crate) verify_consensus_fault: Gas,
     pub(crate) verify_replica_update: Gas,
@@ -568,9 +598,12 @@ pub struct PriceList {
 
     /// Gas cost for checking if CID is reachable.
     pub(crate) ipld_link_checked: Gas,
+
+    /// Gas cost for closing a block, releasing it from the block registry.
+    pub(crate) block_close: Gas,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct WasmGasPrices {
     /// The default gas cost for instructions.
     pub(crate) instruction_default: Gas,
@@ -626,19 +659,21 @@ impl PriceList {
             self.on_chain_message_compute.apply(msg_size),
             self.actor_update + self.on_chain_message_storage.apply(msg_size),
         )
+        .with_category(GasCategory::Message)
     }
 
     /// Returns the gas required when invoking a method.
     #[inline]
     pub fn on_value_transfer(&self) -> GasCharge {
         GasCharge::new("OnValueTransfer", self.send_transfer_funds, Zero::zero())
+            .with_category(GasCategory::Call)
     }
 
     /// Returns the gas required when invoking a method.
     #[inline]
     pub fn on_method_invocation(&self, _param_size: u32, param_links: usize) -> GasCharge {
         let charge = self.send_invoke_method + self.ipld_link_tracked * param_links;
-        GasCharge::new("OnMethodInvocation", charge, Zero::zero())
+        GasCharge::new("OnMethodInvocation", charge, Zero::zero()).with_category(GasCategory::Call)
     }
 
     /// Returns the gas required for returning a value from a method. At the top-level, this charges
@@ -656,12 +691,14 @@ impl PriceList {
                 self.on_chain_return_compute.apply(return_size),
                 self.on_chain_return_storage.apply(return_size),
             )
+            .with_category(GasCategory::Message)
         } else {
             GasCharge::new(
                 "OnReturnValue",
                 self.ipld_link_tracked * return_links,
                 Zero::zero(),
             )
+            .with_category(GasCategory::Call)
         }
     }
 
@@ -673,13 +710,14 @@ impl PriceList {
         if new_address {
             gas += self.address_assignment + self.address_lookup;
         }
-        GasCharge::new("OnCreateActor", Zero::zero(), gas)
+        GasCharge::new("OnCreateActor", Zero::zero(), gas).with_category(GasCategory::State)
     }
 
     /// Returns the gas required for deleting an actor.
     #[inline]
     pub fn on_delete_actor(&self) -> GasCharge {
         GasCharge::new("OnDeleteActor", Zero::zero(), Zero::zero())
+            .with_category(GasCategory::State)
     }
 
     /// Returns gas required for signature verification.
@@ -688,7 +726,7 @@ impl PriceList {
     pub fn on_verify_signature(&self, sig_type: SignatureType, data_len: usize) -> GasCharge {
         let cost = self.sig_cost[&sig_type];
         let gas = cost.apply(data_len);
-        GasCharge::new("OnVerifySignature", gas, Zero::zero())
+        GasCharge::new("OnVerifySignature", gas, Zero::zero()).with_category(GasCategory::Signature)
     }
 
     /// Returns gas required for BLS aggregate signature verification.
@@ -711,6 +749,7 @@ impl PriceList {
             gas_pairings + gas_hashing,
             Zero::zero(),
         )
+        .with_category(GasCategory::Signature)
     }
 
     /// Returns gas required for recovering signer pubkey from signature
@@ -721,6 +760,7 @@ impl PriceList {
             self.secp256k1_recover_cost,
             Zero::zero(),
         )
+        .with_category(GasCategory::Signature)
     }
 
     /// Returns gas required for hashing data.
@@ -728,7 +768,7 @@ impl PriceList {
     pub fn on_hashing(&self, hasher: SupportedHashes, data_len: usize) -> GasCharge {
         let cost = self.hashing_cost[&hasher];
         let gas = cost.apply(data_len);
-        GasCharge::new("OnHashing", gas, Zero::zero())
+        GasCharge::new("OnHashing", gas, Zero::zero()).with_category(GasCategory::Hashing)
     }
 
     #[inline]
@@ -738,6 +778,7 @@ impl PriceList {
             self.utf8_validation.apply(len),
             Zero::zero(),
         )
+        .with_category(GasCategory::Hashing)
     }
 
     /// Returns gas required for computing unsealed sector Cid.
@@ -752,12 +793,14 @@ impl PriceList {
             self.compute_unsealed_sector_cid_base,
             Zero::zero(),
         )
+        .with_category(GasCategory::Proof)
     }
 
     /// Returns gas required for seal verification.
     #[inline]
     pub fn on_verify_seal(&self, _info: &SealVerifyInfo) -> GasCharge {
         GasCharge::new("OnVerifySeal", self.verify_seal_base, Zero::zero())
+            .with_category(GasCategory::Proof)
     }
     #[inline]
     pub fn on_verify_aggregate_seals(
@@ -793,6 +836,7 @@ impl PriceList {
             per_proof * num + step.lookup(num),
             Zero::zero(),
         )
+        .with_category(GasCategory::Proof)
     }
 
     /// Returns gas required for replica verification.
@@ -803,6 +847,7 @@ impl PriceList {
             self.verify_replica_update,
             Zero::zero(),
         )
+        .with_category(GasCategory::Proof)
     }
 
     /// Returns gas required for PoSt verification.
@@ -821,7 +866,7 @@ impl PriceList {
 
         let gas_used = cost.apply(info.challenged_sectors.len());
 
-        GasCharge::new("OnVerifyPost", gas_used, Zero::zero())
+        GasCharge::new("OnVerifyPost", gas_used, Zero::zero()).with_category(GasCategory::Proof)
     }
 
     /// Returns gas required for verifying consensus fault.
@@ -837,6 +882,7 @@ impl PriceList {
             Zero::zero(),
             self.verify_consensus_fault,
         )
+        .with_category(GasCategory::Proof)
     }
 
     /// Returns the cost of the gas required for getting randomness from the client with the given lookback.
@@ -847,6 +893,7 @@ impl PriceList {
             Zero::zero(),
             self.lookback_cost.apply(lookback as u64),
         )
+        .with_category(GasCategory::Chain)
     }
 
     /// Returns the base gas required for loading an object, independent of the object's size.
@@ -857,6 +904,7 @@ impl PriceList {
             self.ipld_link_checked,
             self.block_open.flat,
         )
+        .with_category(GasCategory::Ipld)
     }
 
     /// Returns the gas required for loading an object based on the size of the object.
@@ -877,6 +925,7 @@ impl PriceList {
             // We charge the `block_open` fee as "extra" to make sure the FVM benchmarks still work.
             block_open + retention_surcharge,
         )
+        .with_category(GasCategory::Ipld)
     }
 
     /// Returns the gas required for reading a loaded object.
@@ -887,6 +936,7 @@ impl PriceList {
             self.block_memcpy.apply(data_size),
             Zero::zero(),
         )
+        .with_category(GasCategory::Ipld)
     }
 
     /// Returns the gas required for adding an object to the FVM cache.
@@ -902,6 +952,7 @@ impl PriceList {
         let retention_surcharge = (retention_min - compute).max(Gas::zero());
 
         GasCharge::new("OnBlockCreate", compute, retention_surcharge)
+            .with_category(GasCategory::Ipld)
     }
 
     /// Returns the gas required for committing an object to the state blockstore.
@@ -924,18 +975,27 @@ impl PriceList {
         let deferred_compute = self.block_persist_compute;
 
         GasCharge::new("OnBlockLink", initial_compute, deferred_compute + storage)
+            .with_category(GasCategory::Ipld)
     }
 
     /// Returns the gas required for storing an object.
     #[inline]
     pub fn on_block_stat(&self) -> GasCharge {
-        GasCharge::new("OnBlockStat", Zero::zero(), Zero::zero())
+        GasCharge::new("OnBlockStat", Zero::zero(), Zero::zero()).with_category(GasCategory::Ipld)
+    }
+
+    /// Returns the gas required for closing a block.
+    #[inline]
+    pub fn on_block_close(&self) -> GasCharge {
+        GasCharge::new("OnBlockClose", self.block_close, Zero::zero())
+            .with_category(GasCategory::Ipld)
     }
 
     /// Returns the gas required to lookup an actor in the state-tree.
     #[inline]
     pub fn on_actor_lookup(&self) -> GasCharge {
         GasCharge::new("OnActorLookup", Zero::zero(), self.actor_lookup)
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required to update an actor in the state-tree. Assumes that the actor lookup
@@ -943,6 +1003,7 @@ impl PriceList {
     #[inline]
     pub fn on_actor_update(&self) -> GasCharge {
         GasCharge::new("OnActorUpdate", Zero::zero(), self.actor_update)
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required to create a new actor in the state-tree. Assumes that the actor
@@ -950,18 +1011,20 @@ impl PriceList {
     #[inline]
     pub fn on_actor_create(&self) -> GasCharge {
         GasCharge::new("OnActorCreate", Zero::zero(), self.actor_create_storage)
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for accessing the balance of the current actor.
     #[inline]
     pub fn on_self_balance(&self) -> GasCharge {
         GasCharge::new("OnSelfBalance", Zero::zero(), Zero::zero())
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for accessing the balance of an actor.
     #[inline]
     pub fn on_balance_of(&self) -> GasCharge {
-        GasCharge::new("OnBalanceOf", Zero::zero(), Zero::zero())
+        GasCharge::new("OnBalanceOf", Zero::zero(), Zero::zero()).with_category(GasCategory::State)
     }
 
     /// Returns the gas required for resolving an actor address.
@@ -970,12 +1033,14 @@ impl PriceList {
     #[inline]
     pub fn on_resolve_address(&self) -> GasCharge {
         GasCharge::new("OnResolveAddress", Zero::zero(), Zero::zero())
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for looking up an actor's delegated address.
     #[inline]
     pub fn on_lookup_delegated_address(&self) -> GasCharge {
         GasCharge::new("OnLookupAddress", Zero::zero(), Zero::zero())
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for getting the CID of the code of an actor.
@@ -984,6 +1049,7 @@ impl PriceList {
     #[inline]
     pub fn on_get_actor_code_cid(&self) -> GasCharge {
         GasCharge::new("OnGetActorCodeCid", Zero::zero(), Zero::zero())
+            .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for looking up the type of a builtin actor by CID.
@@ -994,6 +1060,7 @@ impl PriceList {
             self.builtin_actor_manifest_lookup,
             Zero::zero(),
         )
+        .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for looking up the CID of a builtin actor by type.
@@ -1004,6 +1071,7 @@ impl PriceList {
             self.builtin_actor_manifest_lookup,
             Zero::zero(),
         )
+        .with_category(GasCategory::State)
     }
 
     /// Returns the gas required for looking up a tipset CID with the given lookback.
@@ -1014,18 +1082,21 @@ impl PriceList {
             Zero::zero(),
             self.lookback_cost.apply(lookback as u64),
         )
+        .with_category(GasCategory::Chain)
     }
 
     /// Returns the gas required for accessing the network context.
     #[inline]
     pub fn on_network_context(&self) -> GasCharge {
         GasCharge::new("OnNetworkContext", self.network_context, Zero::zero())
+            .with_category(GasCategory::Chain)
     }
 
     /// Returns the gas required for accessing the message context.
     #[inline]
     pub fn on_message_context(&self) -> GasCharge {
         GasCharge::new("OnMessageContext", self.message_context, Zero::zero())
+            .with_category(GasCategory::Chain)
     }
 
     /// Returns the gas required for installing an actor.
@@ -1035,6 +1106,7 @@ impl PriceList {
             self.install_wasm_per_byte_cost * wasm_size,
             Zero::zero(),
         )
+        .with_category(GasCategory::State)
     }
 
     #[inline]
@@ -1066,31 +1138,260 @@ impl PriceList {
             // one copy into the AMT, one copy to the client.
             hash + mem,
         )
+        .with_category(GasCategory::Event)
     }
 
     #[inline]
     pub fn on_get_root(&self) -> GasCharge {
         GasCharge::new("OnActorGetRoot", self.ipld_link_tracked, Gas::zero())
+            .with_category(GasCategory::Ipld)
     }
 
     #[inline]
     pub fn on_set_root(&self) -> GasCharge {
         GasCharge::new("OnActorSetRoot", self.ipld_link_checked, Gas::zero())
+            .with_category(GasCategory::Ipld)
+    }
+}
+
+/// Generates a setter for each of the listed fields of the struct wrapped by a builder.
+macro_rules! setters {
+    ($($(#[$attr:meta])* $field:ident: $ty:ty),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            #[doc = concat!("Overrides `", stringify!($field), "`.")]
+            pub fn $field(mut self, value: $ty) -> Self {
+                self.0.$field = value;
+                self
+            }
+        )+
+    };
+}
+
+impl PriceList {
+    /// Returns a builder for a custom price list, starting from this one.
+    ///
+    /// ```
+    /// use fvm::gas::{Gas, ScalingCost, price_list_by_network_version};
+    /// use fvm_shared::version::NetworkVersion;
+    ///
+    /// let base = price_list_by_network_version(NetworkVersion::V25);
+    /// let custom = base
+    ///     .builder()
+    ///     .send_invoke_method(Gas::new(100_000))
+    ///     .event_per_entry(ScalingCost::fixed(Gas::new(3000)))
+    ///     .build();
+    /// assert_ne!(&custom, base);
+    /// ```
+    pub fn builder(&self) -> PriceListBuilder {
+        PriceListBuilder(self.clone())
+    }
+}
+
+/// Builds a custom [`PriceList`] by overriding individual costs of an existing one. See
+/// [`PriceList::builder`].
+#[derive(Clone, Debug)]
+pub struct PriceListBuilder(PriceList);
+
+impl PriceListBuilder {
+    setters! {
+        on_chain_message_compute: ScalingCost,
+        on_chain_message_storage: ScalingCost,
+        on_chain_return_compute: ScalingCost,
+        on_chain_return_storage: ScalingCost,
+        send_transfer_funds: Gas,
+        send_invoke_method: Gas,
+        address_lookup: Gas,
+        address_assignment: Gas,
+        actor_lookup: Gas,
+        actor_update: Gas,
+        actor_create_storage: Gas,
+        secp256k1_recover_cost: Gas,
+        bls_pairing_cost: Gas,
+        bls_hashing_cost: ScalingCost,
+        lookback_cost: ScalingCost,
+        compute_unsealed_sector_cid_base: Gas,
+        verify_seal_base: Gas,
+        verify_consensus_fault: Gas,
+        verify_replica_update: Gas,
+        block_memcpy: ScalingCost,
+        block_allocate: ScalingCost,
+        block_memory_retention_minimum: ScalingCost,
+        block_open: ScalingCost,
+        block_persist_storage: ScalingCost,
+        block_persist_compute: Gas,
+        wasm_rules: WasmGasPrices,
+        event_per_entry: ScalingCost,
+        builtin_actor_manifest_lookup: Gas,
+        utf8_validation: ScalingCost,
+        network_context: Gas,
+        message_context: Gas,
+        install_wasm_per_byte_cost: Gas,
+        preloaded_actors: Vec<ActorID>,
+        ipld_cbor_scan_per_field: Gas,
+        ipld_cbor_scan_per_cid: Gas,
+        ipld_link_tracked: Gas,
+        ipld_link_checked: Gas,
+        block_close: Gas,
+    }
+
+    /// Overrides the cost of verifying a signature of the given type.
+    #[cfg(feature = "verify-signature")]
+    pub fn sig_cost(mut self, sig_type: SignatureType, cost: ScalingCost) -> Self {
+        self.0.sig_cost.insert(sig_type, cost);
+        self
+    }
+
+    /// Overrides the cost of hashing data with the given hash function.
+    pub fn hashing_cost(mut self, hasher: SupportedHashes, cost: ScalingCost) -> Self {
+        self.0.hashing_cost.insert(hasher, cost);
+        self
+    }
+
+    /// Overrides the per-proof cost of verifying an aggregate seal of the given proof type.
+    pub fn verify_aggregate_seal_per(mut self, proof: RegisteredSealProof, cost: Gas) -> Self {
+        self.0.verify_aggregate_seal_per.insert(proof, cost);
+        self
+    }
+
+    /// Overrides the step cost of verifying an aggregate seal of the given proof type, by number
+    /// of aggregated proofs.
+    pub fn verify_aggregate_seal_steps(
+        mut self,
+        proof: RegisteredSealProof,
+        cost: StepCost,
+    ) -> Self {
+        self.0.verify_aggregate_seal_steps.insert(proof, cost);
+        self
+    }
+
+    /// Overrides the cost of verifying a PoSt of the given proof type, by number of challenged
+    /// sectors.
+    pub fn verify_post_lookup(mut self, proof: RegisteredPoStProof, cost: ScalingCost) -> Self {
+        self.0.verify_post_lookup.insert(proof, cost);
+        self
+    }
+
+    pub fn build(self) -> PriceList {
+        self.0
+    }
+}
+
+impl WasmGasPrices {
+    /// Returns a builder for custom Wasm execution prices, starting from these ones.
+    pub fn builder(&self) -> WasmGasPricesBuilder {
+        WasmGasPricesBuilder(self.clone())
+    }
+}
+
+/// Builds custom [`WasmGasPrices`] by overriding individual costs of existing ones. See
+/// [`WasmGasPrices::builder`].
+#[derive(Clone, Debug)]
+pub struct WasmGasPricesBuilder(WasmGasPrices);
+
+impl WasmGasPricesBuilder {
+    setters! {
+        instruction_default: Gas,
+        math_default: Gas,
+        jump_unconditional: Gas,
+        jump_conditional: Gas,
+        jump_indirect: Gas,
+        call: Gas,
+        memory_fill_base_cost: Gas,
+        memory_fill_per_byte_cost: Gas,
+        memory_access_cost: Gas,
+        memory_copy_per_byte_cost: Gas,
+        host_call_cost: Gas,
+    }
+
+    pub fn build(self) -> WasmGasPrices {
+        self.0
+    }
+}
+
+/// Orders map keys when serializing price lists, to keep the output stable.
+trait EntryKey {
+    fn sort_key(&self) -> i64;
+}
+
+#[cfg(feature = "verify-signature")]
+impl EntryKey for SignatureType {
+    fn sort_key(&self) -> i64 {
+        *self as i64
+    }
+}
+
+impl EntryKey for SupportedHashes {
+    fn sort_key(&self) -> i64 {
+        *self as i64
+    }
+}
+
+impl EntryKey for RegisteredSealProof {
+    fn sort_key(&self) -> i64 {
+        i64::from(*self)
+    }
+}
+
+impl EntryKey for RegisteredPoStProof {
+    fn sort_key(&self) -> i64 {
+        i64::from(*self)
+    }
+}
+
+/// (De)serializes a map as a list of `[key, value]` pairs, sorted by key.
+mod entries {
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::EntryKey;
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + EntryKey,
+        V: Serialize,
+        S: Serializer,
+    {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by_key(|(k, _)| k.sort_key());
+        serializer.collect_seq(entries)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
     }
 }
 
 /// Returns gas price list by NetworkVersion for gas consumption.
 pub fn price_list_by_network_version(network_version: NetworkVersion) -> &'static PriceList {
+    try_price_list_by_network_version(network_version)
+        .unwrap_or_else(|| panic!("network version {nv} not supported", nv = network_version))
+}
+
+/// Returns gas price list by NetworkVersion for gas consumption, or `None` if the network version
+/// isn't supported.
+pub fn try_price_list_by_network_version(
+    network_version: NetworkVersion,
+) -> Option<&'static PriceList> {
     match network_version {
         NetworkVersion::V21 | NetworkVersion::V22 | NetworkVersion::V23 | NetworkVersion::V24 => {
-            &WATERMELON_PRICES
+            Some(&WATERMELON_PRICES)
         }
         NetworkVersion::V25 | NetworkVersion::V26 | NetworkVersion::V27 | NetworkVersion::V28 => {
-            &TEEP_PRICES
+            Some(&TEEP_PRICES)
         }
         #[cfg(feature = "nv29-dev")]
-        NetworkVersion::V29 => &TEEP_PRICES,
-        _ => panic!("network version {nv} not supported", nv = network_version),
+        NetworkVersion::V29 => Some(&TEEP_PRICES),
+        _ => None,
     }
 }
 
@@ -1639,4 +1940,58 @@ mod tests {
             "Single 64GiB NI-PoRep sector charge doesn't match expected value"
         );
     }
+
+    #[test]
+    fn test_price_list_serde_roundtrip() {
+        let pricelist = price_list_by_network_version(NetworkVersion::V25);
+
+        let json = serde_json::to_string(pricelist).unwrap();
+        let from_json: PriceList = serde_json::from_str(&json).unwrap();
+        assert_eq!(&from_json, pricelist);
+
+        let toml = toml::to_string(pricelist).unwrap();
+        let from_toml: PriceList = toml::from_str(&toml).unwrap();
+        assert_eq!(&from_toml, pricelist);
+
+        // Maps are serialized in a stable order.
+        assert_eq!(serde_json::to_string(&from_json).unwrap(), json);
+    }
+
+    #[test]
+    fn test_price_list_builder() {
+        let base = price_list_by_network_version(NetworkVersion::V25);
+        let custom = base
+            .builder()
+            .send_invoke_method(Gas::new(1))
+            .hashing_cost(SupportedHashes::Keccak256, ScalingCost::fixed(Gas::new(2)))
+            .wasm_rules(
+                base.wasm_rules
+                    .builder()
+                    .instruction_default(Gas::new(3))
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(custom.on_method_invocation(0, 0).compute_gas, Gas::new(1));
+        assert_eq!(
+            custom
+                .on_hashing(SupportedHashes::Keccak256, 100)
+                .compute_gas,
+            Gas::new(2)
+        );
+        assert_eq!(custom.wasm_rules.instruction_default, Gas::new(3));
+        assert_eq!(custom.wasm_rules.math_default, base.wasm_rules.math_default);
+
+        // Everything else is left untouched.
+        let restored = custom
+            .builder()
+            .send_invoke_method(base.send_invoke_method)
+            .hashing_cost(
+                SupportedHashes::Keccak256,
+                base.hashing_cost[&SupportedHashes::Keccak256],
+            )
+            .wasm_rules(base.wasm_rules.clone())
+            .build();
+        assert_eq!(&restored, base);
+    }
 }
@@ -9,12 +9,15 @@ use fvm_ipld_encoding::ipld_block::IpldBlock;
 use super::Result;
 use crate::syscall_error;
 
-/// A registry of open blocks (per-kernel). Think "file descriptor" table. At the moment, there's no
-/// way to close/remove a block from this table.
+/// A registry of open blocks (per-kernel). Think "file descriptor" table. Like file descriptors, the
+/// handles of closed blocks are reused by the blocks added next, most recently closed first.
 #[derive(Default)]
 pub struct BlockRegistry {
-    blocks: Vec<Block>,
+    blocks: Vec<Option<Block>>,
+    /// The handles of closed blocks, to be reused.
+    free: Vec<BlockId>,
     reachable: HashSet<Cid>,
+    memory_used: usize,
 }
 
 /// Blocks in the block registry are addressed by an ordinal, starting from 1 (`FIRST_ID`).
@@ -140,8 +143,17 @@ impl BlockRegistry {
             }
         }
 
-        let id = FIRST_ID + self.blocks.len() as u32;
-        self.blocks.push(block);
+        self.memory_used += block.size() as usize;
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.blocks[(id - FIRST_ID) as usize] = Some(block);
+                id
+            }
+            None => {
+                self.blocks.push(Some(block));
+                FIRST_ID + self.blocks.len() as u32 - 1
+            }
+        };
         Ok(id)
     }
 
@@ -153,25 +165,71 @@ impl BlockRegistry {
         id.try_into()
             .ok()
             .and_then(|idx: usize| self.blocks.get(idx - FIRST_ID as usize))
+            .and_then(Option::as_ref)
             .ok_or(syscall_error!(InvalidHandle; "invalid block handle {id}").into())
     }
 
     /// Returns the size & codec of the specified block.
     pub fn stat(&self, id: BlockId) -> Result<BlockStat> {
+        self.get(id).map(Block::stat)
+    }
+
+    /// Removes a block from the registry, returning it. Its handle becomes invalid until it's
+    /// reused by a block added later. The block's children remain reachable.
+    pub fn close(&mut self, id: BlockId) -> Result<Block> {
         if id < FIRST_ID {
             return Err(syscall_error!(InvalidHandle; "invalid block handle {id}").into());
         }
-        id.try_into()
+        let block = id
+            .try_into()
             .ok()
-            .and_then(|idx: usize| self.blocks.get(idx - FIRST_ID as usize))
-            .ok_or(syscall_error!(InvalidHandle; "invalid block handle {id}").into())
-            .map(|b| BlockStat {
-                codec: b.codec(),
-                size: b.size(),
-            })
+            .and_then(|idx: usize| self.blocks.get_mut(idx - FIRST_ID as usize))
+            .and_then(Option::take)
+            .ok_or(syscall_error!(InvalidHandle; "invalid block handle {id}"))?;
+        self.memory_used -= block.size() as usize;
+        self.free.push(id);
+        Ok(block)
+    }
+
+    /// Returns the total size of the data of the blocks in the registry, in bytes.
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
     }
 
     pub fn is_full(&self) -> bool {
-        self.blocks.len() as u32 == MAX_BLOCKS
+        self.free.is_empty() && self.blocks.len() as u32 == MAX_BLOCKS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_and_reuse() {
+        let mut registry = BlockRegistry::new();
+        let block = |size| Block::new(fvm_shared::IPLD_RAW, vec![0u8; size], Vec::new());
+
+        let a = registry.put_reachable(block(1)).unwrap();
+        let b = registry.put_reachable(block(2)).unwrap();
+        let c = registry.put_reachable(block(4)).unwrap();
+        assert_eq!((a, b, c), (1, 2, 3));
+        assert_eq!(registry.memory_used(), 7);
+
+        assert_eq!(registry.close(b).unwrap().size(), 2);
+        assert_eq!(registry.memory_used(), 5);
+        assert!(registry.get(b).is_err());
+        assert!(registry.stat(b).is_err());
+        assert!(registry.close(b).is_err());
+        assert!(registry.close(0).is_err());
+        assert!(registry.close(4).is_err());
+
+        registry.close(a).unwrap();
+        // Handles are reused, most recently closed first.
+        assert_eq!(registry.put_reachable(block(8)).unwrap(), a);
+        assert_eq!(registry.put_reachable(block(8)).unwrap(), b);
+        assert_eq!(registry.put_reachable(block(8)).unwrap(), 4);
+        assert_eq!(registry.stat(a).unwrap().size, 8);
+        assert_eq!(registry.memory_used(), 28);
     }
 }
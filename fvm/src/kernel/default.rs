@@ -25,6 +25,7 @@ use crate::call_manager::{
 use crate::externs::{Chain, Rand};
 use crate::gas::GasTimer;
 use crate::init_actor::INIT_ACTOR_ID;
+use crate::machine::limiter::MemoryLimiter;
 use crate::machine::{BURNT_FUNDS_ACTOR_ID, MachineContext, NetworkConfig};
 use crate::state_tree::ActorState;
 use crate::trace::IpldOperation;
@@ -154,7 +155,7 @@ where
 
         // Send.
         let result = self.call_manager.with_transaction(|cm| {
-            cm.call_actor::<K>(
+            let result = cm.call_actor::<K>(
                 from,
                 *recipient,
                 Entrypoint::Invoke(method),
@@ -162,7 +163,12 @@ where
                 value,
                 gas_limit,
                 read_only,
-            )
+            )?;
+            // Account for the return block before committing, reverting the call if it doesn't fit.
+            if let Some(blk) = &result.value {
+                grow_block_memory(cm.limiter_mut(), blk)?;
+            }
+            Ok(result)
         })?;
 
         // Store result and return.
@@ -175,7 +181,8 @@ where
                 // This can't fail because:
                 // 1. We've already charged for gas.
                 // 2. We've already checked that we have space for a return block.
-                // 3. This block has already been validated by the kernel that returned it.
+                // 3. We've already accounted for the block's memory.
+                // 4. This block has already been validated by the kernel that returned it.
                 let block_id = self
                     .blocks
                     .put_reachable(blk)
@@ -273,6 +280,9 @@ where
                 None,
                 false,
             )?;
+            if let Some(blk) = &result.value {
+                grow_block_memory(cm.limiter_mut(), blk)?;
+            }
 
             Ok(result)
         });
@@ -428,6 +438,7 @@ where
         )?;
 
         let block = Block::new(cid.codec(), data, children);
+        grow_block_memory(self.call_manager.limiter_mut(), &block)?;
         let stat = block.stat();
         let id = self.blocks.put_reachable(block)?;
         t.stop();
@@ -457,6 +468,7 @@ where
         )?;
 
         let blk = Block::new(codec, data, children);
+        grow_block_memory(self.call_manager.limiter_mut(), &blk)?;
 
         t.record(Ok(self.blocks.put_check_reachable(blk)?))
     }
@@ -539,6 +551,19 @@ where
 
         t.record(Ok(self.blocks.stat(id)?))
     }
+
+    fn block_close(&mut self, id: BlockId) -> Result<()> {
+        let t = self
+            .call_manager
+            .charge_gas(self.call_manager.price_list().on_block_close())?;
+
+        let block = self.blocks.close(id)?;
+        self.call_manager
+            .limiter_mut()
+            .release_block_memory(block.size() as usize);
+        t.stop();
+        Ok(())
+    }
 }
 
 impl<C> MessageOps for DefaultKernel<C>
@@ -608,6 +633,15 @@ where
             }
         }
 
+        // Delegated signatures aren't enabled on any network yet, so reject them exactly like
+        // unknown signature types.
+        if sig_type == SignatureType::Delegated && !cfg!(feature = "delegated-signatures") {
+            return Err(
+                syscall_error!(IllegalArgument; "unknown signature type {}", sig_type as u32)
+                    .into(),
+            );
+        }
+
         let t = self.call_manager.charge_gas(
             self.call_manager
                 .price_list()
@@ -615,9 +649,11 @@ where
         )?;
 
         // We only support key addresses (f1/f3). This change does not require a FIP, because no
-        // actors invoke this method with non-key addresses.
+        // actors invoke this method with non-key addresses. f410 signers are only accepted for
+        // delegated signatures, behind the `delegated-signatures` feature.
         let signing_addr = match signer.payload() {
             Payload::BLS(_) | Payload::Secp256k1(_) => *signer,
+            Payload::Delegated(_) if sig_type == SignatureType::Delegated => *signer,
             // Not a key address.
             _ => {
                 return Err(syscall_error!(IllegalArgument; "address protocol {} not supported", signer.protocol()).into());
@@ -1153,3 +1189,15 @@ where
         Ok(())
     }
 }
+
+/// Accounts for the memory of a block about to be added to the block registry of the current call
+/// frame. It's released when the block is closed or the frame returns.
+fn grow_block_memory(limiter: &mut impl MemoryLimiter, block: &Block) -> Result<()> {
+    if !limiter.grow_block_memory(block.size() as usize) {
+        return Err(
+            syscall_error!(LimitExceeded; "out of memory storing a block of {} bytes", block.size())
+                .into(),
+        );
+    }
+    Ok(())
+}
@@ -176,6 +176,24 @@ impl SyscallError {
     }
 }
 
+/// The wall-clock execution time limit of a message (see
+/// [`MachineContext::execution_time_limit`](crate::machine::MachineContext::execution_time_limit))
+/// has been exceeded.
+///
+/// This is raised as a fatal error, but unlike other fatal errors it's not deterministic and the
+/// message won't produce a receipt: the executor returns it as an error instead. Check for it with
+/// [`DeadlineExceeded::is_cause_of`].
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("execution deadline exceeded")]
+pub struct DeadlineExceeded;
+
+impl DeadlineExceeded {
+    /// Returns true if the error was caused by exceeding the execution deadline.
+    pub fn is_cause_of(err: &anyhow::Error) -> bool {
+        err.chain().any(|e| e.is::<DeadlineExceeded>())
+    }
+}
+
 #[test]
 fn test_syscall_error_formatting() {
     let test_value = 1;
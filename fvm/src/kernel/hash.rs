@@ -3,8 +3,9 @@
 use multihash_codetable::{
     Blake2b256, Blake2b512, Keccak256, MultihashDigest, Ripemd160, Sha2_256,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Eq, MultihashDigest, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, MultihashDigest, PartialEq, Hash, Serialize, Deserialize)]
 #[mh(alloc_size = 64)]
 /// Codes and hashers supported by FVM.
 /// You _can_ use this hash directly inside of your actor,
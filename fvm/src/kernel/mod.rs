@@ -16,7 +16,7 @@ pub mod default;
 pub mod filecoin;
 
 pub use blocks::{Block, BlockId, BlockRegistry, BlockStat};
-pub use error::{ClassifyResult, Context, ExecutionError, Result, SyscallError};
+pub use error::{ClassifyResult, Context, DeadlineExceeded, ExecutionError, Result, SyscallError};
 pub use hash::SupportedHashes;
 
 pub struct CallResult {
@@ -157,6 +157,16 @@ pub trait IpldBlockOps {
     ///
     /// This method will fail if the block handle is invalid.
     fn block_stat(&self, id: BlockId) -> Result<BlockStat>;
+
+    /// Closes a block, releasing it. Its handle may be reused by blocks opened or created later.
+    ///
+    /// This method will fail if the block handle is invalid. Kernels that don't support closing
+    /// blocks fail with [`IllegalOperation`](fvm_shared::error::ErrorNumber::IllegalOperation),
+    /// which is the default.
+    fn block_close(&mut self, id: BlockId) -> Result<()> {
+        let _ = id;
+        Err(crate::syscall_error!(IllegalOperation; "closing blocks is not supported").into())
+    }
 }
 
 /// Actor state access and manipulation.
@@ -10,10 +10,18 @@ pub trait MemoryLimiter: Sized {
     ///
     /// - Memory used by tables (8 bytes per element).
     /// - Memory used by wasmtime instances.
+    /// - Memory used by IPLD blocks held in the block registries, if enabled (see
+    ///   [`NetworkConfig::account_block_memory`]).
     ///
-    /// In the future, this will likely be extended to include IPLD blocks, actor code, etc.
+    /// In the future, this will likely be extended to include actor code, etc.
     fn memory_used(&self) -> usize;
 
+    /// Get the peak memory used by the callstack (in bytes) since the current stack frame was
+    /// pushed. Defaults to the memory currently used.
+    fn peak_memory_used(&self) -> usize {
+        self.memory_used()
+    }
+
     /// Returns `true` if growing by `delta` bytes is allowed. Implement this memory to track and
     /// limit memory usage.
     fn grow_memory(&mut self, delta: usize) -> bool;
@@ -37,6 +45,19 @@ pub trait MemoryLimiter: Sized {
         // we charge 8 bytes per table element
         self.grow_memory(to.saturating_sub(from).saturating_mul(8))
     }
+
+    /// Grows the memory used by IPLD blocks by `bytes` when a block is added to a block registry.
+    /// There's no need to manually implement this unless you need to track block metrics.
+    fn grow_block_memory(&mut self, bytes: usize) -> bool {
+        self.grow_memory(bytes)
+    }
+
+    /// Releases `bytes` of memory previously grown by [`grow_block_memory`] in the current stack
+    /// frame, when a block is closed. By default, the memory is only released when the frame is
+    /// popped.
+    ///
+    /// [`grow_block_memory`]: MemoryLimiter::grow_block_memory
+    fn release_block_memory(&mut self, _bytes: usize) {}
 }
 
 /// Limit resources throughout the whole message execution,
@@ -44,6 +65,8 @@ pub trait MemoryLimiter: Sized {
 pub struct DefaultMemoryLimiter {
     max_memory_bytes: usize,
     curr_memory_bytes: usize,
+    peak_memory_bytes: usize,
+    account_blocks: bool,
 }
 
 impl DefaultMemoryLimiter {
@@ -51,11 +74,24 @@ impl DefaultMemoryLimiter {
         Self {
             max_memory_bytes,
             curr_memory_bytes: 0,
+            peak_memory_bytes: 0,
+            account_blocks: false,
         }
     }
 
+    /// Also count the memory used by IPLD blocks towards the limit.
+    pub fn with_block_accounting(mut self) -> Self {
+        self.account_blocks = true;
+        self
+    }
+
     pub fn for_network(config: &NetworkConfig) -> Self {
-        Self::new(config.max_memory_bytes as usize)
+        let limiter = Self::new(config.max_memory_bytes as usize);
+        if config.account_block_memory {
+            limiter.with_block_accounting()
+        } else {
+            limiter
+        }
     }
 }
 
@@ -64,6 +100,10 @@ impl MemoryLimiter for DefaultMemoryLimiter {
         self.curr_memory_bytes
     }
 
+    fn peak_memory_used(&self) -> usize {
+        self.peak_memory_bytes
+    }
+
     fn grow_memory(&mut self, bytes: usize) -> bool {
         let total_desired = self.curr_memory_bytes.saturating_add(bytes);
 
@@ -72,19 +112,35 @@ impl MemoryLimiter for DefaultMemoryLimiter {
         }
 
         self.curr_memory_bytes = total_desired;
+        self.peak_memory_bytes = self.peak_memory_bytes.max(total_desired);
         true
     }
 
+    fn grow_block_memory(&mut self, bytes: usize) -> bool {
+        !self.account_blocks || self.grow_memory(bytes)
+    }
+
+    fn release_block_memory(&mut self, bytes: usize) {
+        if self.account_blocks {
+            self.curr_memory_bytes = self.curr_memory_bytes.saturating_sub(bytes);
+        }
+    }
+
     fn with_stack_frame<T, G, F, R>(t: &mut T, g: G, f: F) -> R
     where
         G: Fn(&mut T) -> &mut Self,
         F: FnOnce(&mut T) -> R,
     {
-        let memory_bytes = g(t).curr_memory_bytes;
+        let limiter = g(t);
+        let memory_bytes = limiter.curr_memory_bytes;
+        // Track the peak of the new frame separately, then fold it into the peak of the parent.
+        let peak_bytes = std::mem::replace(&mut limiter.peak_memory_bytes, memory_bytes);
         let ret = f(t);
         // This method is part of the trait so that a setter like this
         // doesn't have to be made public.
-        g(t).curr_memory_bytes = memory_bytes;
+        let limiter = g(t);
+        limiter.curr_memory_bytes = memory_bytes;
+        limiter.peak_memory_bytes = limiter.peak_memory_bytes.max(peak_bytes);
         ret
     }
 }
@@ -130,4 +186,58 @@ mod tests {
         assert!(limits.grow_memory(2)); // 2 bytes
         assert!(!limits.grow_memory(1));
     }
+
+    #[test]
+    fn peak() {
+        let mut limits = DefaultMemoryLimiter::new(100);
+        assert!(limits.grow_memory(10));
+        DefaultMemoryLimiter::with_stack_frame(
+            &mut limits,
+            |x| x,
+            |limits| {
+                assert_eq!(limits.peak_memory_used(), 10);
+                assert!(limits.grow_memory(30));
+                DefaultMemoryLimiter::with_stack_frame(
+                    limits,
+                    |x| x,
+                    |limits| {
+                        assert!(limits.grow_memory(5));
+                        assert_eq!(limits.peak_memory_used(), 45);
+                    },
+                );
+                assert_eq!(limits.memory_used(), 40);
+                assert_eq!(limits.peak_memory_used(), 45);
+            },
+        );
+        assert_eq!(limits.memory_used(), 10);
+        assert_eq!(limits.peak_memory_used(), 45);
+
+        // A new frame only reports its own peak.
+        DefaultMemoryLimiter::with_stack_frame(
+            &mut limits,
+            |x| x,
+            |limits| {
+                assert!(limits.grow_memory(1));
+                assert_eq!(limits.peak_memory_used(), 11);
+            },
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let mut limits = DefaultMemoryLimiter::new(10);
+        assert!(limits.grow_block_memory(20)); // Not accounted by default.
+        assert_eq!(limits.memory_used(), 0);
+
+        let mut limits = DefaultMemoryLimiter::new(10).with_block_accounting();
+        assert!(limits.grow_block_memory(8));
+        assert!(!limits.grow_block_memory(3));
+        assert!(limits.grow_memory(2));
+        assert_eq!(limits.memory_used(), 10);
+
+        limits.release_block_memory(8);
+        assert_eq!(limits.memory_used(), 2);
+        assert_eq!(limits.peak_memory_used(), 10);
+        assert!(limits.grow_block_memory(8));
+    }
 }
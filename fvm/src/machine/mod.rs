@@ -1,5 +1,7 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use std::time::Duration;
+
 use cid::Cid;
 use derive_more::{Deref, DerefMut};
 use fvm_ipld_blockstore::Blockstore;
@@ -9,6 +11,7 @@ use fvm_shared::econ::TokenAmount;
 use fvm_shared::version::NetworkVersion;
 use num_traits::Zero;
 
+use crate::engine::ModuleCacheLimits;
 use crate::externs::Externs;
 use crate::gas::{PriceList, price_list_by_network_version};
 use crate::kernel::Result;
@@ -110,12 +113,21 @@ pub struct NetworkConfig {
     pub max_inst_memory_bytes: u64,
 
     /// Maximum size of memory used during the entire (recursive) message execution. This currently
-    /// includes Wasm memories and table elements and will eventually be extended to include IPLD
-    /// blocks and actor code.
+    /// includes Wasm memories and table elements, IPLD blocks if [`account_block_memory`] is
+    /// enabled, and will eventually be extended to include actor code.
+    ///
+    /// [`account_block_memory`]: NetworkConfig::account_block_memory
     ///
     /// DEFAULT: 2GiB
     pub max_memory_bytes: u64,
 
+    /// Count the IPLD blocks opened, created or returned by actors towards [`max_memory_bytes`].
+    ///
+    /// DEFAULT: `false`
+    ///
+    /// [`max_memory_bytes`]: NetworkConfig::max_memory_bytes
+    pub account_block_memory: bool,
+
     /// The maximum blocks size that can be created in the FVM.
     ///
     /// DEFAULT: 1MiB
@@ -139,6 +151,23 @@ pub struct NetworkConfig {
 
     /// Actor redirects for debug execution
     pub actor_redirect: Vec<(Cid, Cid)>,
+
+    /// Enable per-function Wasm gas profiling, reported in the execution trace.
+    ///
+    /// DEFAULT: `false`
+    pub wasm_profiling: bool,
+
+    /// Enable interrupting Wasm execution when the execution time limit is exceeded (see
+    /// [`MachineContext::execution_time_limit`]). Not consensus-critical, but slightly slows Wasm
+    /// execution down.
+    ///
+    /// DEFAULT: `false`
+    pub wasm_interruption: bool,
+
+    /// Bounds on the compiled modules kept in memory. Not consensus-critical.
+    ///
+    /// DEFAULT: unbounded
+    pub module_cache_limits: ModuleCacheLimits,
 }
 
 impl NetworkConfig {
@@ -151,11 +180,15 @@ impl NetworkConfig {
             max_wasm_stack: 2048,
             max_inst_memory_bytes: 512 * (1 << 20),
             max_memory_bytes: 2 * (1 << 30),
+            account_block_memory: false,
             actor_debugging: false,
             builtin_actors_override: None,
             price_list: price_list_by_network_version(network_version),
             actor_redirect: vec![],
             max_block_size: 1 << 20,
+            wasm_profiling: false,
+            wasm_interruption: false,
+            module_cache_limits: ModuleCacheLimits::default(),
         }
     }
 
@@ -166,6 +199,37 @@ impl NetworkConfig {
         self
     }
 
+    /// Count the memory used by IPLD blocks towards the execution memory limit. This is a
+    /// consensus-critical option (messages holding too many blocks fail) so it should only be
+    /// enabled for local testing or as a network-wide parameter.
+    pub fn enable_block_memory_accounting(&mut self) -> &mut Self {
+        self.account_block_memory = true;
+        self
+    }
+
+    /// Enable per-function Wasm gas profiling. Actors are instrumented to attribute the Wasm
+    /// execution gas they're charged to their functions, reported as
+    /// [`ExecutionEvent::WasmProfile`](crate::trace::ExecutionEvent::WasmProfile) events when
+    /// tracing is enabled. This doesn't change the gas charged, but slows execution down and should
+    /// only be enabled when developing actors.
+    pub fn enable_wasm_profiling(&mut self) -> &mut Self {
+        self.wasm_profiling = true;
+        self
+    }
+
+    /// Enable interrupting Wasm execution once the execution time limit is exceeded. See
+    /// [`MachineContext::set_execution_time_limit`].
+    pub fn enable_wasm_interruption(&mut self) -> &mut Self {
+        self.wasm_interruption = true;
+        self
+    }
+
+    /// Bound the compiled modules kept in memory. See [`ModuleCacheLimits`].
+    pub fn limit_module_cache(&mut self, limits: ModuleCacheLimits) -> &mut Self {
+        self.module_cache_limits = limits;
+        self
+    }
+
     /// Override actors with the specific manifest. This is primarily useful for testing, or
     /// networks prior to NV16 (where the actor's "manifest" isn't specified on-chain).
     pub fn override_actors(&mut self, manifest: Cid) -> &mut Self {
@@ -173,6 +237,17 @@ impl NetworkConfig {
         self
     }
 
+    /// Override the price list, e.g. with a custom one built with [`PriceList::builder`] or
+    /// deserialized from a file. This is a consensus-critical option, so it should only be used for
+    /// local testing or to evaluate proposed gas changes.
+    ///
+    /// The price list must outlive the config (and any engine created from it). Custom price lists
+    /// can be leaked with [`Box::leak`] as they're usually created once per process.
+    pub fn override_price_list(&mut self, price_list: &'static PriceList) -> &mut Self {
+        self.price_list = price_list;
+        self
+    }
+
     /// Set actor redirects for debug execution
     pub fn redirect_actors(&mut self, actor_redirect: Vec<(Cid, Cid)>) -> &mut Self {
         self.actor_redirect = actor_redirect;
@@ -197,6 +272,7 @@ impl NetworkConfig {
             circ_supply: TokenAmount::zero(),
             tracing: false,
             flush_all_blocks: false,
+            execution_time_limit: None,
         }
     }
 
@@ -247,6 +323,18 @@ pub struct MachineContext {
     /// When true, flush() will write all blocks created during execution to the
     /// blockstore, not just those reachable from the final state root.
     pub flush_all_blocks: bool,
+
+    /// The wall-clock time each message may execute for. Messages exceeding it are aborted with a
+    /// fatal [`DeadlineExceeded`](crate::kernel::DeadlineExceeded) error and produce no receipt.
+    /// Not consensus-critical, and not deterministic: only use it for non-committing execution
+    /// (e.g., estimating gas or calling actors over RPC) and discard the machine's state once it
+    /// has been exceeded.
+    ///
+    /// The limit is checked on every gas charge, and Wasm execution is only interrupted if
+    /// [`NetworkConfig::wasm_interruption`] is enabled.
+    ///
+    /// DEFAULT: `None`
+    pub execution_time_limit: Option<Duration>,
 }
 
 impl MachineContext {
@@ -279,4 +367,17 @@ impl MachineContext {
         self.flush_all_blocks = true;
         self
     }
+
+    /// Set [`MachineContext::execution_time_limit`], also enabling
+    /// [`NetworkConfig::wasm_interruption`] so that actors looping in Wasm get interrupted too.
+    ///
+    /// The engine pool must be created from this context's network config (e.g., with
+    /// [`MultiEngine::get`](crate::engine::MultiEngine::get)):
+    /// [`DefaultExecutor::new`](crate::executor::DefaultExecutor::new) rejects engine pools without
+    /// Wasm interruption.
+    pub fn set_execution_time_limit(&mut self, limit: Duration) -> &mut Self {
+        self.execution_time_limit = Some(limit);
+        self.network.wasm_interruption = true;
+        self
+    }
 }
@@ -69,3 +69,7 @@ pub fn block_stat(
             size: stat.size,
         })
 }
+
+pub fn block_close(context: Context<'_, impl IpldBlockOps>, id: u32) -> Result<()> {
+    context.kernel.block_close(id)
+}
@@ -1,5 +1,7 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
+use std::time::Instant;
+
 use anyhow::{Context as _, anyhow};
 use num_traits::Zero;
 use wasmtime::{AsContext, AsContextMut, ExternType, Global, Module, Val};
@@ -67,6 +69,9 @@ pub(crate) struct InvocationData<K> {
     pub memory: wasmtime::Memory,
 
     pub wasm_prices: &'static WasmGasPrices,
+
+    /// The wall-clock deadline of the message, checked whenever Wasm execution is interrupted.
+    pub deadline: Option<Instant>,
 }
 
 /// Updates the global available gas in the Wasm module after a syscall, to account for any
@@ -279,6 +284,9 @@ where
         linker.link_syscall("ipld", "block_read", ipld::block_read)?;
         linker.link_syscall("ipld", "block_stat", ipld::block_stat)?;
         linker.link_syscall("ipld", "block_link", ipld::block_link)?;
+        if cfg!(feature = "block-close") {
+            linker.link_syscall("ipld", "block_close", ipld::block_close)?;
+        }
 
         linker.link_syscall("self", "root", sself::root)?;
         linker.link_syscall("self", "set_root", sself::set_root)?;
@@ -8,7 +8,7 @@ use fvm_shared::error::ExitCode;
 use fvm_shared::state::ActorState;
 use fvm_shared::{ActorID, MethodNum};
 
-use crate::gas::GasCharge;
+use crate::gas::{Gas, GasCharge};
 use crate::kernel::SyscallError;
 
 /// Execution Trace, only for informational and debugging purposes.
@@ -39,6 +39,13 @@ pub enum ExecutionEvent {
         gas_limit: u64,
         read_only: bool,
     },
+    /// Emitted right before the `CallReturn` or `CallError` of every call that was attempted, with
+    /// the execution memory (see [`MemoryLimiter`](crate::machine::limiter::MemoryLimiter)) used
+    /// by the call stack when the call started, and its peak during the call, including subcalls.
+    CallMemory {
+        initial: usize,
+        peak: usize,
+    },
     CallReturn(ExitCode, Option<IpldBlock>),
     CallError(SyscallError),
     /// Emitted every time an actor is successfully invoked.
@@ -52,4 +59,27 @@ pub enum ExecutionEvent {
         cid: Cid,
         size: usize,
     },
+    /// Emitted at the end of every actor invocation when Wasm profiling is enabled (see
+    /// [`NetworkConfig::enable_wasm_profiling`](crate::machine::NetworkConfig::enable_wasm_profiling)).
+    WasmProfile(WasmProfile),
+}
+
+/// The Wasm execution gas charged by each function of an actor during a single invocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WasmProfile {
+    /// The functions that were charged any gas, most expensive first.
+    pub functions: Vec<FunctionGas>,
+    /// Gas charged after the last function boundary, e.g., by a function that trapped.
+    pub unattributed: Gas,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionGas {
+    /// The index of the function in the actor's Wasm module.
+    pub index: u32,
+    /// The name of the function, if the module has a name section.
+    pub name: Option<String>,
+    /// The Wasm execution gas charged while this function was executing, excluding its callees
+    /// and syscalls.
+    pub gas: Gas,
 }
@@ -477,6 +477,26 @@ mod ipld {
 
         Ok(())
     }
+
+    #[test]
+    fn close() -> anyhow::Result<()> {
+        let (mut kern, _) = build_inspecting_test()?;
+
+        let id = kern.block_create(IPLD_RAW, "foo".as_bytes())?;
+        let cid = kern.block_link(id, Code::Blake2b256.into(), 32)?;
+        kern.block_close(id)?;
+
+        expect_syscall_err!(InvalidHandle, kern.block_stat(id));
+        expect_syscall_err!(InvalidHandle, kern.block_close(id));
+        expect_syscall_err!(InvalidHandle, kern.block_close(0));
+
+        // The handle is reused, and closing doesn't affect reachability.
+        let (opened_id, stat) = kern.block_open(&cid)?;
+        assert_eq!(opened_id, id);
+        assert_eq!(stat.size, 3);
+
+        Ok(())
+    }
 }
 
 mod gas {
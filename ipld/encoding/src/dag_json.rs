@@ -0,0 +1,60 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! DAG-JSON encoding, for debugging state and building RPC responses. CIDs are encoded as
+//! `{"/": "<cid>"}`, and bytes as `{"/": {"bytes": "<base64>"}}`.
+//!
+//! The FVM doesn't accept DAG-JSON blocks on-chain: use DAG-CBOR there.
+
+use crate::{CodecProtocol, Error, de, ser};
+
+fn error(err: impl ToString) -> Error {
+    Error {
+        description: err.to_string(),
+        protocol: CodecProtocol::Json,
+    }
+}
+
+/// Serializes a value to a vector of DAG-JSON.
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: ser::Serialize + ?Sized,
+{
+    serde_ipld_dagjson::to_vec(value).map_err(error)
+}
+
+/// Decode a value from DAG-JSON from the given slice.
+pub fn from_slice<'a, T>(slice: &'a [u8]) -> Result<T, Error>
+where
+    T: de::Deserialize<'a>,
+{
+    serde_ipld_dagjson::from_slice(slice).map_err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::Cid;
+    use multihash_codetable::{Code, MultihashDigest};
+
+    use super::*;
+    use crate::{BytesDe, BytesSer, DAG_CBOR};
+
+    #[test]
+    fn links_and_bytes() {
+        let cid = Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(b"block"));
+        let json = to_vec(&(cid, BytesSer(&[1, 2, 3]))).unwrap();
+        assert_eq!(
+            String::from_utf8(json.clone()).unwrap(),
+            format!(r#"[{{"/":"{cid}"}},{{"/":{{"bytes":"AQID"}}}}]"#)
+        );
+
+        let (decoded_cid, BytesDe(bytes)): (Cid, BytesDe) = from_slice(&json).unwrap();
+        assert_eq!(decoded_cid, cid);
+        assert_eq!(bytes, [1, 2, 3]);
+    }
+
+    #[test]
+    fn invalid() {
+        let err = from_slice::<u64>(b"\"not a number\"").unwrap_err();
+        assert_eq!(err.protocol, CodecProtocol::Json);
+    }
+}
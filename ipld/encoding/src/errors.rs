@@ -61,6 +61,7 @@ impl From<Error> for io::Error {
 pub enum CodecProtocol {
     Unsupported,
     Cbor,
+    Json,
     Raw,
 }
 
@@ -69,6 +70,7 @@ impl fmt::Display for CodecProtocol {
         match *self {
             CodecProtocol::Unsupported => write!(f, "Unsupported"),
             CodecProtocol::Cbor => write!(f, "Cbor"),
+            CodecProtocol::Json => write!(f, "Json"),
             CodecProtocol::Raw => write!(f, "Raw"),
         }
     }
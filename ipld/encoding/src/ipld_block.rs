@@ -5,6 +5,8 @@ use std::fmt::{Debug, Formatter};
 use serde::de::value;
 use {serde, serde_ipld_dagcbor};
 
+#[cfg(feature = "dag-json")]
+use crate::DAG_JSON;
 use crate::{CBOR, CodecProtocol, DAG_CBOR, Error, IPLD_RAW, RawBytes};
 
 #[derive(PartialEq, Eq, Clone, Default)]
@@ -47,6 +49,8 @@ impl IpldBlock {
                 protocol: CodecProtocol::Raw,
             }),
             DAG_CBOR | CBOR => Ok(serde_ipld_dagcbor::from_slice(self.data.as_slice())?),
+            #[cfg(feature = "dag-json")]
+            DAG_JSON => crate::dag_json::from_slice(self.data.as_slice()),
             _ => Err(Error {
                 description: "unsupported protocol".to_string(),
                 protocol: CodecProtocol::Unsupported,
@@ -57,6 +61,8 @@ impl IpldBlock {
         let data = match codec {
             IPLD_RAW => crate::raw::to_vec(value)?,
             DAG_CBOR | CBOR => crate::to_vec(value)?,
+            #[cfg(feature = "dag-json")]
+            DAG_JSON => crate::dag_json::to_vec(value)?,
             _ => {
                 return Err(Error {
                     description: "unsupported protocol".to_string(),
@@ -92,6 +98,20 @@ impl From<RawBytes> for Option<IpldBlock> {
 #[cfg(test)]
 mod test {
     use super::IpldBlock;
+    use crate::{DAG_CBOR, DAG_JSON};
+
+    #[test]
+    fn dag_json() {
+        let value = (1u64, "two".to_string());
+        let block = IpldBlock::serialize(DAG_JSON, &value).unwrap();
+        assert_eq!(block.codec, DAG_JSON);
+        assert_eq!(block.data, br#"[1,"two"]"#);
+        assert_eq!(block.deserialize::<(u64, String)>().unwrap(), value);
+
+        // The same value round-trips through DAG-CBOR.
+        let cbor = IpldBlock::serialize(DAG_CBOR, &value).unwrap();
+        assert_eq!(cbor.deserialize::<(u64, String)>().unwrap(), value);
+    }
 
     #[test]
     fn debug_hex() {
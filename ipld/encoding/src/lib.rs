@@ -5,6 +5,8 @@
 mod bytes;
 mod cbor;
 mod cbor_store;
+#[cfg(feature = "dag-json")]
+pub mod dag_json;
 mod errors;
 pub mod ipld_block;
 mod raw;
@@ -25,6 +27,9 @@ pub use self::vec::*;
 pub const CBOR: u64 = 0x51;
 /// DagCBOR should be used for all IPLD-CBOR data where CIDs need to be traversable.
 pub const DAG_CBOR: u64 = serde_ipld_dagcbor::DAG_CBOR_CODE;
+/// DagJSON may be used to encode IPLD data as JSON off-chain, e.g. for debugging. The FVM doesn't
+/// accept DagJSON blocks. Encoding and decoding it requires the `dag-json` feature.
+pub const DAG_JSON: u64 = 0x0129;
 /// RAW should be used for raw data.
 pub const IPLD_RAW: u64 = 0x55;
 
@@ -0,0 +1,197 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! A blockstore over the actor's IPLD state, to use IPLD data structures (HAMTs, AMTs, etc.) in
+//! actors.
+use std::collections::HashSet;
+
+use anyhow::{Result, anyhow};
+use cid::Cid;
+use fvm_ipld_blockstore::Block;
+use fvm_shared::error::ErrorNumber;
+use multihash_codetable::Code;
+
+use crate::ipld;
+
+/// The length of Blake2b-256 digests. The FVM only links blocks with 32-byte Blake2b-256 hashes.
+const BLAKE2B_256_LEN: u32 = 32;
+
+/// A lower bound on the gas the FVM charges to put a block, per block and per byte: persisting
+/// the block (`block_persist_compute` and `block_persist_storage`), plus allocating, copying and
+/// retaining its data (`block_allocate`, `block_memcpy` and `block_memory_retention_minimum`).
+///
+/// The SDK doesn't know the actual price list, so this only catches batches that can't possibly
+/// be put with the remaining gas.
+const BLOCK_PUT_GAS: u64 = 172000 + 334000;
+const BLOCK_PUT_GAS_PER_BYTE: u64 = 3340 + 12;
+
+/// A [`fvm_ipld_blockstore::Blockstore`] reading and writing blocks with [`ipld::get`] and
+/// [`ipld::put`].
+///
+/// Blocks are stored with the codec and hash function of their CIDs. As the FVM only links blocks
+/// with Blake2b-256 hashes, blocks put with any other hash function (including identity) are
+/// rejected.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Blockstore;
+
+fn put(mh_code: u64, codec: u64, data: &[u8]) -> Result<Cid> {
+    ipld::put(mh_code, BLAKE2B_256_LEN, codec, data)
+        .map_err(|e| anyhow!("failed to put block with hash function {mh_code:#x}: {e}"))
+}
+
+/// Fails if putting blocks of the given sizes would certainly run out of gas, before putting any
+/// of them.
+fn check_batch_gas(sizes: impl IntoIterator<Item = usize>) -> Result<()> {
+    let expected = sizes.into_iter().fold(0u64, |gas, size| {
+        gas.saturating_add(BLOCK_PUT_GAS)
+            .saturating_add(BLOCK_PUT_GAS_PER_BYTE.saturating_mul(size as u64))
+    });
+    let available = crate::gas::available();
+    if expected > available {
+        return Err(anyhow!(
+            "not enough gas to put blocks: expected at least {expected}, {available} available"
+        ));
+    }
+    Ok(())
+}
+
+impl fvm_ipld_blockstore::Blockstore for Blockstore {
+    fn get(&self, k: &Cid) -> Result<Option<Vec<u8>>> {
+        match ipld::get(k) {
+            Ok(block) => Ok(Some(block)),
+            Err(ErrorNumber::NotFound) => Ok(None),
+            Err(e) => Err(anyhow!("failed to get block {k}: {e}")),
+        }
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> Result<()> {
+        let actual = put(k.hash().code(), k.codec(), block)?;
+        if actual != *k {
+            return Err(anyhow!("block has CID {actual}, not {k}"));
+        }
+        Ok(())
+    }
+
+    fn put<D>(&self, mh_code: Code, block: &Block<D>) -> Result<Cid>
+    where
+        D: AsRef<[u8]>,
+    {
+        put(mh_code.into(), block.codec, block.data.as_ref())
+    }
+
+    fn put_many<D, I>(&self, blocks: I) -> Result<()>
+    where
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Code, Block<D>)>,
+    {
+        let blocks: Vec<_> = blocks.into_iter().collect();
+        check_batch_gas(blocks.iter().map(|(_, block)| block.data.as_ref().len()))?;
+        // Let the FVM hash the blocks, instead of hashing them in Wasm to key them.
+        for (mh_code, block) in blocks {
+            self.put(mh_code, &block)?;
+        }
+        Ok(())
+    }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> Result<()>
+    where
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        // There's no batch syscall, so every block is put (and charged for) on its own. Data
+        // structures may flush the same block more than once though: only pay for it once.
+        let mut seen = HashSet::new();
+        let blocks: Vec<_> = blocks
+            .into_iter()
+            .filter(|(k, _)| seen.insert(*k))
+            .collect();
+        check_batch_gas(blocks.iter().map(|(_, block)| block.as_ref().len()))?;
+        for (k, block) in blocks {
+            self.put_keyed(&k, block.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use fvm_ipld_blockstore::Blockstore as _;
+    use fvm_ipld_encoding::IPLD_RAW;
+    use fvm_ipld_hamt::Hamt;
+    use fvm_shared::error::ExitCode;
+    use multihash_codetable::MultihashDigest;
+
+    use super::*;
+    use crate::NO_DATA_BLOCK_ID;
+    use crate::testing::MockRuntime;
+
+    #[test]
+    fn put_and_get() {
+        let mut rt = MockRuntime::new(1000);
+        let exit = rt.invoke(0, None, |_| {
+            let bs = Blockstore;
+            let k = bs
+                .put(Code::Blake2b256, &Block::new(IPLD_RAW, b"foo"))
+                .unwrap();
+            assert_eq!(bs.get(&k).unwrap().unwrap(), b"foo");
+
+            let missing = Cid::new_v1(IPLD_RAW, Code::Blake2b256.digest(b"bar"));
+            assert_eq!(bs.get(&missing).unwrap(), None);
+            bs.put_keyed(&missing, b"baz").unwrap_err();
+            bs.put_many_keyed([(missing, b"bar"), (missing, b"bar")])
+                .unwrap();
+            assert!(bs.has(&missing).unwrap());
+
+            bs.put(Code::Sha2_256, &Block::new(IPLD_RAW, b"foo"))
+                .unwrap_err();
+            NO_DATA_BLOCK_ID
+        });
+        assert_eq!(exit.code, ExitCode::OK, "{:?}", exit.message);
+    }
+
+    #[test]
+    fn put_many_checks_gas() {
+        let mut rt = MockRuntime::new(1000);
+        let exit = rt.invoke(0, None, |_| {
+            let bs = Blockstore;
+            let blocks = [b"foo".as_slice(), b"bar".as_slice()]
+                .map(|data| (Cid::new_v1(IPLD_RAW, Code::Blake2b256.digest(data)), data));
+
+            // Leave enough gas for one block, but not for both.
+            let available = crate::gas::available();
+            crate::gas::charge(
+                "test",
+                available - BLOCK_PUT_GAS - 3 * BLOCK_PUT_GAS_PER_BYTE,
+            );
+            bs.put_many_keyed(blocks).unwrap_err();
+            bs.put_many(blocks.map(|(_, data)| (Code::Blake2b256, Block::new(IPLD_RAW, data))))
+                .unwrap_err();
+            for (k, _) in blocks {
+                assert!(!bs.has(&k).unwrap());
+            }
+
+            // Duplicates are only charged for once.
+            bs.put_many_keyed([blocks[0], blocks[0]]).unwrap();
+            assert!(bs.has(&blocks[0].0).unwrap());
+            NO_DATA_BLOCK_ID
+        });
+        assert_eq!(exit.code, ExitCode::OK, "{:?}", exit.message);
+    }
+
+    #[test]
+    fn hamt() {
+        let mut rt = MockRuntime::new(1000);
+        let exit = rt.invoke(0, None, |_| {
+            let mut hamt = Hamt::<_, String>::new_with_bit_width(Blockstore, 5);
+            for i in 0..100 {
+                hamt.set(format!("{i}").into_bytes().into(), format!("value {i}"))
+                    .unwrap();
+            }
+            let root = hamt.flush().unwrap();
+
+            let hamt = Hamt::<_, String>::load_with_bit_width(&root, Blockstore, 5).unwrap();
+            assert_eq!(hamt.get(b"42".as_slice()).unwrap().unwrap(), "value 42");
+            NO_DATA_BLOCK_ID
+        });
+        assert_eq!(exit.code, ExitCode::OK, "{:?}", exit.message);
+    }
+}
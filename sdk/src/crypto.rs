@@ -24,7 +24,7 @@ use crate::{SyscallResult, status_code_to_bool, sys};
 #[cfg(feature = "verify-signature")]
 /// Verifies that a signature is valid for an address and plaintext.
 ///
-/// NOTE: This only supports f1 and f3 addresses.
+/// NOTE: This only supports f1 and f3 addresses, and f410 addresses with delegated signatures.
 pub fn verify_signature(
     signature: &Signature,
     signer: &Address,
@@ -50,14 +50,17 @@ pub fn verify_signature(
 #[cfg(not(feature = "verify-signature"))]
 /// Verifies that a signature is valid for an address and plaintext.
 ///
-/// NOTE: This only supports f1 and f3 addresses.
+/// NOTE: This only supports f1 and f3 addresses, and f410 addresses with delegated signatures.
 pub fn verify_signature(
     signature: &Signature,
     addr: &Address,
     plaintext: &[u8],
 ) -> SyscallResult<bool> {
     use fvm_shared::{
-        address::{Payload, Protocol},
+        address::{
+            Payload, Protocol,
+            eth::{ETH_ADDRESS_LEN, EthAddress},
+        },
         crypto::signature::SignatureType,
     };
 
@@ -94,6 +97,32 @@ pub fn verify_signature(
                 )
             };
 
+            Ok(addr == &addr_recovered)
+        }
+        SignatureType::Delegated => {
+            if addr.protocol() != Protocol::Delegated {
+                return Err(ErrorNumber::IllegalArgument);
+            }
+
+            let sig: &[u8; SECP_SIG_LEN] = sig_bytes
+                .try_into()
+                .map_err(|_| ErrorNumber::IllegalArgument)?;
+
+            let mut digest = [0u8; SECP_SIG_MESSAGE_HASH_SIZE];
+            hash_into(SupportedHashes::Keccak256, plaintext, &mut digest);
+
+            // The eth address is the last 20 bytes of the keccak hash of the public key, without
+            // its tag byte.
+            let addr_recovered = {
+                let pub_key = recover_secp_public_key(&digest, sig)?;
+                let mut hash = [0u8; 32];
+                hash_into(SupportedHashes::Keccak256, &pub_key[1..], &mut hash);
+                let eth_addr: [u8; ETH_ADDRESS_LEN] = hash[32 - ETH_ADDRESS_LEN..]
+                    .try_into()
+                    .expect("eth addresses are 20 bytes");
+                EthAddress(eth_addr).to_delegated_address()
+            };
+
             Ok(addr == &addr_recovered)
         }
     }
@@ -0,0 +1,130 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Dispatching invocations to typed method handlers.
+use fvm_ipld_encoding::de::DeserializeOwned;
+use fvm_ipld_encoding::ser::Serialize;
+use fvm_ipld_encoding::{DAG_CBOR, from_slice, to_vec};
+use fvm_shared::MethodNum;
+use fvm_shared::error::ExitCode;
+use fvm_shared::sys::BlockId;
+
+use crate::{CBOR_NULL, NO_DATA_BLOCK_ID, ipld, message, vm};
+
+/// Dispatches an invocation to the handler of its method, decoding the parameters and encoding the
+/// return value of the handler. Aborts with `USR_SERIALIZATION` if the parameters can't be decoded
+/// or the return value can't be encoded, and with `USR_UNHANDLED_MESSAGE` if no handler matches the
+/// method.
+///
+/// Missing parameters are decoded as `null` (i.e., handlers taking `()` or an `Option` can be
+/// invoked without parameters), and `null` return values are returned as no data.
+///
+/// ```ignore
+/// #[unsafe(no_mangle)]
+/// pub fn invoke(params: u32) -> u32 {
+///     fvm_sdk::initialize();
+///     Dispatcher::new(params)
+///         .method(1, |params: ConstructorParams| constructor(params))
+///         .method(2, |()| count())
+///         .dispatch()
+/// }
+/// ```
+pub struct Dispatcher {
+    method: MethodNum,
+    params: BlockId,
+    ret: Option<BlockId>,
+}
+
+impl Dispatcher {
+    /// Create a dispatcher for the current invocation, given the parameters passed to `invoke`.
+    pub fn new(params: BlockId) -> Self {
+        Dispatcher {
+            method: message::method_number(),
+            params,
+            ret: None,
+        }
+    }
+
+    /// Handle the given method, if it's the method invoked.
+    pub fn method<P, R>(mut self, method: MethodNum, handler: impl FnOnce(P) -> R) -> Self
+    where
+        P: DeserializeOwned,
+        R: Serialize,
+    {
+        if self.ret.is_none() && self.method == method {
+            let params = decode_params(self.params);
+            self.ret = Some(encode_return(&handler(params)));
+        }
+        self
+    }
+
+    /// Returns the block of the return value of the handler, to be returned by `invoke`.
+    pub fn dispatch(self) -> BlockId {
+        self.ret.unwrap_or_else(|| {
+            vm::abort(
+                ExitCode::USR_UNHANDLED_MESSAGE.value(),
+                Some(&format!("unhandled method {}", self.method)),
+            )
+        })
+    }
+}
+
+fn decode_params<P: DeserializeOwned>(id: BlockId) -> P {
+    let params = message::params_raw(id).expect("failed to read the parameters");
+    match params {
+        Some(block) => block.deserialize(),
+        None => from_slice(CBOR_NULL),
+    }
+    .unwrap_or_else(|e| {
+        vm::abort(
+            ExitCode::USR_SERIALIZATION.value(),
+            Some(&format!("failed to decode the parameters: {e}")),
+        )
+    })
+}
+
+fn encode_return<R: Serialize>(ret: &R) -> BlockId {
+    let data = to_vec(ret).unwrap_or_else(|e| {
+        vm::abort(
+            ExitCode::USR_SERIALIZATION.value(),
+            Some(&format!("failed to encode the return value: {e}")),
+        )
+    });
+    if data == CBOR_NULL {
+        return NO_DATA_BLOCK_ID;
+    }
+    ipld::put_block(DAG_CBOR, &data).expect("failed to store the return value")
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use fvm_ipld_encoding::ipld_block::IpldBlock;
+
+    use super::*;
+    use crate::testing::MockRuntime;
+
+    fn invoke(params: u32) -> u32 {
+        Dispatcher::new(params)
+            .method(1, |(a, b): (u64, u64)| a + b)
+            .method(2, |()| ())
+            .dispatch()
+    }
+
+    #[test]
+    fn dispatch() {
+        let mut rt = MockRuntime::new(1000);
+        let params = IpldBlock::serialize_cbor(&(1u64, 2u64)).unwrap();
+        let exit = rt.invoke(1, params, invoke);
+        assert_eq!(exit.code, ExitCode::OK);
+        assert_eq!(exit.data.unwrap().deserialize::<u64>().unwrap(), 3);
+
+        let exit = rt.invoke(2, None, invoke);
+        assert_eq!(exit.code, ExitCode::OK);
+        assert_eq!(exit.data, None);
+
+        assert_eq!(rt.invoke(1, None, invoke).code, ExitCode::USR_SERIALIZATION);
+        assert_eq!(
+            rt.invoke(3, None, invoke).code,
+            ExitCode::USR_UNHANDLED_MESSAGE
+        );
+    }
+}
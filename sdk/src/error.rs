@@ -29,3 +29,78 @@ pub enum EpochBoundsError {
     #[error("the requested epoch exceeds the maximum lookback")]
     ExceedsLookback,
 }
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum StateError {
+    #[error("actor has no state")]
+    NoState,
+    #[error("failed to read or write the state: {0}")]
+    Ipld(fvm_shared::error::ErrorNumber),
+    #[error("failed to encode or decode the state: {0}")]
+    Serialization(String),
+    #[error(transparent)]
+    Update(#[from] StateUpdateError),
+}
+
+impl From<StateReadError> for StateError {
+    fn from(_: StateReadError) -> Self {
+        StateError::NoState
+    }
+}
+
+impl From<fvm_shared::error::ErrorNumber> for StateError {
+    fn from(e: fvm_shared::error::ErrorNumber) -> Self {
+        StateError::Ipld(e)
+    }
+}
+
+impl From<fvm_ipld_encoding::Error> for StateError {
+    fn from(e: fvm_ipld_encoding::Error) -> Self {
+        StateError::Serialization(e.to_string())
+    }
+}
+
+impl StateError {
+    /// The exit code to abort with on this error.
+    pub fn exit_code(&self) -> fvm_shared::error::ExitCode {
+        use fvm_shared::error::ExitCode;
+        match self {
+            StateError::Serialization(_) => ExitCode::USR_SERIALIZATION,
+            StateError::Update(StateUpdateError::ReadOnly) => ExitCode::USR_READ_ONLY,
+            _ => ExitCode::USR_ILLEGAL_STATE,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum SendError {
+    #[error("failed to encode the parameters: {0}")]
+    Params(String),
+    #[error("send failed: {0}")]
+    Syscall(fvm_shared::error::ErrorNumber),
+    #[error("recipient exited with {code}")]
+    Exit {
+        code: fvm_shared::error::ExitCode,
+        data: Option<fvm_ipld_encoding::ipld_block::IpldBlock>,
+    },
+    #[error("failed to decode the return value: {0}")]
+    Return(String),
+}
+
+impl From<fvm_shared::error::ErrorNumber> for SendError {
+    fn from(e: fvm_shared::error::ErrorNumber) -> Self {
+        SendError::Syscall(e)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Error, Eq, PartialEq)]
+pub enum MethodNameError {
+    #[error("method name is empty")]
+    Empty,
+    #[error("method name must start with an uppercase letter or an underscore")]
+    IllegalStart,
+    #[error("method name must only contain alphanumeric characters and underscores")]
+    IllegalCharacter,
+    #[error("no valid method number in the hash of the method name")]
+    Indeterminable,
+}
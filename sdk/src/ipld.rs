@@ -68,6 +68,14 @@ pub fn get(cid: &Cid) -> SyscallResult<Vec<u8>> {
     }
 }
 
+/// Closes the block referenced by BlockId, releasing it. Actors reading many blocks (e.g., iterating
+/// over large HAMTs) should close the blocks they're done with, as open blocks are otherwise
+/// retained until the end of the invocation.
+#[cfg(feature = "block-close")]
+pub fn close_block(id: fvm_shared::sys::BlockId) -> SyscallResult<()> {
+    unsafe { sys::ipld::block_close(id) }
+}
+
 /// Gets the data of the block referenced by BlockId. If the caller knows the size, this function
 /// will read the block in a single syscall. Otherwise, any block over 1KiB will take two syscalls.
 pub fn get_block(id: fvm_shared::sys::BlockId, size_hint: Option<u32>) -> SyscallResult<Vec<u8>> {
@@ -1,8 +1,10 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 pub mod actor;
+pub mod blockstore;
 pub mod crypto;
 pub mod debug;
+pub mod dispatch;
 pub mod error;
 pub mod event;
 pub mod gas;
@@ -12,12 +14,18 @@ pub mod network;
 pub mod rand;
 pub mod send;
 pub mod sself;
+pub mod state;
 pub mod sys;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod vm;
 
 /// BlockID representing nil parameters or return data.
 pub const NO_DATA_BLOCK_ID: u32 = 0;
 
+/// CBOR `null`, standing in for missing parameters and return values when (de)serializing them.
+pub(crate) const CBOR_NULL: &[u8] = &[0xf6];
+
 // TODO: provide a custom panic handler?
 
 #[inline]
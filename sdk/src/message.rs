@@ -8,53 +8,68 @@ use fvm_shared::{ActorID, MethodNum};
 
 use crate::{NO_DATA_BLOCK_ID, SyscallResult, sys};
 
+#[cfg(not(feature = "testing"))]
 lazy_static::lazy_static! {
-    pub(crate) static ref MESSAGE_CONTEXT: MessageContext = {
+    static ref MESSAGE_CONTEXT: MessageContext = {
         unsafe {
             sys::vm::message_context().expect("failed to lookup message context")
         }
     };
 }
 
+/// Returns the context of the current message, looked up once per invocation.
+#[cfg(not(feature = "testing"))]
+#[inline(always)]
+pub(crate) fn message_context() -> MessageContext {
+    *MESSAGE_CONTEXT
+}
+
+/// Returns the context of the current message. When testing, it's looked up on every call, as the
+/// mock runtime's context changes between tests.
+#[cfg(feature = "testing")]
+pub(crate) fn message_context() -> MessageContext {
+    unsafe { sys::vm::message_context().expect("failed to lookup message context") }
+}
+
 /// Returns the nonce from the (explicit) message.
 #[inline(always)]
 pub fn nonce() -> u64 {
-    MESSAGE_CONTEXT.nonce
+    message_context().nonce
 }
 
 /// Returns the ID address of the caller.
 #[inline(always)]
 pub fn caller() -> ActorID {
-    MESSAGE_CONTEXT.caller
+    message_context().caller
 }
 
 /// Returns the ID address of the origin
 #[inline(always)]
 pub fn origin() -> ActorID {
-    MESSAGE_CONTEXT.origin
+    message_context().origin
 }
 
 /// Returns the ID address of the actor.
 #[inline(always)]
 pub fn receiver() -> ActorID {
-    MESSAGE_CONTEXT.receiver
+    message_context().receiver
 }
 
 /// Returns the message's method number.
 #[inline(always)]
 pub fn method_number() -> MethodNum {
-    MESSAGE_CONTEXT.method_number
+    message_context().method_number
 }
 
 /// Returns the value received from the caller in AttoFIL.
 #[inline(always)]
 pub fn value_received() -> TokenAmount {
-    MESSAGE_CONTEXT.value_received.into()
+    message_context().value_received.into()
 }
 
 /// Returns the execution gas premium
 pub fn gas_premium() -> TokenAmount {
-    MESSAGE_CONTEXT.gas_premium.into()
+    message_context().gas_premium.into()
 }
 
 /// Returns the message parameters as an [`Option<IpldBlock>`].
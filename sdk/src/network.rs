@@ -12,28 +12,43 @@ use fvm_shared::version::NetworkVersion;
 use crate::error::EpochBoundsError;
 use crate::sys;
 
+#[cfg(not(feature = "testing"))]
 lazy_static::lazy_static! {
-    pub(crate) static ref NETWORK_CONTEXT: NetworkContext = {
+    static ref NETWORK_CONTEXT: NetworkContext = {
         unsafe {
             sys::network::context().expect("failed to lookup network context")
         }
     };
 }
 
+/// Returns the network context, looked up once per invocation.
+#[cfg(not(feature = "testing"))]
+#[inline(always)]
+fn network_context() -> NetworkContext {
+    *NETWORK_CONTEXT
+}
+
+/// Returns the network context. When testing, it's looked up on every call, as the mock runtime's
+/// context changes between tests.
+#[cfg(feature = "testing")]
+fn network_context() -> NetworkContext {
+    unsafe { sys::network::context().expect("failed to lookup network context") }
+}
+
 pub fn chain_id() -> ChainID {
-    NETWORK_CONTEXT.chain_id.into()
+    network_context().chain_id.into()
 }
 
 pub fn curr_epoch() -> ChainEpoch {
-    NETWORK_CONTEXT.epoch
+    network_context().epoch
 }
 
 pub fn version() -> NetworkVersion {
-    NETWORK_CONTEXT.network_version
+    network_context().network_version
 }
 
 pub fn base_fee() -> TokenAmount {
-    NETWORK_CONTEXT.base_fee.into()
+    network_context().base_fee.into()
 }
 
 pub fn total_fil_circ_supply() -> TokenAmount {
@@ -46,7 +61,7 @@ pub fn total_fil_circ_supply() -> TokenAmount {
 
 /// Returns the current block time in seconds since the EPOCH.
 pub fn tipset_timestamp() -> u64 {
-    NETWORK_CONTEXT.timestamp
+    network_context().timestamp
 }
 
 /// Returns the tipset CID of the specified epoch, if available. Allows querying from now up to
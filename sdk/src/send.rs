@@ -2,14 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 use std::convert::TryInto;
 
+use fvm_ipld_encoding::de::DeserializeOwned;
 use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::ser::Serialize;
+use fvm_ipld_encoding::{DAG_CBOR, from_slice, to_vec};
 use fvm_shared::address::Address;
 use fvm_shared::econ::TokenAmount;
 use fvm_shared::error::ErrorNumber;
 use fvm_shared::sys::SendFlags;
 use fvm_shared::{MethodNum, Response};
 
-use crate::{NO_DATA_BLOCK_ID, SyscallResult, build_response, sys};
+use crate::error::{MethodNameError, SendError};
+use crate::{CBOR_NULL, NO_DATA_BLOCK_ID, SyscallResult, build_response, sys};
 
 /// Sends a message to another actor.
 pub fn send(
@@ -47,3 +51,163 @@ pub fn send(
         build_response(send)
     }
 }
+
+/// Sends a message to another actor, encoding the parameters and decoding the return value as
+/// DAG-CBOR. Fails if the recipient exits with a non-zero exit code.
+///
+/// `null` parameters are sent as no parameters, and no return value is decoded as `null` (i.e., as
+/// `()` or `None`).
+pub fn send_typed<P, R>(
+    to: &Address,
+    method: MethodNum,
+    params: &P,
+    value: TokenAmount,
+    gas_limit: Option<u64>,
+    flags: SendFlags,
+) -> Result<R, SendError>
+where
+    P: Serialize + ?Sized,
+    R: DeserializeOwned,
+{
+    let params = to_vec(params).map_err(|e| SendError::Params(e.to_string()))?;
+    let params = (params != CBOR_NULL).then_some(IpldBlock {
+        codec: DAG_CBOR,
+        data: params,
+    });
+
+    let Response {
+        exit_code,
+        return_data,
+    } = send(to, method, params, value, gas_limit, flags)?;
+    if !exit_code.is_success() {
+        return Err(SendError::Exit {
+            code: exit_code,
+            data: return_data,
+        });
+    }
+
+    match return_data {
+        Some(ret) => ret.deserialize(),
+        None => from_slice(CBOR_NULL),
+    }
+    .map_err(|e| SendError::Return(e.to_string()))
+}
+
+/// Calls a method of another actor in read-only mode, without transferring value. See
+/// [`send_typed`].
+pub fn call_read_only<P, R>(to: &Address, method: MethodNum, params: &P) -> Result<R, SendError>
+where
+    P: Serialize + ?Sized,
+    R: DeserializeOwned,
+{
+    send_typed(
+        to,
+        method,
+        params,
+        TokenAmount::default(),
+        None,
+        SendFlags::READ_ONLY,
+    )
+}
+
+/// The first method number that can be derived from a method name. Lower method numbers are
+/// reserved.
+const FIRST_HASHED_METHOD: MethodNum = 1 << 24;
+
+/// Computes the method number of a method name, as specified by
+/// [FRC-0042](https://github.com/filecoin-project/FIPs/blob/master/FRCs/frc-0042.md).
+///
+/// Method names must start with an uppercase letter or an underscore, and only contain
+/// alphanumeric characters and underscores.
+pub fn method_hash(name: &str) -> Result<MethodNum, MethodNameError> {
+    match name.chars().next() {
+        None => return Err(MethodNameError::Empty),
+        Some(c) if !(c.is_ascii_uppercase() || c == '_') => {
+            return Err(MethodNameError::IllegalStart);
+        }
+        _ => {}
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(MethodNameError::IllegalCharacter);
+    }
+
+    // Hash natively so method numbers can be computed outside of the FVM (e.g., by clients).
+    let digest = blake2b_simd::Params::new()
+        .hash_length(64)
+        .hash(format!("1|{name}").as_bytes());
+    digest
+        .as_bytes()
+        .chunks_exact(4)
+        .map(|chunk| u32::from_be_bytes(chunk.try_into().unwrap()) as MethodNum)
+        .find(|&method| method >= FIRST_HASHED_METHOD)
+        .ok_or(MethodNameError::Indeterminable)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use fvm_shared::error::ExitCode;
+
+    use super::*;
+    use crate::testing::{ExpectedSend, MockRuntime};
+
+    #[test]
+    fn typed_send() {
+        let mut rt = MockRuntime::new(1000);
+        rt.expect_send(ExpectedSend {
+            to: Address::new_id(100),
+            method: 2,
+            params: IpldBlock::serialize_dag_cbor(&(1u64, 2u64)).unwrap(),
+            result: Ok(Response {
+                exit_code: ExitCode::OK,
+                return_data: IpldBlock::serialize_dag_cbor(&3u64).unwrap(),
+            }),
+            ..Default::default()
+        })
+        .expect_send(ExpectedSend {
+            to: Address::new_id(100),
+            method: 3,
+            flags: SendFlags::READ_ONLY,
+            result: Ok(Response {
+                exit_code: ExitCode::USR_FORBIDDEN,
+                return_data: None,
+            }),
+            ..Default::default()
+        });
+
+        let exit = rt.invoke(0, None, |_| {
+            let to = Address::new_id(100);
+            let sum: u64 = send_typed(
+                &to,
+                2,
+                &(1u64, 2u64),
+                TokenAmount::default(),
+                None,
+                SendFlags::empty(),
+            )
+            .unwrap();
+            assert_eq!(sum, 3);
+            assert_eq!(
+                call_read_only::<_, ()>(&to, 3, &()).unwrap_err(),
+                SendError::Exit {
+                    code: ExitCode::USR_FORBIDDEN,
+                    data: None
+                }
+            );
+            NO_DATA_BLOCK_ID
+        });
+        assert_eq!(exit.code, ExitCode::OK, "{:?}", exit.message);
+        rt.verify();
+    }
+
+    #[test]
+    fn frc42_method_hash() {
+        assert_eq!(method_hash("Receive"), Ok(3726118371));
+        assert_eq!(method_hash("AuthenticateMessage"), Ok(2643134072));
+        assert_eq!(method_hash(""), Err(MethodNameError::Empty));
+        assert_eq!(method_hash("receive"), Err(MethodNameError::IllegalStart));
+        assert_eq!(
+            method_hash("Re-ceive"),
+            Err(MethodNameError::IllegalCharacter)
+        );
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Typed access to the state of the calling actor.
+use std::ops::{Deref, DerefMut};
+
+use cid::Cid;
+use fvm_ipld_encoding::DAG_CBOR;
+use fvm_ipld_encoding::de::DeserializeOwned;
+use fvm_ipld_encoding::ser::Serialize;
+use fvm_shared::crypto::hash::SupportedHashes;
+
+use crate::error::StateError;
+use crate::{ipld, sself};
+
+/// The state of the calling actor, stored as a DAG-CBOR object at its state root.
+///
+/// ```ignore
+/// // Load, mutate and save the state.
+/// let mut state = StateObject::<State>::load()?;
+/// state.count += 1;
+/// state.save()?;
+///
+/// // Or, equivalently, only saving the state if the closure succeeds.
+/// StateObject::<State>::transaction(|state| {
+///     state.count += 1;
+///     Ok::<_, StateError>(())
+/// })?;
+/// ```
+#[derive(Debug)]
+pub struct StateObject<T> {
+    state: T,
+    root: Cid,
+}
+
+impl<T> StateObject<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Store the initial state of the actor, and set it as the state root.
+    pub fn create(state: T) -> Result<Self, StateError> {
+        let root = store(&state)?;
+        Ok(StateObject { state, root })
+    }
+
+    /// Load the state of the actor from its state root.
+    pub fn load() -> Result<Self, StateError> {
+        let root = sself::root()?;
+        let state = fvm_ipld_encoding::from_slice(&ipld::get(&root)?)?;
+        Ok(StateObject { state, root })
+    }
+
+    /// Store the state, and set it as the state root. Returns the new state root.
+    pub fn save(&mut self) -> Result<Cid, StateError> {
+        self.root = store(&self.state)?;
+        Ok(self.root)
+    }
+
+    /// Load the state, apply `f` to it, and save it if `f` succeeds.
+    pub fn transaction<R, E>(f: impl FnOnce(&mut T) -> Result<R, E>) -> Result<R, E>
+    where
+        E: From<StateError>,
+    {
+        let mut state = Self::load()?;
+        let ret = f(&mut state)?;
+        state.save()?;
+        Ok(ret)
+    }
+
+    /// The state root the state was last loaded from or saved to.
+    pub fn root(&self) -> Cid {
+        self.root
+    }
+
+    pub fn into_inner(self) -> T {
+        self.state
+    }
+}
+
+impl<T> Deref for StateObject<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.state
+    }
+}
+
+impl<T> DerefMut for StateObject<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.state
+    }
+}
+
+fn store<T: Serialize>(state: &T) -> Result<Cid, StateError> {
+    let root = ipld::put(
+        SupportedHashes::Blake2b256 as u64,
+        32,
+        DAG_CBOR,
+        &fvm_ipld_encoding::to_vec(state)?,
+    )?;
+    sself::set_root(&root)?;
+    Ok(root)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use fvm_shared::error::ExitCode;
+
+    use super::*;
+    use crate::NO_DATA_BLOCK_ID;
+    use crate::testing::MockRuntime;
+
+    #[test]
+    fn load_and_save() {
+        let mut rt = MockRuntime::new(1000);
+        let exit = rt.invoke(0, None, |_| {
+            assert_eq!(StateObject::<u64>::load().unwrap_err(), StateError::NoState);
+            let mut state = StateObject::create(1u64).unwrap();
+            *state += 1;
+            let root = state.save().unwrap();
+            assert_eq!(sself::root().unwrap(), root);
+
+            // Failed transactions aren't saved.
+            StateObject::<u64>::transaction(|state| {
+                *state += 1;
+                Err::<(), _>(StateError::NoState)
+            })
+            .unwrap_err();
+            StateObject::<u64>::transaction(|state| {
+                *state *= 10;
+                Ok::<_, StateError>(())
+            })
+            .unwrap();
+            NO_DATA_BLOCK_ID
+        });
+        assert_eq!(exit.code, ExitCode::OK, "{:?}", exit.message);
+        assert_eq!(rt.state::<u64>(), Some(20));
+    }
+}
@@ -9,7 +9,7 @@ pub use fvm_shared::sys::out::send::*;
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "actor";
 
     /// Resolves the ID address of an actor.
@@ -10,7 +10,7 @@ pub use fvm_shared::sys::out::crypto::*;
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "crypto";
 
     /// Verifies that a signature is valid for an f1 or f3 address and plaintext.
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 //! Syscalls for debugging.
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "debug";
 
     /// Returns if we're in debug mode. A zero or positive return value means
@@ -10,7 +10,7 @@ use crate::sys::ErrorNumber::*;
 #[doc(inline)]
 pub use fvm_shared::sys::EventEntry;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "event";
 
     /// Emits an actor event to be recorded in the receipt.
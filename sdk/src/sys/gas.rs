@@ -6,7 +6,7 @@
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "gas";
 
     /// Charge gas.
@@ -12,7 +12,7 @@ pub use fvm_shared::sys::out::ipld::*;
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "ipld";
 
     /// Opens a block from the "reachable" set, returning an ID for the block, its codec, and its
@@ -91,6 +91,17 @@ super::fvm_syscalls! {
     /// | [`InvalidHandle`] | if the handle isn't known. |
     pub fn block_stat(id: u32) -> Result<IpldStat>;
 
+    /// Closes the specified block, releasing it. The handle becomes invalid, and may be reused by
+    /// blocks opened or created later. The children of the block remain reachable.
+    ///
+    /// # Errors
+    ///
+    /// | Error             | Reason                     |
+    /// |-------------------|----------------------------|
+    /// | [`InvalidHandle`] | if the handle isn't known. |
+    #[cfg(feature = "block-close")]
+    pub fn block_close(id: u32) -> Result<()>;
+
     /// Computes the given block's CID, writing the resulting CID into `cid`.
     ///
     /// The returned CID is added to the reachable set.
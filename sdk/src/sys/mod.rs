@@ -185,3 +185,48 @@ macro_rules! fvm_syscalls {
 }
 
 pub use fvm_syscalls;
+
+/// Generate the shims of the FVM's syscalls: the Wasm imports, or calls into the mock runtime when
+/// the `testing` feature is enabled.
+#[cfg(not(feature = "testing"))]
+macro_rules! syscalls {
+    ($($tokens:tt)*) => {
+        $crate::sys::fvm_syscalls! { $($tokens)* }
+    };
+}
+
+/// Generate the shims of the FVM's syscalls: the Wasm imports, or calls into the mock runtime when
+/// the `testing` feature is enabled.
+#[cfg(feature = "testing")]
+macro_rules! syscalls {
+    // Returns a value (possibly empty).
+    (module = $module:literal; $(#[$attrs:meta])* $v:vis fn $name:ident($($args:ident : $args_ty:ty),*$(,)?) -> Result<$ret:ty>; $($rest:tt)*) => {
+        $(#[$attrs])*
+        #[allow(clippy::missing_safety_doc)]
+        #[allow(clippy::too_many_arguments)]
+        $v unsafe fn $name($($args:$args_ty),*) -> Result<$ret, $crate::sys::ErrorNumber> {
+            unsafe { $crate::testing::sys::$name($($args),*) }
+        }
+        $crate::sys::syscalls! {
+            module = $module;
+            $($rest)*
+        }
+    };
+    // Does not return.
+    (module = $module:literal; $(#[$attrs:meta])* $v:vis fn $name:ident($($args:ident : $args_ty:ty),*$(,)?) -> !; $($rest:tt)*) => {
+        $(#[$attrs])*
+        #[allow(clippy::missing_safety_doc)]
+        #[allow(clippy::too_many_arguments)]
+        $v unsafe fn $name($($args:$args_ty),*) -> ! {
+            unsafe { $crate::testing::sys::$name($($args),*) }
+        }
+        $crate::sys::syscalls! {
+            module = $module;
+            $($rest)*
+        }
+    };
+    // Base case.
+    (module = $module:literal;) => {};
+}
+
+pub(crate) use syscalls;
@@ -9,7 +9,7 @@ pub use fvm_shared::sys::out::network::NetworkContext;
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "network";
 
     /// Gets the circulating supply.
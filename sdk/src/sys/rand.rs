@@ -8,7 +8,7 @@ use fvm_shared::randomness::RANDOMNESS_LENGTH;
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "rand";
 
     /// Gets 32 bytes of randomness from the ticket chain,
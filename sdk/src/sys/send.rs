@@ -11,7 +11,7 @@ pub use fvm_shared::sys::out::send::*;
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "send";
 
     /// Sends a message to another actor, and returns the exit code and block ID of the return
@@ -6,7 +6,7 @@
 #[cfg(doc)]
 use crate::sys::ErrorNumber::*;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "self";
 
     /// Gets the current root for the calling actor.
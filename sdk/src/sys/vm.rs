@@ -5,7 +5,7 @@
 #[doc(inline)]
 pub use fvm_shared::sys::out::vm::MessageContext;
 
-super::fvm_syscalls! {
+super::syscalls! {
     module = "vm";
 
     /// Abort execution with the given code and optional message and data for the return value.
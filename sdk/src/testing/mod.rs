@@ -0,0 +1,657 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! A mock runtime to unit-test actors natively, without compiling them to Wasm.
+//!
+//! With the `testing` feature enabled, the SDK's syscalls are served in-process by a
+//! [`MockRuntime`] installed on the current thread instead of being imported from the FVM:
+//!
+//! ```ignore
+//! let mut rt = MockRuntime::new(1000);
+//! rt.set_caller(100).set_value_received(TokenAmount::from_atto(10));
+//! rt.expect_send(ExpectedSend {
+//!     to: Address::new_id(101),
+//!     method: 2,
+//!     ..Default::default()
+//! });
+//!
+//! let exit = rt.invoke(1, None, invoke);
+//! assert_eq!(exit.code, ExitCode::OK);
+//! rt.verify();
+//! ```
+//!
+//! The mock runtime doesn't meter gas (it only records explicit charges), and doesn't enforce IPLD
+//! reachability: any block put in its blockstore can be opened.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::panic::AssertUnwindSafe;
+
+use cid::Cid;
+use fvm_ipld_encoding::DAG_CBOR;
+use fvm_ipld_encoding::de::DeserializeOwned;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_ipld_encoding::ser::Serialize;
+use fvm_shared::address::Address;
+use fvm_shared::chainid::ChainID;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::{ErrorNumber, ExitCode};
+use fvm_shared::event::ActorEvent;
+use fvm_shared::randomness::RANDOMNESS_LENGTH;
+use fvm_shared::sys::SendFlags;
+use fvm_shared::sys::out::network::NetworkContext;
+use fvm_shared::sys::out::vm::{ContextFlags, MessageContext};
+use fvm_shared::version::NetworkVersion;
+use fvm_shared::{ActorID, MethodNum, Response};
+use multihash_codetable::{Code, MultihashDigest};
+
+use crate::{NO_DATA_BLOCK_ID, SyscallResult};
+
+pub(crate) mod sys;
+
+thread_local! {
+    static STATE: RefCell<Option<MockState>> = const { RefCell::new(None) };
+    /// The first failed expectation, reported by [`MockRuntime::invoke`] even if the actor caught
+    /// the panic.
+    static FAILURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs a function with the state of the mock runtime installed on this thread.
+fn with_state<R>(f: impl FnOnce(&mut MockState) -> R) -> R {
+    STATE.with_borrow_mut(|state| {
+        f(state
+            .as_mut()
+            .expect("no mock runtime installed on this thread"))
+    })
+}
+
+/// Fails the test, even if the actor catches the panic.
+fn fail(message: String) -> ! {
+    FAILURE.with_borrow_mut(|failure| {
+        failure.get_or_insert_with(|| message.clone());
+    });
+    panic!("{message}")
+}
+
+/// A send expected by the mock runtime, and the result to return to the actor.
+#[derive(Clone, Debug)]
+pub struct ExpectedSend {
+    pub to: Address,
+    pub method: MethodNum,
+    pub params: Option<IpldBlock>,
+    pub value: TokenAmount,
+    pub gas_limit: Option<u64>,
+    pub flags: SendFlags,
+    /// The response of the recipient, or the error of the syscall.
+    pub result: SyscallResult<Response>,
+}
+
+impl Default for ExpectedSend {
+    fn default() -> Self {
+        ExpectedSend {
+            to: Address::new_id(0),
+            method: 0,
+            params: None,
+            value: TokenAmount::default(),
+            gas_limit: None,
+            flags: SendFlags::default(),
+            result: Ok(Response {
+                exit_code: ExitCode::OK,
+                return_data: None,
+            }),
+        }
+    }
+}
+
+/// How an invocation ended: by returning, or by exiting or aborting explicitly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exit {
+    pub code: ExitCode,
+    pub data: Option<IpldBlock>,
+    pub message: Option<String>,
+}
+
+/// The state of the mock runtime, accessed by the syscalls.
+struct MockState {
+    message: MessageContext,
+    network: NetworkContext,
+    circ_supply: TokenAmount,
+    balance: TokenAmount,
+    deleted: bool,
+    root: Option<Cid>,
+    store: HashMap<Cid, Vec<u8>>,
+    /// The open blocks, by block ID minus one.
+    blocks: Vec<Option<IpldBlock>>,
+    free_blocks: Vec<u32>,
+    id_addresses: HashMap<Address, ActorID>,
+    chain_randomness: HashMap<ChainEpoch, [u8; RANDOMNESS_LENGTH]>,
+    beacon_randomness: HashMap<ChainEpoch, [u8; RANDOMNESS_LENGTH]>,
+    tipset_cids: HashMap<ChainEpoch, Cid>,
+    expected_sends: VecDeque<ExpectedSend>,
+    events: Vec<ActorEvent>,
+    gas_charged: u64,
+    logs: Vec<String>,
+}
+
+const ZERO: fvm_shared::sys::TokenAmount = fvm_shared::sys::TokenAmount { lo: 0, hi: 0 };
+
+impl MockState {
+    fn new(receiver: ActorID) -> Self {
+        MockState {
+            message: MessageContext {
+                origin: 0,
+                nonce: 0,
+                caller: 0,
+                receiver,
+                method_number: 0,
+                value_received: ZERO,
+                gas_premium: ZERO,
+                flags: ContextFlags::empty(),
+            },
+            network: NetworkContext {
+                epoch: 0,
+                timestamp: 0,
+                base_fee: ZERO,
+                chain_id: 0,
+                network_version: NetworkVersion::MAX,
+            },
+            circ_supply: TokenAmount::default(),
+            balance: TokenAmount::default(),
+            deleted: false,
+            root: None,
+            store: HashMap::new(),
+            blocks: Vec::new(),
+            free_blocks: Vec::new(),
+            id_addresses: HashMap::new(),
+            chain_randomness: HashMap::new(),
+            beacon_randomness: HashMap::new(),
+            tipset_cids: HashMap::new(),
+            expected_sends: VecDeque::new(),
+            events: Vec::new(),
+            gas_charged: 0,
+            logs: Vec::new(),
+        }
+    }
+
+    /// Adds a block to the open blocks, reusing the IDs of closed blocks like the FVM does.
+    fn open_block(&mut self, block: IpldBlock) -> u32 {
+        match self.free_blocks.pop() {
+            Some(id) => {
+                self.blocks[id as usize - 1] = Some(block);
+                id
+            }
+            None => {
+                self.blocks.push(Some(block));
+                self.blocks.len() as u32
+            }
+        }
+    }
+
+    fn block(&self, id: u32) -> SyscallResult<&IpldBlock> {
+        (id as usize)
+            .checked_sub(1)
+            .and_then(|idx| self.blocks.get(idx))
+            .and_then(Option::as_ref)
+            .ok_or(ErrorNumber::InvalidHandle)
+    }
+
+    #[cfg(feature = "block-close")]
+    fn close_block(&mut self, id: u32) -> SyscallResult<()> {
+        (id as usize)
+            .checked_sub(1)
+            .and_then(|idx| self.blocks.get_mut(idx))
+            .and_then(Option::take)
+            .ok_or(ErrorNumber::InvalidHandle)?;
+        self.free_blocks.push(id);
+        Ok(())
+    }
+
+    fn put(&mut self, codec: u64, data: Vec<u8>) -> Cid {
+        let cid = Cid::new_v1(codec, Code::Blake2b256.digest(&data));
+        self.store.insert(cid, data);
+        cid
+    }
+}
+
+/// A mock of the FVM serving the syscalls of the actor code running on the current thread. See the
+/// [module documentation](self).
+///
+/// Only one mock runtime can be installed on a thread at a time; it's uninstalled when dropped.
+pub struct MockRuntime {
+    // The runtime is tied to the thread it's installed on.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl MockRuntime {
+    /// Install a mock runtime on the current thread, running the actor with the given ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a mock runtime is already installed on this thread.
+    pub fn new(receiver: ActorID) -> Self {
+        STATE.with_borrow_mut(|state| {
+            assert!(
+                state.is_none(),
+                "a mock runtime is already installed on this thread"
+            );
+            *state = Some(MockState::new(receiver));
+        });
+        FAILURE.with_borrow_mut(|failure| *failure = None);
+        MockRuntime {
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Invoke an actor entrypoint (usually `invoke`) with the given method and parameters, as the
+    /// FVM would. Aborts, exits and panics are caught and reported as the [`Exit`] of the
+    /// invocation, like the FVM does. All blocks left open by a previous invocation are closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the actor made an unexpected syscall.
+    pub fn invoke(
+        &mut self,
+        method: MethodNum,
+        params: Option<IpldBlock>,
+        entrypoint: impl FnOnce(u32) -> u32,
+    ) -> Exit {
+        let params_id = with_state(|s| {
+            s.message.method_number = method;
+            s.blocks.clear();
+            s.free_blocks.clear();
+            params.map_or(NO_DATA_BLOCK_ID, |p| s.open_block(p))
+        });
+
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| entrypoint(params_id)));
+
+        if let Some(failure) = FAILURE.with_borrow_mut(Option::take) {
+            panic!("{failure}");
+        }
+
+        match result {
+            Ok(NO_DATA_BLOCK_ID) => Exit {
+                code: ExitCode::OK,
+                data: None,
+                message: None,
+            },
+            Ok(id) => Exit {
+                code: ExitCode::OK,
+                data: Some(with_state(|s| {
+                    s.block(id)
+                        .cloned()
+                        .unwrap_or_else(|_| panic!("actor returned invalid block {id}"))
+                })),
+                message: None,
+            },
+            Err(payload) => match payload.downcast::<Exit>() {
+                Ok(exit) => *exit,
+                Err(payload) => Exit {
+                    code: ExitCode::USR_ASSERTION_FAILED,
+                    data: None,
+                    message: payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned()),
+                },
+            },
+        }
+    }
+
+    /// Assert that all the expected sends were made.
+    pub fn verify(&self) {
+        with_state(|s| {
+            assert!(
+                s.expected_sends.is_empty(),
+                "expected sends weren't made: {:?}",
+                s.expected_sends
+            )
+        })
+    }
+
+    pub fn set_caller(&mut self, caller: ActorID) -> &mut Self {
+        with_state(|s| s.message.caller = caller);
+        self
+    }
+
+    pub fn set_origin(&mut self, origin: ActorID) -> &mut Self {
+        with_state(|s| s.message.origin = origin);
+        self
+    }
+
+    pub fn set_nonce(&mut self, nonce: u64) -> &mut Self {
+        with_state(|s| s.message.nonce = nonce);
+        self
+    }
+
+    pub fn set_value_received(&mut self, value: TokenAmount) -> &mut Self {
+        with_state(|s| {
+            s.message.value_received = value.try_into().expect("value received out of range")
+        });
+        self
+    }
+
+    pub fn set_gas_premium(&mut self, premium: TokenAmount) -> &mut Self {
+        with_state(|s| {
+            s.message.gas_premium = premium.try_into().expect("gas premium out of range")
+        });
+        self
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) -> &mut Self {
+        with_state(|s| {
+            // The context is packed, so its fields can't be borrowed.
+            let mut flags = s.message.flags;
+            flags.set(ContextFlags::READ_ONLY, read_only);
+            s.message.flags = flags;
+        });
+        self
+    }
+
+    pub fn set_epoch(&mut self, epoch: ChainEpoch) -> &mut Self {
+        with_state(|s| s.network.epoch = epoch);
+        self
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: u64) -> &mut Self {
+        with_state(|s| s.network.timestamp = timestamp);
+        self
+    }
+
+    pub fn set_base_fee(&mut self, base_fee: TokenAmount) -> &mut Self {
+        with_state(|s| s.network.base_fee = base_fee.try_into().expect("base fee out of range"));
+        self
+    }
+
+    pub fn set_chain_id(&mut self, chain_id: ChainID) -> &mut Self {
+        with_state(|s| s.network.chain_id = chain_id.into());
+        self
+    }
+
+    pub fn set_network_version(&mut self, version: NetworkVersion) -> &mut Self {
+        with_state(|s| s.network.network_version = version);
+        self
+    }
+
+    pub fn set_circulating_supply(&mut self, supply: TokenAmount) -> &mut Self {
+        with_state(|s| s.circ_supply = supply);
+        self
+    }
+
+    /// Set the balance of the actor. Sends transfer value out of it.
+    pub fn set_balance(&mut self, balance: TokenAmount) -> &mut Self {
+        with_state(|s| s.balance = balance);
+        self
+    }
+
+    pub fn balance(&self) -> TokenAmount {
+        with_state(|s| s.balance.clone())
+    }
+
+    /// Make an address resolve to the given actor ID.
+    pub fn add_id_address(&mut self, address: Address, id: ActorID) -> &mut Self {
+        with_state(|s| s.id_addresses.insert(address, id));
+        self
+    }
+
+    pub fn set_chain_randomness(
+        &mut self,
+        epoch: ChainEpoch,
+        randomness: [u8; RANDOMNESS_LENGTH],
+    ) -> &mut Self {
+        with_state(|s| s.chain_randomness.insert(epoch, randomness));
+        self
+    }
+
+    pub fn set_beacon_randomness(
+        &mut self,
+        epoch: ChainEpoch,
+        randomness: [u8; RANDOMNESS_LENGTH],
+    ) -> &mut Self {
+        with_state(|s| s.beacon_randomness.insert(epoch, randomness));
+        self
+    }
+
+    pub fn set_tipset_cid(&mut self, epoch: ChainEpoch, cid: Cid) -> &mut Self {
+        with_state(|s| s.tipset_cids.insert(epoch, cid));
+        self
+    }
+
+    /// Expect a send from the actor. Sends must be made in the order they're expected.
+    pub fn expect_send(&mut self, send: ExpectedSend) -> &mut Self {
+        with_state(|s| s.expected_sends.push_back(send));
+        self
+    }
+
+    /// Put a block in the blockstore, returning its (Blake2b-256) CID.
+    pub fn put_block(&mut self, codec: u64, data: impl Into<Vec<u8>>) -> Cid {
+        with_state(|s| s.put(codec, data.into()))
+    }
+
+    /// Get a block from the blockstore.
+    pub fn get_block(&self, cid: &Cid) -> Option<Vec<u8>> {
+        with_state(|s| s.store.get(cid).cloned())
+    }
+
+    /// Put an object in the blockstore as DAG-CBOR, returning its CID.
+    pub fn put_cbor<T: Serialize>(&mut self, obj: &T) -> Cid {
+        let data = fvm_ipld_encoding::to_vec(obj).expect("failed to encode object");
+        self.put_block(DAG_CBOR, data)
+    }
+
+    /// Get a DAG-CBOR object from the blockstore.
+    pub fn get_cbor<T: DeserializeOwned>(&self, cid: &Cid) -> Option<T> {
+        self.get_block(cid)
+            .map(|data| fvm_ipld_encoding::from_slice(&data).expect("failed to decode object"))
+    }
+
+    /// The state root of the actor, as last set by the actor or [`MockRuntime::set_root`].
+    pub fn root(&self) -> Option<Cid> {
+        with_state(|s| s.root)
+    }
+
+    pub fn set_root(&mut self, root: Cid) -> &mut Self {
+        with_state(|s| s.root = Some(root));
+        self
+    }
+
+    /// Put the state of the actor in the blockstore, and set it as the state root.
+    pub fn set_state<T: Serialize>(&mut self, state: &T) -> Cid {
+        let root = self.put_cbor(state);
+        self.set_root(root);
+        root
+    }
+
+    /// The state of the actor, if it has a state root.
+    pub fn state<T: DeserializeOwned>(&self) -> Option<T> {
+        self.root()
+            .map(|root| self.get_cbor(&root).expect("state root not found"))
+    }
+
+    /// The events emitted by the actor, in order.
+    pub fn events(&self) -> Vec<ActorEvent> {
+        with_state(|s| s.events.clone())
+    }
+
+    /// Whether the actor deleted itself.
+    pub fn deleted(&self) -> bool {
+        with_state(|s| s.deleted)
+    }
+
+    /// The total gas the actor charged explicitly.
+    pub fn gas_charged(&self) -> u64 {
+        with_state(|s| s.gas_charged)
+    }
+
+    /// The messages logged by the actor.
+    pub fn logs(&self) -> Vec<String> {
+        with_state(|s| s.logs.clone())
+    }
+}
+
+impl Drop for MockRuntime {
+    fn drop(&mut self) {
+        STATE.with_borrow_mut(|state| *state = None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_encoding::IPLD_RAW;
+    use fvm_shared::event::{Entry, Flags};
+    use multihash_codetable::Code;
+
+    use super::*;
+    use crate::{event, ipld, message, network, rand, send, sself, vm};
+
+    /// A toy actor: method 1 stores its params as its state and emits an event, method 2 forwards
+    /// the value it received to its caller, and method 3 aborts.
+    fn invoke(params: u32) -> u32 {
+        match message::method_number() {
+            1 => {
+                let params = message::params_raw(params).unwrap().unwrap();
+                let cid =
+                    ipld::put(Code::Blake2b256.into(), 32, params.codec, &params.data).unwrap();
+                sself::set_root(&cid).unwrap();
+                event::emit_event(&ActorEvent::from(vec![Entry {
+                    flags: Flags::FLAG_INDEXED_ALL,
+                    key: "stored".into(),
+                    codec: IPLD_RAW,
+                    value: params.data,
+                }]))
+                .unwrap();
+                NO_DATA_BLOCK_ID
+            }
+            2 => {
+                let ret = send::send(
+                    &Address::new_id(message::caller()),
+                    0,
+                    None,
+                    message::value_received(),
+                    None,
+                    SendFlags::empty(),
+                )
+                .unwrap();
+                assert!(ret.exit_code.is_success());
+                NO_DATA_BLOCK_ID
+            }
+            3 => vm::abort(ExitCode::USR_FORBIDDEN.value(), Some("forbidden")),
+            _ => panic!("unknown method"),
+        }
+    }
+
+    #[test]
+    fn store_and_emit() {
+        let mut rt = MockRuntime::new(1000);
+        let params = IpldBlock {
+            codec: IPLD_RAW,
+            data: b"foo".to_vec(),
+        };
+        let exit = rt.invoke(1, Some(params.clone()), invoke);
+        assert_eq!(exit.code, ExitCode::OK);
+
+        let root = rt.root().unwrap();
+        assert_eq!(rt.get_block(&root).unwrap(), b"foo");
+        assert_eq!(
+            rt.events(),
+            vec![ActorEvent::from(vec![Entry {
+                flags: Flags::FLAG_INDEXED_ALL,
+                key: "stored".into(),
+                codec: IPLD_RAW,
+                value: b"foo".to_vec(),
+            }])]
+        );
+
+        // Nothing can be stored in read-only mode.
+        rt.set_read_only(true);
+        let exit = rt.invoke(1, Some(params), invoke);
+        assert_eq!(exit.code, ExitCode::USR_ASSERTION_FAILED);
+        assert_eq!(rt.events().len(), 1);
+    }
+
+    #[test]
+    fn send_and_abort() {
+        let mut rt = MockRuntime::new(1000);
+        rt.set_caller(100)
+            .set_value_received(TokenAmount::from_atto(10))
+            .set_balance(TokenAmount::from_atto(15))
+            .expect_send(ExpectedSend {
+                to: Address::new_id(100),
+                value: TokenAmount::from_atto(10),
+                ..Default::default()
+            });
+        assert_eq!(rt.invoke(2, None, invoke).code, ExitCode::OK);
+        rt.verify();
+        assert_eq!(rt.balance(), TokenAmount::from_atto(5));
+
+        // The balance is now too low to send the value again.
+        assert_eq!(
+            rt.invoke(2, None, invoke).code,
+            ExitCode::USR_ASSERTION_FAILED
+        );
+
+        let exit = rt.invoke(3, None, invoke);
+        assert_eq!(exit.code, ExitCode::USR_FORBIDDEN);
+        assert_eq!(exit.message.as_deref(), Some("forbidden"));
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected send")]
+    fn unexpected_send() {
+        let mut rt = MockRuntime::new(1000);
+        rt.invoke(2, None, |_| {
+            // The failure is reported even if the actor catches it.
+            let _ = std::panic::catch_unwind(|| invoke(NO_DATA_BLOCK_ID));
+            NO_DATA_BLOCK_ID
+        });
+    }
+
+    #[test]
+    fn network_and_randomness() {
+        let mut rt = MockRuntime::new(1000);
+        let tipset = rt.put_block(DAG_CBOR, b"tipset".to_vec());
+        rt.set_epoch(10)
+            .set_chain_id(ChainID::from(314))
+            .set_chain_randomness(9, [1; RANDOMNESS_LENGTH])
+            .set_beacon_randomness(9, [2; RANDOMNESS_LENGTH])
+            .set_tipset_cid(9, tipset);
+
+        rt.invoke(0, None, |_| {
+            assert_eq!(network::curr_epoch(), 10);
+            assert_eq!(u64::from(network::chain_id()), 314);
+            assert_eq!(message::receiver(), 1000);
+            assert_eq!(
+                rand::get_chain_randomness(9).unwrap(),
+                [1; RANDOMNESS_LENGTH]
+            );
+            assert_eq!(
+                rand::get_beacon_randomness(9).unwrap(),
+                [2; RANDOMNESS_LENGTH]
+            );
+            assert_eq!(network::tipset_cid(9).unwrap(), tipset);
+            assert!(network::tipset_cid(10).is_err());
+            NO_DATA_BLOCK_ID
+        });
+    }
+
+    #[test]
+    fn state() {
+        let mut rt = MockRuntime::new(1000);
+        rt.set_state(&(1u64, "foo".to_string()));
+        let exit = rt.invoke(0, None, |_| {
+            let state = ipld::get(&sself::root().unwrap()).unwrap();
+            let (n, s): (u64, String) = fvm_ipld_encoding::from_slice(&state).unwrap();
+            let cid = ipld::put(
+                Code::Blake2b256.into(),
+                32,
+                DAG_CBOR,
+                &fvm_ipld_encoding::to_vec(&(n + 1, s)).unwrap(),
+            )
+            .unwrap();
+            sself::set_root(&cid).unwrap();
+            NO_DATA_BLOCK_ID
+        });
+        assert_eq!(exit.code, ExitCode::OK);
+        assert_eq!(rt.state::<(u64, String)>(), Some((2, "foo".into())));
+    }
+}
@@ -0,0 +1,495 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! The syscalls of the mock runtime, with the signatures of the shims in [`crate::sys`].
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use std::io::Read;
+use std::slice;
+
+use cid::Cid;
+use fvm_ipld_encoding::ipld_block::IpldBlock;
+use fvm_shared::address::{Address, Payload};
+use fvm_shared::crypto::signature::{BLS_PUB_LEN, SECP_PUB_LEN};
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::error::{ErrorNumber, ExitCode};
+use fvm_shared::event::{ActorEvent, Entry};
+use fvm_shared::randomness::RANDOMNESS_LENGTH;
+use fvm_shared::sys::out::crypto::VerifyConsensusFault;
+use fvm_shared::sys::out::ipld::{IpldOpen, IpldStat};
+use fvm_shared::sys::out::network::NetworkContext;
+use fvm_shared::sys::out::send::Send;
+use fvm_shared::sys::out::vm::MessageContext;
+use fvm_shared::sys::{EventEntry, SendFlags};
+use multihash_codetable::{Code, MultihashDigest};
+
+use super::{Exit, MockState, fail, with_state};
+use crate::{NO_DATA_BLOCK_ID, SyscallResult};
+
+type Result<T> = SyscallResult<T>;
+
+/// Fails the test on syscalls the mock runtime doesn't implement.
+macro_rules! unsupported {
+    ($($(#[$attrs:meta])* fn $name:ident($($args:ident : $args_ty:ty),*$(,)?) -> $ret:ty;)*) => {
+        $(
+            $(#[$attrs])*
+            #[allow(unused_variables)]
+            pub unsafe fn $name($($args: $args_ty),*) -> $ret {
+                fail(format!(
+                    "the mock runtime doesn't support the {} syscall",
+                    stringify!($name)
+                ))
+            }
+        )*
+    };
+}
+
+/// Reads a CID from memory, without knowing its length.
+unsafe fn read_cid(ptr: *const u8) -> Result<Cid> {
+    struct PtrReader(*const u8);
+
+    impl Read for PtrReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            unsafe {
+                std::ptr::copy_nonoverlapping(self.0, buf.as_mut_ptr(), buf.len());
+                self.0 = self.0.add(buf.len());
+            }
+            Ok(buf.len())
+        }
+    }
+
+    Cid::read_bytes(PtrReader(ptr)).map_err(|_| ErrorNumber::IllegalCid)
+}
+
+/// Writes a CID to a buffer, returning its length.
+unsafe fn write_cid(cid: &Cid, ptr: *mut u8, len: u32) -> Result<u32> {
+    unsafe { write_bytes(&cid.to_bytes(), ptr, len) }
+}
+
+unsafe fn write_bytes(bytes: &[u8], ptr: *mut u8, len: u32) -> Result<u32> {
+    if bytes.len() > len as usize {
+        return Err(ErrorNumber::BufferTooSmall);
+    }
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) };
+    Ok(bytes.len() as u32)
+}
+
+unsafe fn read_bytes<'a>(ptr: *const u8, len: u32) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(ptr, len as usize) }
+    }
+}
+
+unsafe fn read_address(ptr: *const u8, len: u32) -> Result<Address> {
+    Address::from_bytes(unsafe { read_bytes(ptr, len) }).map_err(|_| ErrorNumber::IllegalArgument)
+}
+
+fn check_writable(s: &MockState) -> Result<()> {
+    if s.message.flags.read_only() {
+        Err(ErrorNumber::ReadOnly)
+    } else {
+        Ok(())
+    }
+}
+
+// actor
+
+pub unsafe fn resolve_address(addr_off: *const u8, addr_len: u32) -> Result<u64> {
+    let addr = unsafe { read_address(addr_off, addr_len) }?;
+    match addr.payload() {
+        Payload::ID(id) => Ok(*id),
+        _ => with_state(|s| s.id_addresses.get(&addr).copied()).ok_or(ErrorNumber::NotFound),
+    }
+}
+
+unsupported! {
+    fn lookup_delegated_address(
+        actor_id: u64,
+        addr_buf_off: *mut u8,
+        addr_buf_len: u32,
+    ) -> Result<u32>;
+    fn get_actor_code_cid(actor_id: u64, obuf_off: *mut u8, obuf_len: u32) -> Result<u32>;
+    fn get_builtin_actor_type(cid_off: *const u8) -> Result<i32>;
+    fn get_code_cid_for_type(typ: i32, obuf_off: *mut u8, obuf_len: u32) -> Result<u32>;
+    fn next_actor_address(obuf_off: *mut u8, obuf_len: u32) -> Result<u32>;
+    fn create_actor(
+        actor_id: u64,
+        typ_off: *const u8,
+        delegated_addr_off: *const u8,
+        delegated_addr_len: u32,
+    ) -> Result<()>;
+    #[cfg(feature = "upgrade-actor")]
+    fn upgrade_actor(new_code_cid_off: *const u8, params: u32) -> Result<Send>;
+    #[cfg(feature = "m2-native")]
+    fn install_actor(cid_off: *const u8) -> Result<()>;
+    fn balance_of(actor_id: u64) -> Result<fvm_shared::sys::TokenAmount>;
+}
+
+// crypto
+
+unsupported! {
+    #[cfg(feature = "verify-signature")]
+    fn verify_signature(
+        sig_type: u32,
+        sig_off: *const u8,
+        sig_len: u32,
+        addr_off: *const u8,
+        addr_len: u32,
+        plaintext_off: *const u8,
+        plaintext_len: u32,
+    ) -> Result<i32>;
+    fn verify_bls_aggregate(
+        num_signers: u32,
+        sig_off: *const u8,
+        pub_keys_off: *const [u8; BLS_PUB_LEN],
+        plaintexts_off: *const u8,
+        plaintext_lens_off: *const u32,
+    ) -> Result<i32>;
+    fn recover_secp_public_key(hash_off: *const u8, sig_off: *const u8) -> Result<[u8; SECP_PUB_LEN]>;
+    fn compute_unsealed_sector_cid(
+        proof_type: i64,
+        pieces_off: *const u8,
+        pieces_len: u32,
+        cid_off: *mut u8,
+        cid_len: u32,
+    ) -> Result<u32>;
+    fn verify_post(info_off: *const u8, info_len: u32) -> Result<i32>;
+    fn verify_consensus_fault(
+        h1_off: *const u8,
+        h1_len: u32,
+        h2_off: *const u8,
+        h2_len: u32,
+        extra_off: *const u8,
+        extra_len: u32,
+    ) -> Result<VerifyConsensusFault>;
+    fn verify_aggregate_seals(agg_off: *const u8, agg_len: u32) -> Result<i32>;
+    fn verify_replica_update(rep_off: *const u8, rep_len: u32) -> Result<i32>;
+    fn batch_verify_seals(batch_off: *const u8, batch_len: u32, result_off: *const u8) -> Result<()>;
+}
+
+pub unsafe fn hash(
+    hash_code: u64,
+    data_off: *const u8,
+    data_len: u32,
+    digest_off: *mut u8,
+    digest_len: u32,
+) -> Result<u32> {
+    let code = Code::try_from(hash_code).map_err(|_| ErrorNumber::IllegalArgument)?;
+    let hash = code.digest(unsafe { read_bytes(data_off, data_len) });
+    let digest = &hash.digest()[..(hash.size() as usize).min(digest_len as usize)];
+    unsafe { write_bytes(digest, digest_off, digest_len) }
+}
+
+// debug
+
+pub unsafe fn enabled() -> Result<i32> {
+    // Logging goes through a global logger, which can only be installed once per process.
+    Ok(-1)
+}
+
+pub unsafe fn log(message: *const u8, message_len: u32) -> Result<()> {
+    let message = String::from_utf8_lossy(unsafe { read_bytes(message, message_len) });
+    with_state(|s| s.logs.push(message.into_owned()));
+    Ok(())
+}
+
+pub unsafe fn store_artifact(
+    _name_off: *const u8,
+    _name_len: u32,
+    _data_off: *const u8,
+    _data_len: u32,
+) -> Result<()> {
+    Ok(())
+}
+
+// event
+
+pub unsafe fn emit_event(
+    evt_off: *const EventEntry,
+    evt_len: u32,
+    key_off: *const u8,
+    key_len: u32,
+    value_off: *const u8,
+    value_len: u32,
+) -> Result<()> {
+    let entries = if evt_len == 0 {
+        &[]
+    } else {
+        unsafe { slice::from_raw_parts(evt_off, evt_len as usize) }
+    };
+    let mut keys = unsafe { read_bytes(key_off, key_len) };
+    let mut values = unsafe { read_bytes(value_off, value_len) };
+
+    let mut event = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let (k_len, v_len) = (entry.key_len as usize, entry.val_len as usize);
+        if k_len > keys.len() || v_len > values.len() {
+            return Err(ErrorNumber::IllegalArgument);
+        }
+        let (key, rest) = keys.split_at(k_len);
+        keys = rest;
+        let (value, rest) = values.split_at(v_len);
+        values = rest;
+        event.push(Entry {
+            flags: entry.flags,
+            key: String::from_utf8(key.to_vec()).map_err(|_| ErrorNumber::IllegalArgument)?,
+            codec: entry.codec,
+            value: value.to_vec(),
+        });
+    }
+
+    with_state(|s| {
+        check_writable(s)?;
+        s.events.push(ActorEvent::from(event));
+        Ok(())
+    })
+}
+
+// gas
+
+pub unsafe fn charge(_name_off: *const u8, _name_len: u32, amount: u64) -> Result<()> {
+    with_state(|s| s.gas_charged = s.gas_charged.saturating_add(amount));
+    Ok(())
+}
+
+pub unsafe fn available() -> Result<u64> {
+    Ok(with_state(|s| {
+        (i64::MAX as u64).saturating_sub(s.gas_charged)
+    }))
+}
+
+// ipld
+
+pub unsafe fn block_open(cid: *const u8) -> Result<IpldOpen> {
+    let cid = unsafe { read_cid(cid) }?;
+    with_state(|s| {
+        let data = s.store.get(&cid).ok_or(ErrorNumber::NotFound)?.clone();
+        let size = data.len() as u32;
+        let codec = cid.codec();
+        let id = s.open_block(IpldBlock { codec, data });
+        Ok(IpldOpen { codec, id, size })
+    })
+}
+
+pub unsafe fn block_create(codec: u64, data: *const u8, len: u32) -> Result<u32> {
+    let data = unsafe { read_bytes(data, len) }.to_vec();
+    Ok(with_state(|s| s.open_block(IpldBlock { codec, data })))
+}
+
+pub unsafe fn block_read(id: u32, offset: u32, obuf: *mut u8, max_len: u32) -> Result<i32> {
+    with_state(|s| {
+        let data = &s.block(id)?.data;
+        let start = (offset as usize).min(data.len());
+        let end = (offset as usize + max_len as usize).min(data.len());
+        let read = &data[start..end];
+        unsafe { std::ptr::copy_nonoverlapping(read.as_ptr(), obuf, read.len()) };
+        Ok((data.len() as i64 - offset as i64 - max_len as i64) as i32)
+    })
+}
+
+pub unsafe fn block_stat(id: u32) -> Result<IpldStat> {
+    with_state(|s| {
+        let block = s.block(id)?;
+        Ok(IpldStat {
+            codec: block.codec,
+            size: block.data.len() as u32,
+        })
+    })
+}
+
+#[cfg(feature = "block-close")]
+pub unsafe fn block_close(id: u32) -> Result<()> {
+    with_state(|s| s.close_block(id))
+}
+
+pub unsafe fn block_link(
+    id: u32,
+    hash_fun: u64,
+    hash_len: u32,
+    cid: *mut u8,
+    cid_max_len: u32,
+) -> Result<u32> {
+    // Like the FVM, only link blocks with 32-byte Blake2b-256 hashes.
+    if hash_fun != u64::from(Code::Blake2b256) || hash_len != 32 {
+        return Err(ErrorNumber::IllegalCid);
+    }
+    let k = with_state(|s| {
+        let block = s.block(id)?.clone();
+        let k = Cid::new_v1(block.codec, Code::Blake2b256.digest(&block.data));
+        s.store.insert(k, block.data);
+        Ok(k)
+    })?;
+    unsafe { write_cid(&k, cid, cid_max_len) }
+}
+
+// network
+
+pub unsafe fn total_fil_circ_supply() -> Result<fvm_shared::sys::TokenAmount> {
+    with_state(|s| {
+        Ok((&s.circ_supply)
+            .try_into()
+            .expect("circulating supply out of range"))
+    })
+}
+
+pub unsafe fn tipset_cid(epoch: i64, ret_off: *mut u8, ret_len: u32) -> Result<u32> {
+    let k = with_state(|s| {
+        if epoch < 0 || epoch >= s.network.epoch {
+            return Err(ErrorNumber::IllegalArgument);
+        }
+        Ok(s.tipset_cids.get(&epoch).copied())
+    })?
+    .unwrap_or_else(|| fail(format!("no tipset CID set for epoch {epoch}")));
+    unsafe { write_cid(&k, ret_off, ret_len) }
+}
+
+pub unsafe fn context() -> Result<NetworkContext> {
+    Ok(with_state(|s| s.network))
+}
+
+// rand
+
+pub unsafe fn get_chain_randomness(epoch: i64) -> Result<[u8; RANDOMNESS_LENGTH]> {
+    with_state(|s| s.chain_randomness.get(&epoch).copied())
+        .ok_or_else(|| fail(format!("no chain randomness set for epoch {epoch}")))
+}
+
+pub unsafe fn get_beacon_randomness(epoch: i64) -> Result<[u8; RANDOMNESS_LENGTH]> {
+    with_state(|s| s.beacon_randomness.get(&epoch).copied())
+        .ok_or_else(|| fail(format!("no beacon randomness set for epoch {epoch}")))
+}
+
+// send
+
+pub unsafe fn send(
+    recipient_off: *const u8,
+    recipient_len: u32,
+    method: u64,
+    params: u32,
+    value_hi: u64,
+    value_lo: u64,
+    gas_limit: u64,
+    flags: SendFlags,
+) -> Result<Send> {
+    let to = unsafe { read_address(recipient_off, recipient_len) }?;
+    let value = TokenAmount::from(fvm_shared::sys::TokenAmount {
+        lo: value_lo,
+        hi: value_hi,
+    });
+    let gas_limit = (gas_limit != u64::MAX).then_some(gas_limit);
+
+    with_state(|s| {
+        let params = match params {
+            NO_DATA_BLOCK_ID => None,
+            id => Some(s.block(id)?.clone()),
+        };
+        if !value.is_zero() {
+            check_writable(s)?;
+        }
+        if value > s.balance {
+            return Err(ErrorNumber::InsufficientFunds);
+        }
+
+        let Some(expected) = s.expected_sends.pop_front() else {
+            fail(format!(
+                "unexpected send to {to}, method {method}, value {value}"
+            ))
+        };
+        if expected.to != to
+            || expected.method != method
+            || expected.params != params
+            || expected.value != value
+            || expected.gas_limit != gas_limit
+            || expected.flags != flags
+        {
+            fail(format!(
+                "unexpected send to {to}, method {method}, params {params:?}, value {value}, \
+                 gas limit {gas_limit:?}, flags {flags:?}; expected {expected:?}"
+            ))
+        }
+
+        let response = expected.result?;
+        if response.exit_code.is_success() {
+            s.balance -= &value;
+        }
+        let (return_id, return_codec, return_size) = match response.return_data {
+            Some(block) => {
+                let (codec, size) = (block.codec, block.data.len() as u32);
+                (s.open_block(block), codec, size)
+            }
+            None => (NO_DATA_BLOCK_ID, 0, 0),
+        };
+        Ok(Send {
+            exit_code: response.exit_code.value(),
+            return_id,
+            return_codec,
+            return_size,
+        })
+    })
+}
+
+// self
+
+pub unsafe fn root(cid: *mut u8, cid_max_len: u32) -> Result<u32> {
+    let root = with_state(|s| match s.root {
+        Some(root) if !s.deleted => Ok(root),
+        _ => Err(ErrorNumber::IllegalOperation),
+    })?;
+    unsafe { write_cid(&root, cid, cid_max_len) }
+}
+
+pub unsafe fn set_root(cid: *const u8) -> Result<()> {
+    let cid = unsafe { read_cid(cid) }?;
+    with_state(|s| {
+        if s.deleted {
+            return Err(ErrorNumber::IllegalOperation);
+        }
+        check_writable(s)?;
+        if !s.store.contains_key(&cid) {
+            return Err(ErrorNumber::NotFound);
+        }
+        s.root = Some(cid);
+        Ok(())
+    })
+}
+
+pub unsafe fn current_balance() -> Result<fvm_shared::sys::TokenAmount> {
+    with_state(|s| Ok((&s.balance).try_into().expect("balance out of range")))
+}
+
+pub unsafe fn self_destruct(burn_funds: bool) -> Result<()> {
+    with_state(|s| {
+        check_writable(s)?;
+        if !burn_funds && !s.balance.is_zero() {
+            return Err(ErrorNumber::IllegalOperation);
+        }
+        s.balance = TokenAmount::default();
+        s.deleted = true;
+        Ok(())
+    })
+}
+
+// vm
+
+pub unsafe fn exit(code: u32, blk_id: u32, message_off: *const u8, message_len: u32) -> ! {
+    let data = match blk_id {
+        NO_DATA_BLOCK_ID => None,
+        id => Some(
+            with_state(|s| s.block(id).cloned())
+                .unwrap_or_else(|_| fail(format!("exited with invalid block {id}"))),
+        ),
+    };
+    let message = (message_len > 0).then(|| {
+        String::from_utf8_lossy(unsafe { read_bytes(message_off, message_len) }).into_owned()
+    });
+    std::panic::resume_unwind(Box::new(Exit {
+        code: ExitCode::new(code),
+        data,
+        message,
+    }))
+}
+
+pub unsafe fn message_context() -> Result<MessageContext> {
+    Ok(with_state(|s| s.message))
+}
@@ -0,0 +1,280 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Ethereum addresses, and their conversions to and from Filecoin addresses.
+//!
+//! Ethereum addresses map to `f410` addresses (delegated addresses in the namespace of the
+//! Ethereum Address Manager), except for "masked" ID addresses (`0xff` followed by 11 zero bytes
+//! and a big-endian actor ID), which map to ID addresses.
+//!
+//! Computing addresses from public keys and EIP-55 checksums requires Keccak-256, and is only
+//! available with the `crypto` or `signing` features. Without them, mixed-case addresses are
+//! parsed without validating their checksums.
+
+use std::fmt;
+use std::str::FromStr;
+
+use data_encoding::{HEXLOWER, HEXLOWER_PERMISSIVE};
+
+use super::{Address, Error, Payload};
+use crate::ActorID;
+
+/// The ID of the Ethereum Address Manager actor, the namespace of `f410` addresses.
+pub const EAM_ACTOR_ID: ActorID = 10;
+
+/// The length of Ethereum addresses.
+pub const ETH_ADDRESS_LEN: usize = 20;
+
+/// The prefix of Ethereum addresses masking actor IDs.
+const ID_MASK_PREFIX: [u8; 12] = [0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+#[cfg(any(feature = "crypto", feature = "signing"))]
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    use multihash_codetable::{Code, MultihashDigest};
+
+    Code::Keccak256
+        .digest(data)
+        .digest()
+        .try_into()
+        .expect("keccak256 digests are 32 bytes")
+}
+
+/// A 20-byte Ethereum address. Formatted as lowercase hex, see `to_checksummed_string` (with the
+/// `crypto` or `signing` features) for [EIP-55](https://eips.ethereum.org/EIPS/eip-55) formatting.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EthAddress(pub [u8; ETH_ADDRESS_LEN]);
+
+impl EthAddress {
+    /// The Ethereum address masking an actor ID.
+    pub const fn from_id(id: ActorID) -> Self {
+        let mut bytes = [0u8; ETH_ADDRESS_LEN];
+        bytes[0] = 0xff;
+        let id = id.to_be_bytes();
+        let mut i = 0;
+        while i < id.len() {
+            bytes[ID_MASK_PREFIX.len() + i] = id[i];
+            i += 1;
+        }
+        EthAddress(bytes)
+    }
+
+    /// The actor ID masked by this address, if it's a masked ID address.
+    pub fn as_id(&self) -> Option<ActorID> {
+        let (prefix, id) = self.0.split_at(ID_MASK_PREFIX.len());
+        (prefix == ID_MASK_PREFIX).then(|| ActorID::from_be_bytes(id.try_into().unwrap()))
+    }
+
+    /// The Ethereum address of a secp256k1 public key, in uncompressed form: the last 20 bytes of
+    /// the Keccak-256 hash of the key, without its `0x04` tag.
+    #[cfg(any(feature = "crypto", feature = "signing"))]
+    pub fn from_secp256k1_public_key(pubkey: &[u8]) -> Result<Self, Error> {
+        if pubkey.len() != super::SECP_PUB_LEN {
+            return Err(Error::InvalidSECPLength(pubkey.len()));
+        }
+        if pubkey[0] != 0x04 {
+            return Err(Error::InvalidPayload);
+        }
+        let hash = keccak256(&pubkey[1..]);
+        Ok(EthAddress(
+            hash[hash.len() - ETH_ADDRESS_LEN..].try_into().unwrap(),
+        ))
+    }
+
+    /// The Filecoin address of this address: an ID address if it's a masked ID address, and an
+    /// `f410` address otherwise.
+    pub fn to_filecoin_address(&self) -> Address {
+        match self.as_id() {
+            Some(id) => Address::new_id(id),
+            None => self.to_delegated_address(),
+        }
+    }
+
+    /// The `f410` address of this address, even if it's a masked ID address.
+    pub fn to_delegated_address(&self) -> Address {
+        Address::new_delegated(EAM_ACTOR_ID, &self.0).expect("eth addresses are valid subaddresses")
+    }
+
+    /// Formats this address with an EIP-55 checksum.
+    #[cfg(any(feature = "crypto", feature = "signing"))]
+    pub fn to_checksummed_string(&self) -> String {
+        let hex = HEXLOWER.encode(&self.0);
+        let hash = keccak256(hex.as_bytes());
+
+        let mut s = String::with_capacity(2 + hex.len());
+        s.push_str("0x");
+        for (i, c) in hex.chars().enumerate() {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0xf;
+            s.push(if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            });
+        }
+        s
+    }
+}
+
+impl From<EthAddress> for Address {
+    fn from(addr: EthAddress) -> Self {
+        addr.to_filecoin_address()
+    }
+}
+
+impl TryFrom<&Address> for EthAddress {
+    type Error = Error;
+
+    /// Converts ID addresses to masked ID addresses, and `f410` addresses to their Ethereum
+    /// addresses.
+    fn try_from(addr: &Address) -> Result<Self, Error> {
+        match addr.payload() {
+            Payload::ID(id) => Ok(EthAddress::from_id(*id)),
+            Payload::Delegated(delegated) if delegated.namespace() == EAM_ACTOR_ID => delegated
+                .subaddress()
+                .try_into()
+                .map(EthAddress)
+                .map_err(|_| Error::InvalidPayloadLength(delegated.subaddress().len())),
+            Payload::Delegated(_) => Err(Error::InvalidPayload),
+            _ => Err(Error::NonDelegatedAddress),
+        }
+    }
+}
+
+impl TryFrom<Address> for EthAddress {
+    type Error = Error;
+
+    fn try_from(addr: Address) -> Result<Self, Error> {
+        EthAddress::try_from(&addr)
+    }
+}
+
+impl FromStr for EthAddress {
+    type Err = Error;
+
+    /// Parses a `0x`-prefixed hex address. Mixed-case addresses must have a valid EIP-55
+    /// checksum, which is only validated with the `crypto` or `signing` features: without them,
+    /// any mixed-case address is accepted.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let hex = s.strip_prefix("0x").ok_or(Error::InvalidPayload)?;
+        if hex.len() != 2 * ETH_ADDRESS_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let bytes = HEXLOWER_PERMISSIVE
+            .decode(hex.as_bytes())
+            .map_err(|_| Error::InvalidPayload)?;
+        let addr = EthAddress(bytes.try_into().unwrap());
+
+        #[cfg(any(feature = "crypto", feature = "signing"))]
+        {
+            let has_lower = hex.bytes().any(|b| b.is_ascii_lowercase());
+            let has_upper = hex.bytes().any(|b| b.is_ascii_uppercase());
+            if has_lower && has_upper && addr.to_checksummed_string() != s {
+                return Err(Error::InvalidChecksum);
+            }
+        }
+        Ok(addr)
+    }
+}
+
+impl fmt::Display for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", HEXLOWER.encode(&self.0))
+    }
+}
+
+impl fmt::Debug for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EthAddress")
+            .field(&format_args!("\"{}\"", self))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(any(feature = "crypto", feature = "signing"))]
+    fn eip55() {
+        for s in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let addr: EthAddress = s.parse().unwrap();
+            assert_eq!(addr.to_checksummed_string(), s);
+            assert_eq!(addr.to_string(), s.to_lowercase());
+            assert_eq!(s.to_lowercase().parse::<EthAddress>(), Ok(addr));
+        }
+        assert_eq!(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD".parse::<EthAddress>(),
+            Err(Error::InvalidChecksum)
+        );
+        assert_eq!(
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea".parse::<EthAddress>(),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "crypto", feature = "signing")))]
+    fn unchecked_mixed_case() {
+        let addr: EthAddress = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            addr.to_string(),
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+        );
+    }
+
+    #[test]
+    fn filecoin_conversions() {
+        let masked = EthAddress::from_id(1234);
+        assert_eq!(
+            masked.to_string(),
+            "0xff000000000000000000000000000000000004d2"
+        );
+        assert_eq!(masked.as_id(), Some(1234));
+        assert_eq!(Address::from(masked), Address::new_id(1234));
+        assert_eq!(EthAddress::try_from(Address::new_id(1234)), Ok(masked));
+
+        let eth: EthAddress = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"
+            .parse()
+            .unwrap();
+        assert_eq!(eth.as_id(), None);
+        let f410 = Address::from(eth);
+        assert_eq!(f410, Address::new_delegated(EAM_ACTOR_ID, &eth.0).unwrap());
+        assert_eq!(EthAddress::try_from(&f410), Ok(eth));
+        assert_eq!(
+            EthAddress::try_from(Address::new_delegated(11, &eth.0).unwrap()),
+            Err(Error::InvalidPayload)
+        );
+        assert_eq!(
+            EthAddress::try_from(Address::new_actor(b"actor")),
+            Err(Error::NonDelegatedAddress)
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "crypto", feature = "signing"))]
+    fn from_public_key() {
+        // The public key of the private key 1.
+        let pubkey = HEXLOWER
+            .decode(
+                b"0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+                  483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+            )
+            .unwrap();
+        assert_eq!(
+            EthAddress::from_secp256k1_public_key(&pubkey)
+                .unwrap()
+                .to_checksummed_string(),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        assert_eq!(
+            EthAddress::from_secp256k1_public_key(&pubkey[1..]),
+            Err(Error::InvalidSECPLength(64))
+        );
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod errors;
+pub mod eth;
 mod network;
 mod payload;
 mod protocol;
@@ -165,6 +166,21 @@ impl Address {
         self.payload.to_bytes()
     }
 
+    /// Formats the address with the prefix of the given network, regardless of the current
+    /// network.
+    pub fn to_string_with_network(&self, network: Network) -> String {
+        self.display_with_network(network).to_string()
+    }
+
+    /// Returns an adapter displaying the address with the prefix of the given network, regardless
+    /// of the current network.
+    pub fn display_with_network(&self, network: Network) -> AddressDisplay<'_> {
+        AddressDisplay {
+            address: self,
+            network,
+        }
+    }
+
     /// Get ID of the address. ID protocol only.
     pub fn id(&self) -> Result<u64, Error> {
         match self.payload {
@@ -176,10 +192,24 @@ impl Address {
 
 impl fmt::Display for Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let protocol = self.protocol();
+        self.display_with_network(current_network()).fmt(f)
+    }
+}
+
+/// Displays an address with the prefix of a given network, instead of the current network. See
+/// [`Address::display_with_network`].
+#[derive(Copy, Clone, Debug)]
+pub struct AddressDisplay<'a> {
+    address: &'a Address,
+    network: Network,
+}
+
+impl fmt::Display for AddressDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let protocol = self.address.protocol();
 
         // write `fP` where P is the protocol number.
-        write!(f, "{}{}", current_network().to_prefix(), protocol)?;
+        write!(f, "{}{}", self.network.to_prefix(), protocol)?;
 
         fn write_payload(
             f: &mut fmt::Formatter<'_>,
@@ -203,7 +233,7 @@ impl fmt::Display for Address {
             f.write_str(&ADDRESS_ENCODER.encode(&buf))
         }
 
-        match self.payload() {
+        match self.address.payload() {
             Payload::ID(id) => write!(f, "{}", id),
             Payload::Secp256k1(data) | Payload::Actor(data) => {
                 write_payload(f, protocol, None, data)
@@ -42,7 +42,8 @@ impl Network {
         }
     }
 
-    /// Parse an address belonging to this network.
+    /// Parse an address belonging to this network, regardless of the current network. Fails if the
+    /// address has the prefix of another network.
     pub fn parse_address(self, addr: &str) -> Result<Address, Error> {
         let (addr, network) = super::parse_address(addr)?;
         if network != self {
@@ -75,6 +76,27 @@ mod tests {
     use super::*;
     use crate::address::Address;
 
+    #[test]
+    fn explicit_network() {
+        let addr = Address::new_actor(b"actor");
+        let mainnet = addr.to_string_with_network(Network::Mainnet);
+        let testnet = format!("{}", addr.display_with_network(Network::Testnet));
+        assert!(mainnet.starts_with("f2"));
+        assert!(testnet.starts_with("t2"));
+        assert_eq!(mainnet[1..], testnet[1..]);
+
+        assert_eq!(Network::Mainnet.parse_address(&mainnet), Ok(addr));
+        assert_eq!(Network::Testnet.parse_address(&testnet), Ok(addr));
+        assert_eq!(
+            Network::Mainnet.parse_address(&testnet),
+            Err(Error::UnknownNetwork)
+        );
+        assert_eq!(
+            Network::Testnet.parse_address(&mainnet),
+            Err(Error::UnknownNetwork)
+        );
+    }
+
     // We fork this test into a new process because it messes with global state.
     use rusty_fork::rusty_fork_test;
     rusty_fork_test! {
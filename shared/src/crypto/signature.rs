@@ -33,6 +33,9 @@ pub const SECP_SIG_MESSAGE_HASH_SIZE: usize = 32;
 pub enum SignatureType {
     Secp256k1 = 1,
     BLS = 2,
+    /// A secp256k1 signature over the Keccak-256 hash of the data (e.g., an RLP-encoded Ethereum
+    /// transaction), signed by the owner of an `f410` address.
+    Delegated = 3,
 }
 
 /// A cryptographic signature, represented in bytes, of any key protocol.
@@ -68,7 +71,7 @@ impl<'de> de::Deserialize<'de> for Signature {
 
         // Remove signature type byte
         let sig_type = SignatureType::from_u8(bytes[0])
-            .ok_or_else(|| de::Error::custom("Invalid signature type byte (must be 1 or 2)"))?;
+            .ok_or_else(|| de::Error::custom("Invalid signature type byte (must be 1, 2 or 3)"))?;
 
         Ok(Signature {
             bytes: bytes[1..].to_vec(),
@@ -94,6 +97,14 @@ impl Signature {
         }
     }
 
+    /// Creates a delegated Signature given the raw bytes.
+    pub fn new_delegated(bytes: Vec<u8>) -> Self {
+        Self {
+            sig_type: SignatureType::Delegated,
+            bytes,
+        }
+    }
+
     /// Returns reference to signature bytes.
     pub fn bytes(&self) -> &[u8] {
         &self.bytes
@@ -108,11 +119,12 @@ impl Signature {
 #[cfg(feature = "arb")]
 impl quickcheck::Arbitrary for SignatureType {
     fn arbitrary(g: &mut quickcheck::Gen) -> Self {
-        if bool::arbitrary(g) {
-            SignatureType::Secp256k1
-        } else {
-            SignatureType::BLS
-        }
+        *g.choose(&[
+            SignatureType::Secp256k1,
+            SignatureType::BLS,
+            SignatureType::Delegated,
+        ])
+        .unwrap()
     }
 }
 
@@ -144,6 +156,7 @@ pub fn verify(
     match sig_type {
         SignatureType::BLS => self::ops::verify_bls_sig(sig_data, data, addr),
         SignatureType::Secp256k1 => self::ops::verify_secp256k1_sig(sig_data, data, addr),
+        SignatureType::Delegated => self::ops::verify_delegated_sig(sig_data, data, addr),
     }
 }
 
@@ -153,8 +166,10 @@ pub mod ops {
         PublicKey as BlsPubKey, Serialize, Signature as BlsSignature, verify_messages,
     };
     use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey};
+    use multihash_codetable::{Code, MultihashDigest};
 
     use super::{Error, SECP_PUB_LEN, SECP_SIG_LEN, SECP_SIG_MESSAGE_HASH_SIZE};
+    use crate::address::eth::EthAddress;
     use crate::address::{Address, Protocol};
 
     /// Returns `String` error if a bls signature is invalid.
@@ -258,6 +273,41 @@ pub mod ops {
         }
     }
 
+    /// Returns `String` error if a delegated signature is invalid: the signature must be a secp256k1
+    /// signature over the Keccak-256 hash of the data, by the key of the given `f410` address.
+    pub fn verify_delegated_sig(
+        signature: &[u8],
+        data: &[u8],
+        addr: &Address,
+    ) -> Result<(), String> {
+        if addr.protocol() != Protocol::Delegated {
+            return Err(format!(
+                "cannot validate a delegated signature against a {} address",
+                addr.protocol()
+            ));
+        }
+
+        let sig: &[u8; SECP_SIG_LEN] = signature.try_into().map_err(|_| {
+            format!(
+                "Invalid delegated signature length. Was {}, must be 65",
+                signature.len()
+            )
+        })?;
+
+        let hash: [u8; SECP_SIG_MESSAGE_HASH_SIZE] = Code::Keccak256
+            .digest(data)
+            .digest()
+            .try_into()
+            .expect("keccak256 digests are 32 bytes");
+
+        let rec_addr = ecrecover_delegated(&hash, sig).map_err(|e| e.to_string())?;
+        if &rec_addr == addr {
+            Ok(())
+        } else {
+            Err("Delegated signature verification failed".to_owned())
+        }
+    }
+
     /// Return the public key used for signing a message given it's signing bytes hash and signature.
     pub fn recover_secp_public_key(
         hash: &[u8; SECP_SIG_MESSAGE_HASH_SIZE],
@@ -297,6 +347,83 @@ pub mod ops {
         let addr = Address::new_secp256k1(&key)?;
         Ok(addr)
     }
+
+    /// Return the `f410` (Ethereum) address of the signer of a message, given its signing bytes
+    /// hash and secp signature.
+    pub fn ecrecover_delegated(
+        hash: &[u8; 32],
+        signature: &[u8; SECP_SIG_LEN],
+    ) -> Result<Address, Error> {
+        let key = recover_secp_public_key(hash, signature)?;
+        Ok(EthAddress::from_secp256k1_public_key(&key)?.to_delegated_address())
+    }
+}
+
+/// Signing with secp256k1 and BLS private keys.
+#[cfg(feature = "signing")]
+pub mod signing {
+    pub use bls_signatures::PrivateKey as BlsPrivateKey;
+    use bls_signatures::Serialize;
+    pub use k256::ecdsa::SigningKey as Secp256k1PrivateKey;
+    use multihash_codetable::{Code, MultihashDigest};
+
+    use super::{Error, SECP_SIG_LEN, Signature};
+    use crate::address::Address;
+    use crate::address::eth::EthAddress;
+
+    /// Signs data with a secp256k1 key. Like Lotus, this signs the Blake2b-256 hash of the data,
+    /// and appends the recovery ID to the signature.
+    pub fn sign_secp256k1(key: &Secp256k1PrivateKey, data: &[u8]) -> Result<Signature, Error> {
+        let hash = blake2b_simd::Params::new()
+            .hash_length(32)
+            .to_state()
+            .update(data)
+            .finalize();
+        sign_prehash(key, hash.as_bytes()).map(Signature::new_secp256k1)
+    }
+
+    /// Signs data with a secp256k1 key, as the owner of an `f410` address. This signs the
+    /// Keccak-256 hash of the data, and appends the recovery ID to the signature.
+    pub fn sign_delegated(key: &Secp256k1PrivateKey, data: &[u8]) -> Result<Signature, Error> {
+        sign_prehash(key, Code::Keccak256.digest(data).digest()).map(Signature::new_delegated)
+    }
+
+    fn sign_prehash(key: &Secp256k1PrivateKey, hash: &[u8]) -> Result<Vec<u8>, Error> {
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(hash)
+            .map_err(|e| Error::SigningError(e.to_string()))?;
+
+        let mut bytes = Vec::with_capacity(SECP_SIG_LEN);
+        bytes.extend_from_slice(&signature.to_bytes());
+        bytes.push(recovery_id.to_byte());
+        Ok(bytes)
+    }
+
+    /// Signs data with a BLS key.
+    pub fn sign_bls(key: &BlsPrivateKey, data: &[u8]) -> Signature {
+        Signature::new_bls(key.sign(data).as_bytes())
+    }
+
+    /// The secp256k1 address of a secp256k1 key.
+    pub fn secp256k1_address(key: &Secp256k1PrivateKey) -> Address {
+        Address::new_secp256k1(key.verifying_key().to_encoded_point(false).as_bytes())
+            .expect("uncompressed public keys are valid secp256k1 address payloads")
+    }
+
+    /// The `f410` address of a secp256k1 key.
+    pub fn delegated_address(key: &Secp256k1PrivateKey) -> Address {
+        EthAddress::from_secp256k1_public_key(
+            key.verifying_key().to_encoded_point(false).as_bytes(),
+        )
+        .expect("uncompressed public keys have Ethereum addresses")
+        .to_delegated_address()
+    }
+
+    /// The BLS address of a BLS key.
+    pub fn bls_address(key: &BlsPrivateKey) -> Address {
+        Address::new_bls(&key.public_key().as_bytes())
+            .expect("public keys are valid BLS address payloads")
+    }
 }
 
 #[cfg(all(test, feature = "crypto"))]
@@ -311,7 +438,8 @@ mod tests {
     use super::ops::recover_secp_public_key;
     use super::*;
     use crate::Address;
-    use crate::crypto::signature::ops::{ecrecover, verify_bls_aggregate};
+    use crate::address::eth::EthAddress;
+    use crate::crypto::signature::ops::{ecrecover, ecrecover_delegated, verify_bls_aggregate};
 
     #[test]
     fn bls_agg_verify() {
@@ -408,6 +536,45 @@ mod tests {
         let recovered = recover_secp_public_key(&hash, &sig).unwrap();
         let hashed = Code::Keccak256.digest(&recovered[1..]);
         assert_eq!(expected, &hashed.digest()[12..]);
+
+        assert_eq!(
+            ecrecover_delegated(&hash, &sig).unwrap(),
+            Address::new_delegated(10, &expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn delegated_verify() {
+        let rng = &mut ChaCha8Rng::seed_from_u64(8);
+
+        let signing_key = SigningKey::random(rng);
+        let encoded_point = signing_key.verifying_key().to_encoded_point(false);
+        let eth_addr = EthAddress::from_secp256k1_public_key(encoded_point.as_bytes()).unwrap();
+        let f410_addr = eth_addr.to_delegated_address();
+
+        let data = b"rlp-encoded transaction";
+        let hash = Code::Keccak256.digest(data);
+        let (signature, recovery_id) = signing_key
+            .sign_prehash_recoverable(hash.digest())
+            .expect("signing should not fail");
+        let mut sig_bytes = signature.to_bytes().to_vec();
+        sig_bytes.push(recovery_id.to_byte());
+        let sig = Signature::new_delegated(sig_bytes);
+
+        sig.verify(data, &f410_addr).unwrap();
+        sig.verify(b"other data", &f410_addr).unwrap_err();
+        // The signer must be the f410 address, not the masked ID or key address.
+        sig.verify(
+            data,
+            &Address::new_secp256k1(encoded_point.as_bytes()).unwrap(),
+        )
+        .unwrap_err();
+        sig.verify(data, &Address::new_delegated(11, &eth_addr.0).unwrap())
+            .unwrap_err();
+        // Secp256k1 signatures are over Blake2b-256 hashes, not Keccak-256 hashes.
+        Signature::new_secp256k1(sig.bytes.clone())
+            .verify(data, &f410_addr)
+            .unwrap_err();
     }
 
     #[test]
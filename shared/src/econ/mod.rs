@@ -9,9 +9,13 @@ use num_bigint::BigInt;
 use num_integer::Integer;
 use num_traits::{Signed, Zero};
 use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
 
+pub use self::units::{ParseTokenAmountError, TokenAmountDisplay, TokenFormat, TokenUnit};
 use crate::bigint::bigint_ser;
 
+mod units;
+
 /// A quantity of native tokens.
 /// A token amount is an integer, but has a human interpretation as a value with
 /// 18 decimal places.
@@ -335,6 +339,38 @@ impl TokenAmount {
     }
 }
 
+/// The error returned by arithmetic on token amounts (e.g. [`TokenAmount::try_sub`]) that would
+/// result in a negative amount.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("token amount would be negative: {0}")]
+pub struct NegativeTokenAmount(pub TokenAmount);
+
+// Arithmetic failing on negative results, for balances which must never go negative.
+impl TokenAmount {
+    #[inline]
+    pub fn try_add(&self, other: &TokenAmount) -> Result<TokenAmount, NegativeTokenAmount> {
+        Self::non_negative(self + other)
+    }
+
+    #[inline]
+    pub fn try_sub(&self, other: &TokenAmount) -> Result<TokenAmount, NegativeTokenAmount> {
+        Self::non_negative(self - other)
+    }
+
+    #[inline]
+    pub fn try_mul(&self, other: impl Into<BigInt>) -> Result<TokenAmount, NegativeTokenAmount> {
+        Self::non_negative(self * other.into())
+    }
+
+    fn non_negative(amount: TokenAmount) -> Result<TokenAmount, NegativeTokenAmount> {
+        if amount.is_negative() {
+            Err(NegativeTokenAmount(amount))
+        } else {
+            Ok(amount)
+        }
+    }
+}
+
 impl Sum for TokenAmount {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
         Self::from_atto(iter.map(|t| t.atto).sum::<BigInt>())
@@ -372,7 +408,7 @@ mod test {
     use num_bigint::BigInt;
     use num_traits::Zero;
 
-    use super::TokenAmount;
+    use super::{NegativeTokenAmount, TokenAmount};
 
     fn whole(x: impl Into<BigInt>) -> TokenAmount {
         TokenAmount::from_whole(x)
@@ -456,6 +492,27 @@ mod test {
         assert_eq!(atto(4), a);
     }
 
+    #[test]
+    fn checked() {
+        assert_eq!(atto(10).try_add(&atto(5)), Ok(atto(15)));
+        assert_eq!(atto(10).try_add(&atto(-10)), Ok(atto(0)));
+        assert_eq!(
+            atto(10).try_add(&atto(-11)),
+            Err(NegativeTokenAmount(atto(-1)))
+        );
+        assert_eq!(atto(10).try_sub(&atto(10)), Ok(atto(0)));
+        assert_eq!(
+            atto(10).try_sub(&atto(11)),
+            Err(NegativeTokenAmount(atto(-1)))
+        );
+        assert_eq!(atto(10).try_mul(3), Ok(atto(30)));
+        assert_eq!(atto(10).try_mul(-3), Err(NegativeTokenAmount(atto(-30))));
+        assert_eq!(
+            NegativeTokenAmount(atto(-1)).to_string(),
+            "token amount would be negative: -0.000000000000000001"
+        );
+    }
+
     #[test]
     fn nano_fil() {
         assert_eq!(
@@ -0,0 +1,419 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::fmt;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+use thiserror::Error;
+
+use super::TokenAmount;
+
+/// A unit of native tokens, from the indivisible attoFIL (10^-18 FIL) to whole FIL.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TokenUnit {
+    Atto,
+    Femto,
+    Pico,
+    Nano,
+    Micro,
+    Milli,
+    Whole,
+}
+
+impl TokenUnit {
+    /// All units, from the smallest to the largest.
+    pub const ALL: [TokenUnit; 7] = [
+        TokenUnit::Atto,
+        TokenUnit::Femto,
+        TokenUnit::Pico,
+        TokenUnit::Nano,
+        TokenUnit::Micro,
+        TokenUnit::Milli,
+        TokenUnit::Whole,
+    ];
+
+    /// The number of attoFIL in this unit, as a power of 10.
+    pub const fn exponent(self) -> u32 {
+        match self {
+            TokenUnit::Atto => 0,
+            TokenUnit::Femto => 3,
+            TokenUnit::Pico => 6,
+            TokenUnit::Nano => 9,
+            TokenUnit::Micro => 12,
+            TokenUnit::Milli => 15,
+            TokenUnit::Whole => TokenAmount::DECIMALS as u32,
+        }
+    }
+
+    /// The SI symbol of this unit, e.g. `nFIL`.
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            TokenUnit::Atto => "aFIL",
+            TokenUnit::Femto => "fFIL",
+            TokenUnit::Pico => "pFIL",
+            TokenUnit::Nano => "nFIL",
+            TokenUnit::Micro => "μFIL",
+            TokenUnit::Milli => "mFIL",
+            TokenUnit::Whole => "FIL",
+        }
+    }
+
+    /// The largest unit in which the amount is at least one, or whole FIL for zero.
+    fn si(amount: &TokenAmount) -> Self {
+        let atto = amount.atto().abs();
+        if atto.is_zero() {
+            return TokenUnit::Whole;
+        }
+        TokenUnit::ALL
+            .into_iter()
+            .rev()
+            .find(|unit| atto >= unit.scale())
+            .unwrap_or(TokenUnit::Atto)
+    }
+
+    fn scale(self) -> BigInt {
+        BigInt::from(10u8).pow(self.exponent())
+    }
+}
+
+impl fmt::Display for TokenUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for TokenUnit {
+    type Err = ParseTokenAmountError;
+
+    /// Parses a unit by its SI symbol (e.g. `nFIL`) or its name (e.g. `nanoFIL`), ignoring case.
+    /// `uFIL` is accepted for microFIL.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "fil" => TokenUnit::Whole,
+            "millifil" | "mfil" => TokenUnit::Milli,
+            "microfil" | "μfil" | "ufil" => TokenUnit::Micro,
+            "nanofil" | "nfil" => TokenUnit::Nano,
+            "picofil" | "pfil" => TokenUnit::Pico,
+            "femtofil" | "ffil" => TokenUnit::Femto,
+            "attofil" | "afil" => TokenUnit::Atto,
+            _ => return Err(ParseTokenAmountError::UnknownUnit(s.to_owned())),
+        })
+    }
+}
+
+/// An error parsing a token amount or unit.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseTokenAmountError {
+    #[error("invalid token amount: {0:?}")]
+    InvalidNumber(String),
+    #[error("unknown token unit: {0:?}")]
+    UnknownUnit(String),
+    #[error("token amount {0:?} is more precise than its unit allows")]
+    ExcessPrecision(String),
+}
+
+impl TokenAmount {
+    /// Parses a decimal quantity of the given unit, without a unit suffix. Parsing is exact: it
+    /// fails if the quantity has more decimal places than the unit allows.
+    pub fn parse_in(number: &str, unit: TokenUnit) -> Result<Self, ParseTokenAmountError> {
+        let invalid = || ParseTokenAmountError::InvalidNumber(number.to_owned());
+
+        let (negative, digits) = match number.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if (int.is_empty() && frac.is_empty())
+            || !int.bytes().all(|b| b.is_ascii_digit())
+            || !frac.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let frac = frac.trim_end_matches('0');
+        let exponent = unit.exponent() as usize;
+        if frac.len() > exponent {
+            return Err(ParseTokenAmountError::ExcessPrecision(number.to_owned()));
+        }
+
+        let atto = format!("{int}{frac}{}", "0".repeat(exponent - frac.len()));
+        let atto = if atto.is_empty() {
+            BigInt::zero()
+        } else {
+            BigInt::parse_bytes(atto.as_bytes(), 10).ok_or_else(invalid)?
+        };
+        Ok(TokenAmount::from_atto(if negative { -atto } else { atto }))
+    }
+
+    /// Formats the token amount as configured, e.g. in nanoFIL or with a fixed precision.
+    pub fn display_with(&self, format: TokenFormat) -> TokenAmountDisplay<'_> {
+        TokenAmountDisplay {
+            amount: self,
+            format,
+        }
+    }
+}
+
+impl FromStr for TokenAmount {
+    type Err = ParseTokenAmountError;
+
+    /// Parses a decimal amount followed by an optional unit, e.g. `1.5 FIL`, `300nanoFIL`, or
+    /// `0.000001`. Amounts without a unit are in whole FIL, so the output of both [`Display`]
+    /// and [`TokenAmount::display_with`] parses back to the same amount (unless formatted with a
+    /// truncating precision).
+    ///
+    /// [`Display`]: fmt::Display
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, unit) = s.split_at(
+            s.find(|c: char| !matches!(c, '0'..='9' | '.' | '-' | '+'))
+                .unwrap_or(s.len()),
+        );
+        let unit = match unit.trim_start() {
+            "" => TokenUnit::Whole,
+            unit => unit.parse()?,
+        };
+        TokenAmount::parse_in(number, unit)
+    }
+}
+
+/// How to format a token amount with [`TokenAmount::display_with`]. By default, amounts are
+/// formatted in whole FIL, with all significant decimal places, e.g. `1.5 FIL`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TokenFormat {
+    unit: Option<TokenUnit>,
+    precision: Option<usize>,
+    trim_zeros: bool,
+}
+
+impl Default for TokenFormat {
+    fn default() -> Self {
+        TokenFormat::new()
+    }
+}
+
+impl TokenFormat {
+    pub const fn new() -> Self {
+        TokenFormat {
+            unit: Some(TokenUnit::Whole),
+            precision: None,
+            trim_zeros: true,
+        }
+    }
+
+    /// Formats amounts in the given unit.
+    pub const fn unit(mut self, unit: TokenUnit) -> Self {
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Formats each amount in the largest unit in which it's at least one, e.g. `1.5 mFIL`.
+    pub const fn si(mut self) -> Self {
+        self.unit = None;
+        self
+    }
+
+    /// Formats amounts with the given number of decimal places, truncating any others.
+    pub const fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Whether to drop trailing zeros after the decimal point (and the decimal point itself, if
+    /// nothing follows it). Enabled by default.
+    pub const fn trim_zeros(mut self, trim_zeros: bool) -> Self {
+        self.trim_zeros = trim_zeros;
+        self
+    }
+}
+
+/// A token amount formatted with a [`TokenFormat`], see [`TokenAmount::display_with`].
+#[derive(Copy, Clone, Debug)]
+pub struct TokenAmountDisplay<'a> {
+    amount: &'a TokenAmount,
+    format: TokenFormat,
+}
+
+impl fmt::Display for TokenAmountDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = self
+            .format
+            .unit
+            .unwrap_or_else(|| TokenUnit::si(self.amount));
+        let (int, frac) = self.amount.atto().abs().div_rem(&unit.scale());
+
+        let mut frac = match unit.exponent() as usize {
+            0 => String::new(),
+            exponent => format!("{:0>exponent$}", frac.to_str_radix(10)),
+        };
+        if let Some(precision) = self.format.precision {
+            if frac.len() > precision {
+                frac.truncate(precision);
+            } else {
+                frac.extend(std::iter::repeat_n('0', precision - frac.len()));
+            }
+        }
+        if self.format.trim_zeros {
+            frac.truncate(frac.trim_end_matches('0').len());
+        }
+
+        // Don't print amounts truncated to zero as "-0".
+        let truncated_to_zero = int.is_zero() && frac.bytes().all(|b| b == b'0');
+        let mut s = String::new();
+        if self.amount.is_negative() && !truncated_to_zero {
+            s.push('-');
+        }
+        s.push_str(&int.to_str_radix(10));
+        if !frac.is_empty() {
+            s.push('.');
+            s.push_str(&frac);
+        }
+        s.push(' ');
+        s.push_str(unit.symbol());
+        f.pad(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atto(x: impl Into<BigInt>) -> TokenAmount {
+        TokenAmount::from_atto(x)
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("1.5 FIL".parse(), Ok(TokenAmount::from_nano(1_500_000_000)));
+        assert_eq!("1.5fil".parse(), Ok(TokenAmount::from_nano(1_500_000_000)));
+        assert_eq!("300 nanoFIL".parse(), Ok(TokenAmount::from_nano(300)));
+        assert_eq!("300 nFIL".parse(), Ok(TokenAmount::from_nano(300)));
+        assert_eq!("0.000001 FIL".parse(), Ok(atto(1_000_000_000_000u64)));
+        assert_eq!("0.000001".parse(), Ok(atto(1_000_000_000_000u64)));
+        assert_eq!("-2 μFIL".parse(), Ok(atto(-2_000_000_000_000i64)));
+        assert_eq!("1 attoFIL".parse(), Ok(atto(1)));
+        assert_eq!(".5 mFIL".parse(), Ok(atto(500_000_000_000_000u64)));
+        assert_eq!("1.000 aFIL".parse(), Ok(atto(1)));
+        assert_eq!("0".parse(), Ok(TokenAmount::zero()));
+        assert_eq!(
+            "123456789.123456789123456789 FIL".parse(),
+            Ok(atto(
+                BigInt::parse_bytes(b"123456789123456789123456789", 10).unwrap()
+            ))
+        );
+
+        assert_eq!(
+            "1.5 aFIL".parse::<TokenAmount>(),
+            Err(ParseTokenAmountError::ExcessPrecision("1.5".into()))
+        );
+        assert_eq!(
+            "0.0000000000000000001".parse::<TokenAmount>(),
+            Err(ParseTokenAmountError::ExcessPrecision(
+                "0.0000000000000000001".into()
+            ))
+        );
+        assert_eq!(
+            "1 kFIL".parse::<TokenAmount>(),
+            Err(ParseTokenAmountError::UnknownUnit("kFIL".into()))
+        );
+        for invalid in ["", ".", "FIL", "1.2.3", "--1", "1-2"] {
+            assert_eq!(
+                invalid.parse::<TokenAmount>(),
+                Err(ParseTokenAmountError::InvalidNumber(
+                    invalid.trim_end_matches("FIL").into()
+                )),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn format() {
+        let amount = TokenAmount::from_nano(1_500_000_000);
+        assert_eq!(
+            amount.display_with(TokenFormat::new()).to_string(),
+            "1.5 FIL"
+        );
+        assert_eq!(
+            amount
+                .display_with(TokenFormat::new().unit(TokenUnit::Nano))
+                .to_string(),
+            "1500000000 nFIL"
+        );
+        assert_eq!(
+            amount
+                .display_with(TokenFormat::new().trim_zeros(false))
+                .to_string(),
+            "1.500000000000000000 FIL"
+        );
+        assert_eq!(
+            amount
+                .display_with(TokenFormat::new().precision(3).trim_zeros(false))
+                .to_string(),
+            "1.500 FIL"
+        );
+        assert_eq!(
+            atto(123_456)
+                .display_with(TokenFormat::new().si())
+                .to_string(),
+            "123.456 fFIL"
+        );
+        assert_eq!(
+            atto(-123_456)
+                .display_with(TokenFormat::new().si().precision(1))
+                .to_string(),
+            "-123.4 fFIL"
+        );
+        assert_eq!(
+            atto(-1)
+                .display_with(TokenFormat::new().precision(3))
+                .to_string(),
+            "0 FIL"
+        );
+        assert_eq!(
+            atto(-1)
+                .display_with(TokenFormat::new().precision(3).trim_zeros(false))
+                .to_string(),
+            "0.000 FIL"
+        );
+        assert_eq!(
+            TokenAmount::zero()
+                .display_with(TokenFormat::new().si())
+                .to_string(),
+            "0 FIL"
+        );
+        assert_eq!(
+            format!("{:>10}", atto(1).display_with(TokenFormat::new().si())),
+            "    1 aFIL"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let amounts = [
+            TokenAmount::zero(),
+            atto(1),
+            atto(-1),
+            atto(123_456_789),
+            TokenAmount::from_nano(1_500_000_000),
+            TokenAmount::from_whole(-42) + atto(7),
+        ];
+        let mut formats = vec![
+            TokenFormat::new(),
+            TokenFormat::new().si(),
+            TokenFormat::new().trim_zeros(false),
+            TokenFormat::new().si().precision(20),
+        ];
+        formats.extend(TokenUnit::ALL.map(|unit| TokenFormat::new().unit(unit)));
+
+        for amount in amounts {
+            assert_eq!(amount.to_string().parse(), Ok(amount.clone()));
+            for format in formats.iter() {
+                let formatted = amount.display_with(*format).to_string();
+                assert_eq!(formatted.parse(), Ok(amount.clone()), "{formatted}");
+            }
+        }
+    }
+}
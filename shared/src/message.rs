@@ -3,12 +3,19 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use anyhow::anyhow;
-use fvm_ipld_encoding::RawBytes;
+use cid::Cid;
+use cid::multihash::Multihash;
 use fvm_ipld_encoding::de::{Deserialize, Deserializer};
 use fvm_ipld_encoding::ser::{Serialize, Serializer};
+use fvm_ipld_encoding::tuple::*;
+use fvm_ipld_encoding::{DAG_CBOR, Error as EncodingError, RawBytes, to_vec};
 
 use crate::MethodNum;
 use crate::address::Address;
+use crate::crypto::hash::SupportedHashes;
+#[cfg(feature = "signing")]
+use crate::crypto::signature::{Error as SigningError, signing};
+use crate::crypto::signature::{Signature, SignatureType};
 use crate::econ::TokenAmount;
 
 /// Default Unsigned VM message type which includes all data needed for a state transition
@@ -39,6 +46,23 @@ impl Message {
         }
         Ok(())
     }
+
+    /// The CID of the message: the Blake2b-256 hash of its DAG-CBOR encoding. This is what gets
+    /// signed.
+    pub fn cid(&self) -> Result<Cid, EncodingError> {
+        Ok(cid_of(&to_vec(self)?))
+    }
+}
+
+fn cid_of(bytes: &[u8]) -> Cid {
+    let digest = blake2b_simd::Params::new()
+        .hash_length(32)
+        .to_state()
+        .update(bytes)
+        .finalize();
+    let hash = Multihash::wrap(SupportedHashes::Blake2b256 as u64, digest.as_bytes())
+        .expect("blake2b-256 digests fit in a multihash");
+    Cid::new_v1(DAG_CBOR, hash)
 }
 
 impl Serialize for Message {
@@ -94,6 +118,61 @@ impl<'de> Deserialize<'de> for Message {
     }
 }
 
+/// A message with the signature of its sender, encoded as a `(message, signature)` tuple.
+#[derive(PartialEq, Clone, Debug, Hash, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct SignedMessage {
+    pub message: Message,
+    pub signature: Signature,
+}
+
+impl SignedMessage {
+    /// The CID of the message as included on chain. Like Lotus, messages signed with BLS are
+    /// identified by the CID of the unsigned message (their signatures get aggregated in blocks),
+    /// and other messages by the CID of the signed message.
+    pub fn cid(&self) -> Result<Cid, EncodingError> {
+        match self.signature.signature_type() {
+            SignatureType::BLS => self.message.cid(),
+            SignatureType::Secp256k1 | SignatureType::Delegated => Ok(cid_of(&to_vec(self)?)),
+        }
+    }
+}
+
+#[cfg(feature = "crypto")]
+impl SignedMessage {
+    /// Checks that the message was signed by its sender: the signature must be over the bytes of
+    /// the CID of the unsigned message.
+    ///
+    /// Messages with delegated signatures are signed as Ethereum transactions, which can't be
+    /// verified here.
+    pub fn verify(&self) -> Result<(), String> {
+        if self.signature.signature_type() == SignatureType::Delegated {
+            return Err("cannot verify messages with delegated signatures".to_owned());
+        }
+        let cid = self.message.cid().map_err(|e| e.to_string())?;
+        self.signature.verify(&cid.to_bytes(), &self.message.from)
+    }
+}
+
+#[cfg(feature = "signing")]
+impl SignedMessage {
+    /// Signs a message with the secp256k1 key of its sender.
+    pub fn sign_secp256k1(
+        message: Message,
+        key: &signing::Secp256k1PrivateKey,
+    ) -> Result<Self, SigningError> {
+        let cid = message.cid()?;
+        let signature = signing::sign_secp256k1(key, &cid.to_bytes())?;
+        Ok(SignedMessage { message, signature })
+    }
+
+    /// Signs a message with the BLS key of its sender.
+    pub fn sign_bls(message: Message, key: &signing::BlsPrivateKey) -> Result<Self, SigningError> {
+        let cid = message.cid()?;
+        let signature = signing::sign_bls(key, &cid.to_bytes());
+        Ok(SignedMessage { message, signature })
+    }
+}
+
 #[cfg(feature = "arb")]
 impl quickcheck::Arbitrary for Message {
     fn arbitrary(g: &mut quickcheck::Gen) -> Self {
@@ -111,3 +190,100 @@ impl quickcheck::Arbitrary for Message {
         }
     }
 }
+
+#[cfg(feature = "arb")]
+impl quickcheck::Arbitrary for SignedMessage {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        Self {
+            message: Message::arbitrary(g),
+            signature: Signature::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_encoding::from_slice;
+    use multihash_codetable::{Code, MultihashDigest};
+
+    use super::*;
+
+    fn message(from: Address) -> Message {
+        Message {
+            version: 0,
+            from,
+            to: Address::new_id(1234),
+            sequence: 7,
+            value: TokenAmount::from_atto(100),
+            method_num: 2,
+            params: RawBytes::new(vec![0x80]),
+            gas_limit: 1_000_000,
+            gas_fee_cap: TokenAmount::from_atto(200),
+            gas_premium: TokenAmount::from_atto(50),
+        }
+    }
+
+    #[test]
+    fn encoding_and_cids() {
+        let msg = message(Address::new_id(1));
+        let secp = SignedMessage {
+            message: msg.clone(),
+            signature: Signature::new_secp256k1(vec![1; 65]),
+        };
+        let bls = SignedMessage {
+            message: msg.clone(),
+            signature: Signature::new_bls(vec![2; 96]),
+        };
+
+        let bytes = to_vec(&secp).unwrap();
+        assert_eq!(bytes[0], 0x82, "signed messages are encoded as 2-tuples");
+        assert_eq!(from_slice::<SignedMessage>(&bytes).unwrap(), secp);
+
+        let msg_cid = msg.cid().unwrap();
+        assert_eq!(msg_cid.codec(), DAG_CBOR);
+        assert_eq!(
+            msg_cid,
+            Cid::new_v1(DAG_CBOR, Code::Blake2b256.digest(&to_vec(&msg).unwrap()))
+        );
+        assert_eq!(bls.cid().unwrap(), msg_cid);
+        assert_eq!(secp.cid().unwrap(), cid_of(&bytes));
+        assert_ne!(secp.cid().unwrap(), msg_cid);
+    }
+
+    #[cfg(all(feature = "crypto", feature = "signing"))]
+    #[test]
+    fn sign_and_verify() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+
+        use signing::*;
+
+        let rng = &mut ChaCha8Rng::seed_from_u64(8);
+
+        let secp_key = Secp256k1PrivateKey::random(rng);
+        let secp_msg =
+            SignedMessage::sign_secp256k1(message(secp256k1_address(&secp_key)), &secp_key)
+                .unwrap();
+        assert_eq!(
+            secp_msg.signature.signature_type(),
+            SignatureType::Secp256k1
+        );
+        secp_msg.verify().unwrap();
+
+        let bls_key = BlsPrivateKey::generate(rng);
+        let bls_msg = SignedMessage::sign_bls(message(bls_address(&bls_key)), &bls_key).unwrap();
+        assert_eq!(bls_msg.signature.signature_type(), SignatureType::BLS);
+        bls_msg.verify().unwrap();
+
+        // Tampering with the message, or signing with the wrong key, invalidates the signature.
+        let mut tampered = secp_msg.clone();
+        tampered.message.sequence += 1;
+        tampered.verify().unwrap_err();
+
+        let wrong_signer = SignedMessage {
+            message: message(bls_address(&BlsPrivateKey::generate(rng))),
+            signature: bls_msg.signature.clone(),
+        };
+        wrong_signer.verify().unwrap_err();
+    }
+}
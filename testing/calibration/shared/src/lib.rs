@@ -6,6 +6,10 @@ use fvm_shared::event::Flags;
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
 
+mod regression;
+
+pub use self::regression::*;
+
 #[derive(FromPrimitive)]
 #[repr(u64)]
 pub enum Method {
@@ -0,0 +1,84 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use serde::{Deserialize, Serialize};
+
+/// A linear model `y = intercept + slope * x`, along with its coefficient of determination.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct LinearFit {
+    pub intercept: f64,
+    pub slope: f64,
+    pub r_squared: f64,
+}
+
+/// Fits a line through `(x, y)` points with the least squares method.
+///
+/// https://www.mathsisfun.com/data/least-squares-regression.html
+pub fn least_squares(xys: impl IntoIterator<Item = (f64, f64)>) -> LinearFit {
+    let xys = xys.into_iter().collect::<Vec<_>>();
+
+    let mut sum_x = 0f64;
+    let mut sum_y = 0f64;
+    let mut sum_x2 = 0f64;
+    let mut sum_xy = 0f64;
+    let n = xys.len() as f64;
+
+    for (x, y) in xys.iter() {
+        sum_y += y;
+        sum_x += x;
+        sum_x2 += x * x;
+        sum_xy += x * y;
+    }
+
+    // If the variable is the same in all observations, all we can estimate is a constant.
+    let d = n * sum_x2 - sum_x * sum_x;
+    let m: f64 = if d == 0.0 {
+        0.0
+    } else {
+        (n * sum_xy - sum_x * sum_y) / d
+    };
+    let b: f64 = (sum_y - m * sum_x) / n;
+
+    // R2 = 1 - RSS/TSS
+    // RSS = sum of squares of residuals
+    // TSS = total sum of squares
+    let mean_y = sum_y / n;
+    let mut tss = 0f64;
+    let mut rss = 0f64;
+
+    for (x, y) in xys.iter() {
+        let f = m * x + b;
+        let e = y - f;
+        rss += e * e;
+
+        let e = y - mean_y;
+        tss += e * e;
+    }
+    // A constant is perfectly explained by the intercept.
+    let r_squared = if tss == 0.0 { 1.0 } else { 1.0 - rss / tss };
+
+    LinearFit {
+        intercept: b,
+        slope: m,
+        r_squared,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_line() {
+        let fit = least_squares((0..10).map(|x| (x as f64, 100.0 + 3.0 * x as f64)));
+        assert!((fit.intercept - 100.0).abs() < 1e-9);
+        assert!((fit.slope - 3.0).abs() < 1e-9);
+        assert!((fit.r_squared - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn constant_variable() {
+        let fit = least_squares([(1.0, 10.0), (1.0, 20.0), (1.0, 30.0)]);
+        assert_eq!(fit.slope, 0.0);
+        assert!((fit.intercept - 20.0).abs() < 1e-9);
+    }
+}
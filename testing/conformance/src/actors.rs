@@ -2,12 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 use std::sync::Mutex;
 
+use cid::Cid;
 use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
 use fvm_ipld_car::load_car;
 use lazy_static::lazy_static;
 
 static V10_BUNDLE: &[u8] = include_bytes!("../actors/v10.tar.zst");
-static V11_BUNDLE: &[u8] = include_bytes!("../actors/v11.tar.zst");
+pub(crate) static V11_BUNDLE: &[u8] = include_bytes!("../actors/v11.tar.zst");
 
 lazy_static! {
     static ref ACTORS: Mutex<MemoryBlockstore> =
@@ -25,6 +26,11 @@ fn load_bundles(bundles: &[&[u8]]) -> anyhow::Result<MemoryBlockstore> {
     Ok(bs)
 }
 
+/// Returns true if the block is part of the bundled actors.
+pub fn is_bundled(cid: &Cid) -> anyhow::Result<bool> {
+    ACTORS.lock().unwrap().has(cid)
+}
+
 /// Load the bundled actors into the specified blockstore.
 pub fn load_actors(bs: &impl Blockstore) -> anyhow::Result<()> {
     ACTORS.lock().unwrap().copy_to(bs)
@@ -0,0 +1,150 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use clap::{Parser, ValueEnum};
+use fvm::engine::MultiEngine;
+use fvm_conformance_tests::driver::is_runnable;
+use fvm_conformance_tests::runner::{ErrorAction, Glob, VectorFilter, parse_selector_filter};
+use fvm_conformance_tests::summary::Summary;
+use fvm_conformance_tests::tracing::TestTraceExporter;
+use fvm_conformance_tests::vm::TestStatsGlobal;
+use itertools::Itertools;
+use walkdir::WalkDir;
+
+/// Run conformance test vectors in parallel and emit a machine readable report.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Test vector files or directories to run.
+    #[arg(default_value = "test-vectors/corpus")]
+    vectors: Vec<PathBuf>,
+
+    /// Number of vectors to run in parallel. Defaults to the number of CPUs, capped at 48.
+    #[arg(short = 'j', long)]
+    parallelism: Option<usize>,
+
+    /// Report format.
+    #[arg(short, long, value_enum, default_value = "json")]
+    format: Format,
+
+    /// File to write the report to, instead of stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Only run vectors whose ID matches this glob, e.g. `msg_application--*`.
+    #[arg(long)]
+    id: Option<Glob>,
+
+    /// Only run vectors whose selector contains this entry, e.g. `min_protocol_version=genesis`.
+    /// Can be repeated.
+    #[arg(long, value_parser = parse_selector_filter)]
+    selector: Vec<(String, String)>,
+
+    /// What to do if a vector doesn't contain its postcondition state root: error, warn or ignore.
+    #[arg(long, default_value = "warn")]
+    postcondition_missing: ErrorAction,
+
+    /// Directory to export gas charge traces to.
+    #[arg(long)]
+    trace_dir: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Json,
+    Junit,
+}
+
+fn main() {
+    env_logger::init();
+    match run() {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("ERROR: {e:?}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Runs the vectors and writes the report, returning whether all of them passed.
+fn run() -> anyhow::Result<bool> {
+    let args = Args::parse();
+    let parallelism = args.parallelism.unwrap_or_else(num_cpus::get).clamp(1, 48);
+
+    let filter = VectorFilter {
+        id: args.id,
+        selector: args.selector,
+    };
+    let tracer = Arc::new(args.trace_dir.map(TestTraceExporter::new));
+    let stats = Arc::new(TestStatsGlobal::new_ref());
+    let engines = MultiEngine::new(parallelism as u32);
+
+    // Collect test vector files
+    let mut vector_paths = Vec::new();
+    for path in args.vectors {
+        if path.is_file() {
+            vector_paths.push(path);
+        } else {
+            for entry in WalkDir::new(path).into_iter().filter_ok(is_runnable) {
+                vector_paths.push(entry?.into_path());
+            }
+        }
+    }
+
+    // Process the vectors on a pool of threads, each taking the next vector from the list until
+    // there are none left.
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(vector_paths.len()));
+    std::thread::scope(|s| {
+        for _ in 0..parallelism {
+            s.spawn(|| {
+                while let Some(path) = vector_paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let res = fvm_conformance_tests::runner::run_vector(
+                        path.clone(),
+                        &engines,
+                        &filter,
+                        args.postcondition_missing,
+                        stats.clone(),
+                        tracer.clone(),
+                    );
+                    results.lock().unwrap().push((path.clone(), res));
+                }
+            });
+        }
+    });
+
+    // Report the vectors in a stable order, regardless of the order they completed in.
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut summary = Summary::default();
+    for (path, res) in results {
+        summary.add(path, res.map(|(_, variants)| variants));
+    }
+
+    let report = match args.format {
+        Format::Json => summary.to_json()?,
+        Format::Junit => summary.to_junit(),
+    };
+    match args.output {
+        Some(path) => std::fs::write(path, report)?,
+        None => println!("{report}"),
+    }
+
+    if let Some(tracer) = tracer.as_ref() {
+        tracer.export_tombstones()?;
+    }
+
+    eprintln!(
+        "conformance tests result: {}/{} tests passed ({} skipped, {} vector errors)",
+        summary.passed,
+        summary.passed + summary.failed,
+        summary.skipped,
+        summary.errors,
+    );
+
+    Ok(summary.is_success())
+}
@@ -8,6 +8,7 @@ use std::{env, process};
 
 use anyhow::anyhow;
 use fvm_conformance_tests::tracing::TestGasCharge;
+use fvm_gas_calibration_shared::LinearFit;
 use fvm_ipld_encoding::de::DeserializeOwned;
 use serde::Serialize;
 
@@ -117,45 +118,21 @@ fn export_json<T: Serialize>(path: &PathBuf, values: Vec<T>) -> anyhow::Result<(
     Ok(())
 }
 
-// https://www.mathsisfun.com/data/least-squares-regression.html
 fn least_squares(name: String, charges: Vec<Obs>) -> RegressionResult {
-    let mut sum_x = 0f64;
-    let mut sum_y = 0f64;
-    let mut sum_x2 = 0f64;
-    let mut sum_xy = 0f64;
-    let n = charges.len() as f64;
-
-    for charge in charges.iter() {
-        sum_y += charge.compute_gas;
-        sum_x += charge.elapsed_nanos;
-        sum_x2 += charge.elapsed_nanos * charge.elapsed_nanos;
-        sum_xy += charge.elapsed_nanos * charge.compute_gas;
-    }
-
-    let m: f64 = (n * sum_xy - sum_x * sum_y) / (n * sum_x2 - sum_x * sum_x);
-    let b: f64 = (sum_y - m * sum_x) / n;
-
-    // R2 = 1 - RSS/TSS
-    // RSS = sum of squares of residuals
-    // TSS = total sum of squares
-    let mean_y = sum_y / n;
-    let mut tss = 0f64;
-    let mut rss = 0f64;
-
-    for charge in charges.iter() {
-        let f = m * charge.elapsed_nanos + b;
-        let e = charge.compute_gas - f;
-        rss += e * e;
-
-        let e = charge.compute_gas - mean_y;
-        tss += e * e;
-    }
-    let r_squared = 1.0 - rss / tss;
+    let LinearFit {
+        intercept,
+        slope,
+        r_squared,
+    } = fvm_gas_calibration_shared::least_squares(
+        charges
+            .iter()
+            .map(|charge| (charge.elapsed_nanos, charge.compute_gas)),
+    );
 
     RegressionResult {
         name,
-        intercept: b,
-        slope: m,
+        intercept,
+        slope,
         r_squared,
     }
 }
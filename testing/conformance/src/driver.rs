@@ -21,6 +21,7 @@ use fvm_shared::version::NetworkVersion;
 use ipld_core::ipld::Ipld;
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde::Serialize;
 use walkdir::DirEntry;
 
 use crate::tracing::TestTraceFun;
@@ -55,7 +56,7 @@ pub fn is_runnable(entry: &DirEntry) -> bool {
 
     for rx in SKIP_TESTS.iter() {
         if rx.is_match(file_name) {
-            println!("SKIPPING: {}", file_name);
+            eprintln!("SKIPPING: {}", file_name);
             return false;
         }
     }
@@ -63,46 +64,110 @@ pub fn is_runnable(entry: &DirEntry) -> bool {
     file_name.ends_with(".json")
 }
 
-/// Compares the result of running a message with the expected result.
-fn check_msg_result(expected_rec: &Receipt, ret: &ApplyRet, label: impl Display) -> Result<()> {
-    let error = ret
-        .failure_info
-        .as_ref()
-        .map(|e| e.to_string())
-        .unwrap_or_else(|| "no error".into());
-    let actual_rec = &ret.msg_receipt;
-    let (expected, actual) = (expected_rec.exit_code, actual_rec.exit_code);
-    if expected != actual {
-        return Err(anyhow!(
-            "exit code of msg {} did not match; expected: {:?}, got {:?}. Error: {}",
-            label,
-            expected,
-            actual,
-            error
-        ));
+/// The exit code, return data and gas used of a message receipt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReceiptSummary {
+    pub exit_code: u32,
+    pub return_data: String,
+    pub gas_used: u64,
+}
+
+impl From<&Receipt> for ReceiptSummary {
+    fn from(rec: &Receipt) -> Self {
+        ReceiptSummary {
+            exit_code: rec.exit_code.value(),
+            return_data: hex::encode(rec.return_data.bytes()),
+            gas_used: rec.gas_used,
+        }
     }
+}
+
+/// The receipt of a message did not match the receipt expected by the vector.
+#[derive(Debug, Clone)]
+pub struct ReceiptMismatch {
+    /// Index of the message in the vector.
+    pub index: usize,
+    pub expected: Receipt,
+    pub actual: Receipt,
+    /// The failure info of the message execution, if any.
+    pub error: Option<String>,
+}
 
-    let (expected, actual) = (&expected_rec.return_data, &actual_rec.return_data);
-    if expected != actual {
-        return Err(anyhow!(
-            "return data of msg {} did not match; expected: {:?}, got {:?}",
-            label,
-            expected.as_slice(),
-            actual.as_slice()
-        ));
+impl ReceiptMismatch {
+    /// Returns the difference between the actual and expected gas used.
+    pub fn gas_delta(&self) -> i64 {
+        self.actual.gas_used as i64 - self.expected.gas_used as i64
     }
+}
 
-    let (expected, actual) = (expected_rec.gas_used, actual_rec.gas_used);
-    if expected != actual {
-        return Err(anyhow!(
-            "gas used of msg {} did not match; expected: {}, got {}",
-            label,
-            expected,
-            actual
-        ));
+impl Display for ReceiptMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (expected, actual) = (&self.expected, &self.actual);
+        if expected.exit_code != actual.exit_code {
+            write!(
+                f,
+                "exit code of msg {} did not match; expected: {:?}, got {:?}. Error: {}",
+                self.index,
+                expected.exit_code,
+                actual.exit_code,
+                self.error.as_deref().unwrap_or("no error")
+            )
+        } else if expected.return_data != actual.return_data {
+            write!(
+                f,
+                "return data of msg {} did not match; expected: {:?}, got {:?}",
+                self.index,
+                expected.return_data.as_slice(),
+                actual.return_data.as_slice()
+            )
+        } else {
+            write!(
+                f,
+                "gas used of msg {} did not match; expected: {}, got {}",
+                self.index, expected.gas_used, actual.gas_used
+            )
+        }
     }
+}
 
-    Ok(())
+impl std::error::Error for ReceiptMismatch {}
+
+/// The state root after applying all messages did not match the one expected by the vector.
+#[derive(Debug, Clone)]
+pub struct StateRootMismatch {
+    pub expected: Cid,
+    pub actual: Cid,
+}
+
+impl Display for StateRootMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wrong post root cid; expected {}, but got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for StateRootMismatch {}
+
+/// Compares the result of running a message with the expected result.
+fn check_msg_result(expected_rec: &Receipt, ret: &ApplyRet, index: usize) -> Result<()> {
+    let actual_rec = &ret.msg_receipt;
+    if expected_rec.exit_code == actual_rec.exit_code
+        && expected_rec.return_data == actual_rec.return_data
+        && expected_rec.gas_used == actual_rec.gas_used
+    {
+        return Ok(());
+    }
+
+    Err(ReceiptMismatch {
+        index,
+        expected: expected_rec.clone(),
+        actual: actual_rec.clone(),
+        error: ret.failure_info.as_ref().map(|e| e.to_string()),
+    }
+    .into())
 }
 
 fn compare_actors(
@@ -182,11 +247,11 @@ fn compare_state_roots(bs: &MemoryBlockstore, root: &Cid, vector: &MessageVector
         )?;
     }
 
-    Err(anyhow!(
-        "wrong post root cid; expected {}, but got {}",
-        &vector.postconditions.state_tree.root_cid,
-        root
-    ))
+    Err(StateRootMismatch {
+        expected: vector.postconditions.state_tree.root_cid,
+        actual: *root,
+    }
+    .into())
 }
 
 /// Represents the result from running a vector.
@@ -0,0 +1,514 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use anyhow::{Context as _, anyhow};
+use cid::Cid;
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use fvm::call_manager::DefaultCallManager;
+use fvm::engine::MultiEngine;
+use fvm::executor::{ApplyKind, DefaultExecutor, Executor};
+use fvm::externs::{Chain, Consensus, Externs, Rand};
+use fvm::kernel::filecoin::DefaultFilecoinKernel;
+use fvm::machine::{DefaultMachine, Machine, NetworkConfig};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_car::{Block, CarHeader, CarWriter};
+use fvm_ipld_encoding::to_vec;
+use fvm_shared::address::Protocol;
+use fvm_shared::clock::ChainEpoch;
+use fvm_shared::consensus::ConsensusFault;
+use fvm_shared::crypto::signature::SECP_SIG_LEN;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
+use num_traits::ToPrimitive;
+
+use crate::actors::is_bundled;
+use crate::externs::TestExterns;
+use crate::vector::{
+    ApplyMessage, GenerationData, MessageVector, MetaData, PostConditions, PreConditions,
+    Randomness, RandomnessKind, RandomnessMatch, RandomnessRule, StateTreeVector, Variant,
+};
+
+type GeneratorExecutor<BS> = DefaultExecutor<
+    DefaultFilecoinKernel<
+        DefaultCallManager<DefaultMachine<RecordingBlockstore<BS>, RecordingExterns>>,
+    >,
+>;
+
+/// Wrapper around a `Blockstore` recording the CIDs of all the blocks read from and written to
+/// it, so we know which blocks a vector needs to include.
+#[derive(Debug)]
+pub struct RecordingBlockstore<BS> {
+    base: BS,
+    touched: RefCell<BTreeSet<Cid>>,
+}
+
+impl<BS> RecordingBlockstore<BS>
+where
+    BS: Blockstore,
+{
+    pub fn new(base: BS) -> Self {
+        Self {
+            base,
+            touched: Default::default(),
+        }
+    }
+
+    /// Returns the CIDs of all the blocks read or written so far.
+    pub fn touched(&self) -> BTreeSet<Cid> {
+        self.touched.borrow().clone()
+    }
+
+    /// Returns the wrapped blockstore.
+    pub fn into_inner(self) -> BS {
+        self.base
+    }
+}
+
+impl<BS> Blockstore for RecordingBlockstore<BS>
+where
+    BS: Blockstore,
+{
+    fn get(&self, cid: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        let bytes = self.base.get(cid)?;
+        if bytes.is_some() {
+            self.touched.borrow_mut().insert(*cid);
+        }
+        Ok(bytes)
+    }
+
+    fn has(&self, cid: &Cid) -> anyhow::Result<bool> {
+        self.base.has(cid)
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.base.put_keyed(k, block)?;
+        self.touched.borrow_mut().insert(*k);
+        Ok(())
+    }
+}
+
+/// Externs recording the randomness drawn by the executed messages, so the runner can replay it.
+/// Randomness is served the same way the runner serves it: from the given entries, falling back to
+/// fixed values.
+pub struct RecordingExterns {
+    base: TestExterns,
+    drawn: RefCell<Randomness>,
+}
+
+impl RecordingExterns {
+    pub fn new(randomness: &Randomness) -> Self {
+        Self {
+            base: TestExterns::new(randomness),
+            drawn: Default::default(),
+        }
+    }
+
+    /// Returns the randomness drawn so far, in the order it was first drawn.
+    pub fn drawn(&self) -> Randomness {
+        self.drawn.borrow().clone()
+    }
+
+    fn record(
+        &self,
+        kind: RandomnessKind,
+        epoch: ChainEpoch,
+        ret: anyhow::Result<[u8; 32]>,
+    ) -> anyhow::Result<[u8; 32]> {
+        let ret = ret?;
+        let on = RandomnessRule { kind, epoch };
+        let mut drawn = self.drawn.borrow_mut();
+        if !drawn.iter().any(|m| m.on == on) {
+            drawn.push(RandomnessMatch {
+                on,
+                ret: ret.to_vec(),
+            });
+        }
+        Ok(ret)
+    }
+}
+
+impl Externs for RecordingExterns {}
+
+impl Rand for RecordingExterns {
+    fn get_chain_randomness(&self, round: ChainEpoch) -> anyhow::Result<[u8; 32]> {
+        self.record(
+            RandomnessKind::Chain,
+            round,
+            self.base.get_chain_randomness(round),
+        )
+    }
+
+    fn get_beacon_randomness(&self, round: ChainEpoch) -> anyhow::Result<[u8; 32]> {
+        self.record(
+            RandomnessKind::Beacon,
+            round,
+            self.base.get_beacon_randomness(round),
+        )
+    }
+}
+
+impl Consensus for RecordingExterns {
+    fn verify_consensus_fault(
+        &self,
+        h1: &[u8],
+        h2: &[u8],
+        extra: &[u8],
+    ) -> anyhow::Result<(Option<ConsensusFault>, i64)> {
+        self.base.verify_consensus_fault(h1, h2, extra)
+    }
+}
+
+impl Chain for RecordingExterns {
+    fn get_tipset_cid(&self, epoch: ChainEpoch) -> anyhow::Result<Cid> {
+        self.base.get_tipset_cid(epoch)
+    }
+}
+
+/// The execution environment of a generated vector.
+#[derive(Debug, Clone)]
+pub struct VectorParams {
+    /// ID of the vector, recorded in its metadata.
+    pub id: String,
+    /// Free-form description of the vector, e.g. what failure it reproduces.
+    pub description: String,
+    /// The network configuration to execute the messages with. Note that the runner only replays
+    /// the network version, so any other non-default setting is not captured in the vector.
+    pub network: NetworkConfig,
+    /// The epoch to execute the messages at.
+    pub epoch: ChainEpoch,
+    pub base_fee: TokenAmount,
+    pub circ_supply: Option<TokenAmount>,
+    /// Randomness to serve to the messages, e.g. as drawn from the chain the failure was found on.
+    /// Any other randomness drawn gets the runner's fixed fallback values. All the randomness drawn
+    /// is recorded in the vector.
+    pub randomness: Randomness,
+}
+
+/// Executes the messages on top of the given state root and captures the result as a message
+/// vector, which can be replayed by the conformance test runner.
+///
+/// The vector's CAR only contains the blocks read or written while executing the messages, except
+/// for the builtin actors bundled with the runner. The blockstore must contain the state root and
+/// the code of all the actors referenced by it.
+pub fn generate_vector<BS>(
+    params: &VectorParams,
+    blockstore: BS,
+    state_root: Cid,
+    messages: &[Message],
+    engines: &MultiEngine,
+) -> anyhow::Result<MessageVector>
+where
+    BS: Blockstore + 'static,
+{
+    let epoch = params.epoch;
+    // Same timestamp the runner uses when replaying the vector.
+    let mut mc = params
+        .network
+        .for_epoch(epoch, (epoch * 30) as u64, state_root);
+    mc.set_base_fee(params.base_fee.clone());
+    if let Some(circ_supply) = &params.circ_supply {
+        mc.set_circulating_supply(circ_supply.clone());
+    }
+
+    let machine = DefaultMachine::new(
+        &mc,
+        RecordingBlockstore::new(blockstore),
+        RecordingExterns::new(&params.randomness),
+    )?;
+    let engine = engines.get(&mc.network).map_err(|e| anyhow!(e))?;
+    let mut exec: GeneratorExecutor<BS> = DefaultExecutor::new(engine, machine)?;
+
+    let mut apply_messages = Vec::with_capacity(messages.len());
+    let mut receipts = Vec::with_capacity(messages.len());
+    for msg in messages {
+        let bytes = to_vec(msg)?;
+
+        // Mirror the message length the runner computes when replaying the vector.
+        let mut raw_length = bytes.len();
+        if msg.from.protocol() == Protocol::Secp256k1 {
+            // 65 bytes signature + 1 byte type + 3 bytes for field info.
+            raw_length += SECP_SIG_LEN + 4;
+        }
+
+        let ret = exec
+            .execute_message(msg.clone(), ApplyKind::Explicit, raw_length)
+            .with_context(|| format!("failed to execute message {}", apply_messages.len()))?;
+        receipts.push(ret.msg_receipt);
+        apply_messages.push(ApplyMessage {
+            bytes,
+            epoch_offset: None,
+        });
+    }
+
+    let post_root = exec.flush().context("flushing executor failed")?;
+    let machine = exec
+        .into_machine()
+        .ok_or_else(|| anyhow!("machine poisoned"))?;
+    let randomness = machine.externs().drawn();
+    let bs = machine.into_store().into_inner();
+
+    let touched = bs.touched();
+    let bs = bs.into_inner();
+
+    // Write the touched blocks into a gzipped CAR, skipping the actors the runner loads anyway.
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut car = CarWriter::new(CarHeader::from(vec![state_root, post_root]), &mut encoder)?;
+    for cid in touched {
+        if is_bundled(&cid)? {
+            continue;
+        }
+        let data = bs
+            .get(&cid)?
+            .ok_or_else(|| anyhow!("missing touched block {cid}"))?;
+        car.write(Block { cid, data })?;
+    }
+    drop(car);
+    let car = encoder.finish()?;
+
+    let to_u128 = |amount: &TokenAmount| {
+        amount
+            .atto()
+            .to_u128()
+            .ok_or_else(|| anyhow!("token amount {amount} out of range"))
+    };
+
+    Ok(MessageVector {
+        selector: None,
+        meta: Some(MetaData {
+            id: params.id.clone(),
+            version: String::new(),
+            description: params.description.clone(),
+            comment: String::new(),
+            generation: vec![GenerationData {
+                source: module_path!().to_owned(),
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+            }],
+        }),
+        car,
+        preconditions: PreConditions {
+            state_tree: StateTreeVector {
+                root_cid: state_root,
+            },
+            basefee: Some(to_u128(&params.base_fee)?),
+            circ_supply: params.circ_supply.as_ref().map(to_u128).transpose()?,
+            variants: vec![Variant {
+                id: format!("nv{}", u32::from(params.network.network_version)),
+                epoch,
+                nv: params.network.network_version.into(),
+            }],
+        },
+        apply_messages,
+        postconditions: PostConditions {
+            state_tree: StateTreeVector {
+                root_cid: post_root,
+            },
+            receipts,
+            receipts_roots: Vec::new(),
+        },
+        randomness,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::sync::Arc;
+
+    use fvm::machine::{BURNT_FUNDS_ACTOR_ID, Manifest};
+    use fvm::state_tree::{ActorState, StateTree};
+    use fvm::{account_actor, init_actor, system_actor};
+    use fvm_ipld_blockstore::MemoryBlockstore;
+    use fvm_ipld_car::load_car;
+    use fvm_ipld_encoding::CborStore;
+    use fvm_shared::address::Address;
+    use fvm_shared::state::StateTreeVersion;
+    use fvm_shared::version::NetworkVersion;
+    use multihash_codetable::Code;
+    use num_traits::Zero;
+
+    use super::*;
+    use crate::actors::V11_BUNDLE;
+    use crate::driver::VariantResult;
+    use crate::runner::{ErrorAction, VectorFilter, run_vector};
+
+    /// Loads the bundled v11 actors, returning the CID of the mainnet manifest.
+    fn load_mainnet_manifest(bs: &MemoryBlockstore) -> Cid {
+        let mut reader = tar::Archive::new(zstd::Decoder::with_buffer(V11_BUNDLE).unwrap());
+        for entry in reader.entries().unwrap() {
+            let entry = entry.unwrap();
+            let mainnet = entry
+                .path()
+                .unwrap()
+                .ends_with("builtin-actors-mainnet.car");
+            let roots = load_car(bs, entry).unwrap();
+            if mainnet {
+                return roots[0];
+            }
+        }
+        panic!("no mainnet bundle");
+    }
+
+    /// Creates a state tree with the system, init and burnt funds actors, and an account with
+    /// the given address. Returns the state root.
+    fn create_state(bs: &MemoryBlockstore, account: &Address) -> Cid {
+        let manifest = load_mainnet_manifest(bs);
+        let (version, data): (u32, Cid) = bs.get_cbor(&manifest).unwrap().unwrap();
+        let manifest = Manifest::load(bs, &data, version).unwrap();
+
+        let mut state_tree = StateTree::new(bs, StateTreeVersion::V5).unwrap();
+        let mut set_actor = |id, code, state: Cid, balance| {
+            state_tree.set_actor(
+                id,
+                ActorState {
+                    code,
+                    state,
+                    sequence: 0,
+                    balance,
+                    delegated_address: None,
+                },
+            )
+        };
+        set_actor(
+            system_actor::SYSTEM_ACTOR_ID,
+            *manifest.get_system_code(),
+            bs.put_cbor(
+                &system_actor::State {
+                    builtin_actors: data,
+                },
+                Code::Blake2b256,
+            )
+            .unwrap(),
+            TokenAmount::zero(),
+        );
+        set_actor(
+            init_actor::INIT_ACTOR_ID,
+            *manifest.get_init_code(),
+            bs.put_cbor(&init_actor::State::new_test(bs), Code::Blake2b256)
+                .unwrap(),
+            TokenAmount::zero(),
+        );
+        let burnt_funds = account_actor::State {
+            address: Address::new_id(BURNT_FUNDS_ACTOR_ID),
+        };
+        set_actor(
+            BURNT_FUNDS_ACTOR_ID,
+            *manifest.get_account_code(),
+            bs.put_cbor(&burnt_funds, Code::Blake2b256).unwrap(),
+            TokenAmount::zero(),
+        );
+
+        let id = state_tree.register_new_address(account).unwrap();
+        state_tree.set_actor(
+            id,
+            ActorState {
+                code: *manifest.get_account_code(),
+                state: bs
+                    .put_cbor(
+                        &account_actor::State { address: *account },
+                        Code::Blake2b256,
+                    )
+                    .unwrap(),
+                sequence: 0,
+                balance: TokenAmount::from_whole(1000),
+                delegated_address: None,
+            },
+        );
+        state_tree.flush().unwrap()
+    }
+
+    #[test]
+    fn records_reads_and_writes() {
+        let bs = RecordingBlockstore::new(MemoryBlockstore::new());
+        let written = bs.put_cbor(&"written", Code::Blake2b256).unwrap();
+        assert_eq!(bs.touched(), BTreeSet::from([written]));
+
+        let base = bs.into_inner();
+        let read = base.put_cbor(&"read", Code::Blake2b256).unwrap();
+        let untouched = base.put_cbor(&"untouched", Code::Blake2b256).unwrap();
+
+        let bs = RecordingBlockstore::new(base);
+        assert!(bs.has(&untouched).unwrap());
+        assert_eq!(bs.get_cbor::<String>(&read).unwrap().unwrap(), "read");
+        assert_eq!(bs.touched(), BTreeSet::from([read]));
+    }
+
+    #[test]
+    fn records_randomness() {
+        let recorded = vec![RandomnessMatch {
+            on: RandomnessRule {
+                kind: RandomnessKind::Beacon,
+                epoch: 10,
+            },
+            ret: vec![1; 32],
+        }];
+        let externs = RecordingExterns::new(&recorded);
+        assert_eq!(externs.get_beacon_randomness(10).unwrap(), [1; 32]);
+        let chain = externs.get_chain_randomness(10).unwrap();
+        assert_eq!(externs.get_chain_randomness(10).unwrap(), chain);
+
+        let drawn = externs.drawn();
+        assert_eq!(drawn.len(), 2);
+        assert_eq!(drawn[0].on, recorded[0].on);
+        assert_eq!(drawn[1].ret, chain);
+
+        // Replaying the drawn randomness serves the same values.
+        let replayed = TestExterns::new(&drawn);
+        assert_eq!(replayed.get_beacon_randomness(10).unwrap(), [1; 32]);
+        assert_eq!(replayed.get_chain_randomness(10).unwrap(), chain);
+    }
+
+    #[test]
+    fn generated_vector_replays() {
+        let bs = MemoryBlockstore::new();
+        let sender = Address::new_secp256k1(&[4; 65]).unwrap();
+        let state_root = create_state(&bs, &sender);
+
+        let params = VectorParams {
+            id: "generated-send".to_owned(),
+            description: "a plain value transfer".to_owned(),
+            network: NetworkConfig::new(NetworkVersion::V21),
+            epoch: 100,
+            base_fee: TokenAmount::from_atto(100),
+            circ_supply: None,
+            randomness: Vec::new(),
+        };
+        let message = Message {
+            version: 0,
+            from: sender,
+            to: Address::new_id(BURNT_FUNDS_ACTOR_ID),
+            sequence: 0,
+            value: TokenAmount::from_atto(1),
+            method_num: 0,
+            params: Default::default(),
+            gas_limit: 10_000_000,
+            gas_fee_cap: TokenAmount::from_atto(100),
+            gas_premium: TokenAmount::zero(),
+        };
+        let engines = MultiEngine::new(1);
+        let vector = generate_vector(&params, bs, state_root, &[message], &engines).unwrap();
+        assert!(vector.postconditions.receipts[0].exit_code.is_success());
+
+        let path =
+            std::env::temp_dir().join(format!("fvm-generated-vector-{}.json", std::process::id()));
+        vector.to_writer(File::create(&path).unwrap()).unwrap();
+        let result = run_vector(
+            path.clone(),
+            &engines,
+            &VectorFilter::default(),
+            ErrorAction::Error,
+            Arc::new(None),
+            Arc::new(None),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        let (_, results) = result.unwrap();
+        match results.as_slice() {
+            [VariantResult::Ok { .. }] => {}
+            [VariantResult::Failed { reason, .. }] => panic!("replay failed: {reason:?}"),
+            _ => panic!("expected a single passing variant"),
+        }
+    }
+}
@@ -6,7 +6,10 @@ pub mod actors;
 pub mod cidjson;
 pub mod driver;
 pub mod externs;
+pub mod generator;
 pub mod rand;
+pub mod runner;
+pub mod summary;
 pub mod tracing;
 pub mod vector;
 pub mod vm;
@@ -0,0 +1,236 @@
+// Copyright 2021-2023 Protocol Labs
+// Copyright 2019-2022 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{Context as _, anyhow};
+use fvm::engine::MultiEngine;
+use itertools::Itertools;
+use regex::Regex;
+
+use crate::driver::{VariantResult, run_variant};
+use crate::tracing::TestTraceExporterRef;
+use crate::vector::{MessageVector, Selector};
+use crate::vm::TestStatsRef;
+
+/// What to do when a vector doesn't contain its postcondition state root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorAction {
+    Error,
+    Warn,
+    Ignore,
+}
+
+impl FromStr for ErrorAction {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err("must be one of error|warn|ignore".into()),
+        }
+    }
+}
+
+/// Selects the vectors to run.
+#[derive(Debug, Clone, Default)]
+pub struct VectorFilter {
+    /// Only run vectors whose ID matches this glob.
+    pub id: Option<Glob>,
+    /// Only run vectors whose selector has all of these `key=value` entries.
+    pub selector: Vec<(String, String)>,
+}
+
+impl VectorFilter {
+    /// Returns true if the vector passes the filter.
+    ///
+    /// The ID of a vector is taken from its metadata, falling back to the file name.
+    pub fn matches(&self, path: &Path, vector: &MessageVector) -> bool {
+        if let Some(glob) = &self.id {
+            let id = match &vector.meta {
+                Some(meta) => meta.id.clone(),
+                None => path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            };
+            if !glob.is_match(&id) {
+                return false;
+            }
+        }
+        self.selector.iter().all(|(key, value)| {
+            vector
+                .selector
+                .as_ref()
+                .and_then(|s| selector_value(s, key))
+                == Some(value.as_str())
+        })
+    }
+}
+
+/// Looks up a selector entry by the key it has in the vector JSON.
+fn selector_value<'a>(selector: &'a Selector, key: &str) -> Option<&'a str> {
+    match key {
+        "chaos_actor" => selector.chaos_actor.as_deref(),
+        "min_protocol_version" => selector.min_protocol_version.as_deref(),
+        "requires:consensus_fault_extern" => selector.consensus_fault.as_deref(),
+        _ => None,
+    }
+}
+
+/// A glob pattern supporting `*` (any sequence of characters) and `?` (any single character).
+#[derive(Debug, Clone)]
+pub struct Glob(Regex);
+
+impl Glob {
+    pub fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s)
+    }
+}
+
+impl FromStr for Glob {
+    type Err = regex::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut re = String::from("^");
+        for c in s.chars() {
+            match c {
+                '*' => re.push_str(".*"),
+                '?' => re.push('.'),
+                c => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        re.push('$');
+        Regex::new(&re).map(Glob)
+    }
+}
+
+/// Runs a single test vector and returns a list of VectorResults,
+/// one per variant.
+///
+/// Vectors which don't pass the filter are reported with all their variants skipped.
+pub fn run_vector(
+    path: PathBuf,
+    engines: &MultiEngine,
+    filter: &VectorFilter,
+    postcondition_missing_action: ErrorAction,
+    stats: Arc<TestStatsRef>,
+    tracer: Arc<TestTraceExporterRef>,
+) -> anyhow::Result<(PathBuf, Vec<VariantResult>)> {
+    let v = MessageVector::from_file(&path).context("failed to parse message vector")?;
+
+    let skip_reason = if !v.is_supported() {
+        Some("selector not supported")
+    } else if !filter.matches(&path, &v) {
+        Some("filtered out")
+    } else {
+        None
+    };
+
+    if let Some(reason) = skip_reason {
+        let results = v
+            .preconditions
+            .variants
+            .into_iter()
+            .map(|variant| VariantResult::Skipped {
+                id: variant.id,
+                reason: reason.to_owned(),
+            })
+            .collect();
+        return Ok((path, results));
+    }
+
+    // Import the blockstore and do sanity checks
+    let (bs, imported_root) = v.seed_blockstore()?;
+    anyhow::ensure!(
+        imported_root.contains(&v.preconditions.state_tree.root_cid),
+        "imported roots ({}) do not contain precondition CID {}",
+        imported_root.iter().join(", "),
+        v.preconditions.state_tree.root_cid
+    );
+    if !imported_root.contains(&v.postconditions.state_tree.root_cid) {
+        let msg = format!(
+            "imported roots ({}) do not contain postcondition CID {}",
+            imported_root.iter().join(", "),
+            v.postconditions.state_tree.root_cid
+        );
+
+        match postcondition_missing_action {
+            ErrorAction::Error => {
+                anyhow::bail!(msg);
+            }
+            ErrorAction::Warn => {
+                eprintln!("WARN: {msg} in {}", path.display())
+            }
+            ErrorAction::Ignore => (),
+        }
+    }
+
+    // Run all variants
+    let results = v
+        .preconditions
+        .variants
+        .iter()
+        .map(|variant| {
+            let variant_id = variant.id.clone();
+            let name = format!("{} | {}", path.display(), variant_id);
+
+            run_variant(
+                bs.clone(),
+                &v,
+                variant,
+                engines,
+                true,
+                stats.as_ref().clone(),
+                tracer
+                    .as_ref()
+                    .clone()
+                    .map(|t| t.export_fun(path.clone(), variant_id.clone())),
+            )
+            .with_context(|| format!("failed to run {name}"))
+            .unwrap_or_else(|e| VariantResult::Failed {
+                id: variant_id,
+                reason: e,
+            })
+        })
+        .collect();
+
+    Ok((path, results))
+}
+
+/// Parses a `key=value` selector filter.
+pub fn parse_selector_filter(s: &str) -> anyhow::Result<(String, String)> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow!("selector filter must have the form key=value: {s}"))?;
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matching() {
+        let glob: Glob = "msg_*_ok?".parse().unwrap();
+        assert!(glob.is_match("msg_transfer_ok1"));
+        assert!(glob.is_match("msg__ok2"));
+        assert!(!glob.is_match("msg_transfer_ok"));
+        assert!(!glob.is_match("xmsg_transfer_ok1"));
+
+        let glob: Glob = "a.b+c".parse().unwrap();
+        assert!(glob.is_match("a.b+c"));
+        assert!(!glob.is_match("aabbc"));
+    }
+
+    #[test]
+    fn selector_filter_parsing() {
+        assert_eq!(
+            parse_selector_filter("chaos_actor=true").unwrap(),
+            ("chaos_actor".to_owned(), "true".to_owned())
+        );
+        assert!(parse_selector_filter("chaos_actor").is_err());
+    }
+}
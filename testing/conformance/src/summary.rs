@@ -0,0 +1,233 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::driver::{ReceiptMismatch, ReceiptSummary, StateRootMismatch, VariantResult};
+
+/// Outcome of a single variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Difference between the actual and expected receipt of the first mismatching message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReceiptDiff {
+    pub index: usize,
+    pub expected: ReceiptSummary,
+    pub actual: ReceiptSummary,
+    /// Actual minus expected gas used.
+    pub gas_delta: i64,
+}
+
+impl From<&ReceiptMismatch> for ReceiptDiff {
+    fn from(m: &ReceiptMismatch) -> Self {
+        ReceiptDiff {
+            index: m.index,
+            expected: (&m.expected).into(),
+            actual: (&m.actual).into(),
+            gas_delta: m.gas_delta(),
+        }
+    }
+}
+
+/// Difference between the actual and expected post state root.
+#[derive(Debug, Clone, Serialize)]
+pub struct StateRootDiff {
+    pub expected: String,
+    pub actual: String,
+}
+
+impl From<&StateRootMismatch> for StateRootDiff {
+    fn from(m: &StateRootMismatch) -> Self {
+        StateRootDiff {
+            expected: m.expected.to_string(),
+            actual: m.actual.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantReport {
+    pub id: String,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt_diff: Option<ReceiptDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_root_diff: Option<StateRootDiff>,
+}
+
+impl From<VariantResult> for VariantReport {
+    fn from(res: VariantResult) -> Self {
+        match res {
+            VariantResult::Ok { id } => VariantReport {
+                id,
+                status: Status::Passed,
+                reason: None,
+                receipt_diff: None,
+                state_root_diff: None,
+            },
+            VariantResult::Skipped { reason, id } => VariantReport {
+                id,
+                status: Status::Skipped,
+                reason: Some(reason),
+                receipt_diff: None,
+                state_root_diff: None,
+            },
+            VariantResult::Failed { reason, id } => VariantReport {
+                id,
+                status: Status::Failed,
+                receipt_diff: reason.downcast_ref::<ReceiptMismatch>().map(Into::into),
+                state_root_diff: reason.downcast_ref::<StateRootMismatch>().map(Into::into),
+                reason: Some(format!("{reason:#}")),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VectorReport {
+    pub path: PathBuf,
+    /// Set if the vector could not be processed at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub variants: Vec<VariantReport>,
+}
+
+/// Machine readable summary of a conformance test run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Number of vectors that could not be processed.
+    pub errors: usize,
+    pub vectors: Vec<VectorReport>,
+}
+
+impl Summary {
+    /// Records the outcome of running a vector.
+    pub fn add(&mut self, path: PathBuf, result: anyhow::Result<Vec<VariantResult>>) {
+        let report = match result {
+            Ok(results) => {
+                let variants: Vec<VariantReport> = results.into_iter().map(Into::into).collect();
+                for v in &variants {
+                    match v.status {
+                        Status::Passed => self.passed += 1,
+                        Status::Failed => self.failed += 1,
+                        Status::Skipped => self.skipped += 1,
+                    }
+                }
+                VectorReport {
+                    path,
+                    error: None,
+                    variants,
+                }
+            }
+            Err(e) => {
+                self.errors += 1;
+                VectorReport {
+                    path,
+                    error: Some(format!("{e:#}")),
+                    variants: Vec::new(),
+                }
+            }
+        };
+        self.vectors.push(report);
+    }
+
+    /// Returns true if no variant failed and all vectors could be processed.
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.errors == 0
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Renders the summary as a JUnit XML report, with a test suite per vector and a test case
+    /// per variant.
+    pub fn to_junit(&self) -> String {
+        let mut out = String::new();
+        let tests = self.passed + self.failed + self.skipped + self.errors;
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            out,
+            "<testsuites name=\"conformance\" tests=\"{tests}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">",
+            self.failed, self.errors, self.skipped
+        );
+        for vector in &self.vectors {
+            let name = xml_escape(&vector.path.display().to_string());
+            let count = |status| {
+                vector
+                    .variants
+                    .iter()
+                    .filter(|v| v.status == status)
+                    .count()
+            };
+            let _ = writeln!(
+                out,
+                "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">",
+                vector.variants.len().max(vector.error.is_some() as usize),
+                count(Status::Failed),
+                vector.error.is_some() as usize,
+                count(Status::Skipped),
+            );
+            if let Some(error) = &vector.error {
+                let _ = writeln!(
+                    out,
+                    "    <testcase name=\"{name}\" classname=\"{name}\">\n      <error message=\"{}\"/>\n    </testcase>",
+                    xml_escape(error)
+                );
+            }
+            for variant in &vector.variants {
+                let id = xml_escape(&variant.id);
+                let reason = xml_escape(variant.reason.as_deref().unwrap_or_default());
+                match variant.status {
+                    Status::Passed => {
+                        let _ = writeln!(out, "    <testcase name=\"{id}\" classname=\"{name}\"/>");
+                    }
+                    Status::Failed => {
+                        let _ = writeln!(
+                            out,
+                            "    <testcase name=\"{id}\" classname=\"{name}\">\n      <failure message=\"{reason}\"/>\n    </testcase>"
+                        );
+                    }
+                    Status::Skipped => {
+                        let _ = writeln!(
+                            out,
+                            "    <testcase name=\"{id}\" classname=\"{name}\">\n      <skipped message=\"{reason}\"/>\n    </testcase>"
+                        );
+                    }
+                }
+            }
+            out.push_str("  </testsuite>\n");
+        }
+        out.push_str("</testsuites>\n");
+        out
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -4,7 +4,7 @@
 
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Write};
 use std::path::Path;
 
 use anyhow::{Context as _, anyhow};
@@ -15,17 +15,17 @@ use fvm_ipld_car::load_car;
 use fvm_ipld_encoding::tuple::*;
 use fvm_shared::clock::ChainEpoch;
 use fvm_shared::receipt::Receipt;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::actors::load_actors;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct StateTreeVector {
     #[serde(with = "super::cidjson")]
     pub root_cid: Cid,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GenerationData {
     #[serde(default)]
     pub source: String,
@@ -33,7 +33,7 @@ pub struct GenerationData {
     pub version: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MetaData {
     pub id: String,
     #[serde(default)]
@@ -46,7 +46,7 @@ pub struct MetaData {
     pub generation: Vec<GenerationData>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PreConditions {
     pub state_tree: StateTreeVector,
     #[serde(default)]
@@ -57,7 +57,7 @@ pub struct PreConditions {
     pub variants: Vec<Variant>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PostConditions {
     pub state_tree: StateTreeVector,
     #[serde(with = "message_receipt_vec")]
@@ -66,7 +66,7 @@ pub struct PostConditions {
     pub receipts_roots: Vec<Cid>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Selector {
     #[serde(default)]
     pub chaos_actor: Option<String>,
@@ -86,7 +86,7 @@ impl Selector {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Variant {
     pub id: String,
     pub epoch: ChainEpoch,
@@ -97,14 +97,14 @@ pub struct Variant {
 pub type Randomness = Vec<RandomnessMatch>;
 
 /// One randomness entry.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RandomnessMatch {
     pub on: RandomnessRule,
     #[serde(with = "base64_bytes")]
     pub ret: Vec<u8>,
 }
 
-#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum RandomnessKind {
     Beacon,
@@ -112,13 +112,13 @@ pub enum RandomnessKind {
 }
 
 /// Rule for matching when randomness is returned.
-#[derive(Debug, Deserialize_tuple, PartialEq, Eq, Clone)]
+#[derive(Debug, Serialize_tuple, Deserialize_tuple, PartialEq, Eq, Clone)]
 pub struct RandomnessRule {
     pub kind: RandomnessKind,
     pub epoch: ChainEpoch,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MessageVector {
     pub selector: Option<Selector>,
     #[serde(rename = "_meta")]
@@ -177,6 +177,24 @@ impl MessageVector {
     pub fn is_supported(&self) -> bool {
         self.selector.as_ref().is_none_or(Selector::supported)
     }
+
+    /// Writes the message vector as JSON, in the form expected by [`MessageVector::from_file`].
+    pub fn to_writer(&self, writer: impl Write) -> anyhow::Result<()> {
+        // See `from_file` for why we don't let serde add the class as a tag.
+        #[derive(Serialize)]
+        struct ClassifiedVector<'a> {
+            class: &'static str,
+            #[serde(flatten)]
+            vector: &'a MessageVector,
+        }
+
+        let vector = ClassifiedVector {
+            class: "message",
+            vector: self,
+        };
+        serde_json::to_writer_pretty(writer, &vector)?;
+        Ok(())
+    }
 }
 
 impl MessageVector {
@@ -193,7 +211,7 @@ impl MessageVector {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApplyMessage {
     #[serde(with = "base64_bytes")]
     pub bytes: Vec<u8>,
@@ -209,6 +227,15 @@ mod base64_bytes {
 
     use super::*;
 
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        base64::engine::general_purpose::STANDARD
+            .encode(bytes)
+            .serialize(serializer)
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
     where
         D: Deserializer<'de>,
@@ -226,7 +253,7 @@ mod message_receipt_vec {
 
     use super::*;
 
-    #[derive(Deserialize)]
+    #[derive(Serialize, Deserialize)]
     pub struct MessageReceiptVector {
         exit_code: ExitCode,
         #[serde(rename = "return", with = "base64_bytes")]
@@ -234,6 +261,21 @@ mod message_receipt_vec {
         gas_used: u64,
     }
 
+    pub fn serialize<S>(receipts: &[Receipt], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        receipts
+            .iter()
+            .map(|r| MessageReceiptVector {
+                exit_code: r.exit_code,
+                return_value: r.return_data.to_vec(),
+                gas_used: r.gas_used,
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Receipt>, D::Error>
     where
         D: Deserializer<'de>,
@@ -1,45 +1,23 @@
 // Copyright 2021-2023 Protocol Labs
 // Copyright 2019-2022 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
-use std::collections::HashMap;
 use std::env::var;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::{Path, PathBuf};
-use std::str::FromStr;
+use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{Context as _, anyhow};
+use anyhow::anyhow;
 use colored::*;
 use futures::{StreamExt, stream};
 use fvm::engine::MultiEngine;
 use fvm_conformance_tests::driver::*;
 use fvm_conformance_tests::report;
-use fvm_conformance_tests::tracing::{TestTraceExporter, TestTraceExporterRef};
-use fvm_conformance_tests::vector::{MessageVector, Selector};
-use fvm_conformance_tests::vm::{TestStatsGlobal, TestStatsRef};
+use fvm_conformance_tests::runner::{ErrorAction, VectorFilter, run_vector};
+use fvm_conformance_tests::tracing::TestTraceExporter;
+use fvm_conformance_tests::vm::TestStatsGlobal;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use walkdir::WalkDir;
 
-enum ErrorAction {
-    Error,
-    Warn,
-    Ignore,
-}
-
-impl FromStr for ErrorAction {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "error" => Ok(Self::Error),
-            "warn" => Ok(Self::Warn),
-            "ignore" => Ok(Self::Ignore),
-            _ => Err("must be one of error|warn|ignore".into()),
-        }
-    }
-}
-
 lazy_static! {
     /// The maximum parallelism when processing test vectors. Capped at 48.
     static ref TEST_VECTOR_PARALLELISM: usize = std::env::var_os("TEST_VECTOR_PARALLELISM")
@@ -97,7 +75,17 @@ async fn conformance_test_runner() -> anyhow::Result<()> {
 
             async move {
                 // Run the vector processing in a blocking task
-                tokio::task::spawn_blocking(move || run_vector(path, stats, tracer)).await?
+                tokio::task::spawn_blocking(move || {
+                    run_vector(
+                        path,
+                        &ENGINES,
+                        &VectorFilter::default(),
+                        *TEST_VECTOR_POSTCONDITION_MISSING_ACTION,
+                        stats,
+                        tracer,
+                    )
+                })
+                .await?
             }
         })
         .buffer_unordered(*TEST_VECTOR_PARALLELISM)
@@ -173,104 +161,3 @@ async fn conformance_test_runner() -> anyhow::Result<()> {
         Ok(())
     }
 }
-
-/// Runs a single test vector and returns a list of VectorResults,
-/// one per variant.
-fn run_vector(
-    path: PathBuf,
-    stats: Arc<TestStatsRef>,
-    tracer: Arc<TestTraceExporterRef>,
-) -> anyhow::Result<(PathBuf, Vec<VariantResult>)> {
-    let file = File::open(&path)?;
-    let reader = BufReader::new(file);
-
-    // Parse the test vector
-    let mut vector: HashMap<String, Box<serde_json::value::RawValue>> =
-        serde_json::from_reader(reader).context("failed to parse vector")?;
-    let class_json = vector
-        .remove("class")
-        .context("expected test vector to have a class")?;
-
-    let class: &str =
-        serde_json::from_str(class_json.get()).context("failed to parse test vector class")?;
-    let vector_json = serde_json::to_string(&vector)?;
-
-    match class {
-        "message" => {
-            let v: MessageVector =
-                serde_json::from_str(&vector_json).context("failed to parse message vector")?;
-            let skip = !v.selector.as_ref().is_none_or(Selector::supported);
-
-            if skip {
-                let results = v
-                    .preconditions
-                    .variants
-                    .into_iter()
-                    .map(|variant| VariantResult::Skipped {
-                        id: variant.id,
-                        reason: "selector not supported".to_owned(),
-                    })
-                    .collect();
-                return Ok((path, results));
-            }
-
-            // Import the blockstore and do sanity checks
-            let (bs, imported_root) = v.seed_blockstore()?;
-            anyhow::ensure!(
-                imported_root.contains(&v.preconditions.state_tree.root_cid),
-                "imported roots ({}) do not contain precondition CID {}",
-                imported_root.iter().join(", "),
-                v.preconditions.state_tree.root_cid
-            );
-            if !imported_root.contains(&v.postconditions.state_tree.root_cid) {
-                let msg = format!(
-                    "imported roots ({}) do not contain postcondition CID {}",
-                    imported_root.iter().join(", "),
-                    v.postconditions.state_tree.root_cid
-                );
-
-                match *TEST_VECTOR_POSTCONDITION_MISSING_ACTION {
-                    ErrorAction::Error => {
-                        anyhow::bail!(msg);
-                    }
-                    ErrorAction::Warn => {
-                        eprintln!("WARN: {msg} in {}", path.display())
-                    }
-                    ErrorAction::Ignore => (),
-                }
-            }
-
-            // Run all variants
-            let results = v
-                .preconditions
-                .variants
-                .iter()
-                .map(|variant| {
-                    let variant_id = variant.id.clone();
-                    let name = format!("{} | {}", path.display(), variant_id);
-
-                    run_variant(
-                        bs.clone(),
-                        &v,
-                        variant,
-                        &ENGINES,
-                        true,
-                        stats.as_ref().clone(),
-                        tracer
-                            .as_ref()
-                            .clone()
-                            .map(|t| t.export_fun(path.clone(), variant_id.clone())),
-                    )
-                    .with_context(|| format!("failed to run {name}"))
-                    .unwrap_or_else(|e| VariantResult::Failed {
-                        id: variant_id,
-                        reason: e,
-                    })
-                })
-                .collect();
-
-            Ok((path, results))
-        }
-        other => Err(anyhow!("unknown test vector class: {}", other)),
-    }
-}
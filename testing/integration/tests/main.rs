@@ -3,10 +3,12 @@
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::rc::Rc;
+use std::time::Duration;
 
 use anyhow::anyhow;
 use cid::Cid;
-use fvm::executor::{ApplyKind, Executor, ThreadedExecutor};
+use fvm::executor::{ApplyKind, ApplyRet, Executor, ThreadedExecutor};
+use fvm::kernel::DeadlineExceeded;
 use fvm::machine::Machine;
 use fvm_integration_tests::dummy::DummyExterns;
 use fvm_integration_tests::tester::{Account, IntegrationExecutor, Tester};
@@ -20,9 +22,9 @@ use fvm_shared::message::Message;
 use fvm_shared::state::StateTreeVersion;
 use fvm_shared::version::NetworkVersion;
 use fvm_test_actors::wasm_bin::{
-    ADDRESS_ACTOR_BINARY, CREATE_ACTOR_BINARY, CUSTOM_SYSCALL_ACTOR_BINARY, EXIT_DATA_ACTOR_BINARY,
-    HELLO_WORLD_ACTOR_BINARY, IPLD_ACTOR_BINARY, OOM_ACTOR_BINARY, READONLY_ACTOR_BINARY,
-    SSELF_ACTOR_BINARY, STACK_OVERFLOW_ACTOR_BINARY, SYSCALL_ACTOR_BINARY,
+    ADDRESS_ACTOR_BINARY, BLOCK_OOM_ACTOR_BINARY, CREATE_ACTOR_BINARY, CUSTOM_SYSCALL_ACTOR_BINARY,
+    EXIT_DATA_ACTOR_BINARY, HELLO_WORLD_ACTOR_BINARY, IPLD_ACTOR_BINARY, OOM_ACTOR_BINARY,
+    READONLY_ACTOR_BINARY, SSELF_ACTOR_BINARY, STACK_OVERFLOW_ACTOR_BINARY, SYSCALL_ACTOR_BINARY,
     SYSCALL_ACTOR_BINARY_FIP0079, UPGRADE_ACTOR_BINARY, UPGRADE_RECEIVE_ACTOR_BINARY,
 };
 use num_traits::Zero;
@@ -138,6 +140,94 @@ fn ipld() {
     }
 }
 
+#[test]
+fn gas_breakdown() {
+    use fvm::gas::{Gas, GasCategory};
+    use fvm::trace::ExecutionEvent;
+
+    // Instantiate tester
+    let mut tester = new_tester(
+        NV_FOR_TEST,
+        StateTreeVersion::V5,
+        MemoryBlockstore::default(),
+    )
+    .unwrap();
+
+    let sender: [Account; 1] = tester.create_accounts().unwrap();
+
+    // Set actor state
+    let actor_state = State::default();
+    let state_cid = tester.set_state(&actor_state).unwrap();
+
+    // Set actor
+    let actor_address = Address::new_id(10000);
+
+    tester
+        .set_actor_from_bin(
+            IPLD_ACTOR_BINARY,
+            state_cid,
+            actor_address,
+            TokenAmount::zero(),
+        )
+        .unwrap();
+
+    // Instantiate machine
+    tester
+        .instantiate_machine_with_config(
+            DummyExterns,
+            |_| (),
+            |mc| {
+                mc.enable_tracing();
+            },
+        )
+        .unwrap();
+
+    // Send message
+    let message = Message {
+        from: sender[0].1,
+        to: actor_address,
+        gas_limit: 1000000000,
+        method_num: 1,
+        ..Message::default()
+    };
+
+    let res = tester
+        .executor
+        .unwrap()
+        .execute_message(message, ApplyKind::Explicit, 100)
+        .unwrap();
+    assert!(res.msg_receipt.exit_code.is_success());
+
+    let breakdown = res.gas_breakdown.expect("no gas breakdown");
+
+    let traced: Gas = res
+        .exec_trace
+        .iter()
+        .filter_map(|e| match e {
+            ExecutionEvent::GasCharge(charge) => Some(charge.total()),
+            _ => None,
+        })
+        .fold(Gas::zero(), |a, b| a + b);
+    assert_eq!(breakdown.total.total(), traced);
+    assert_eq!(breakdown.total.total().round_up(), res.msg_receipt.gas_used);
+
+    for category in [GasCategory::Message, GasCategory::Wasm, GasCategory::Ipld] {
+        assert!(
+            !breakdown.category(category).total().is_zero(),
+            "no {category:?} gas"
+        );
+    }
+    assert!(breakdown.by_actor.contains_key(&sender[0].0));
+    assert!(breakdown.by_actor.contains_key(&10000));
+    assert_eq!(
+        breakdown
+            .by_category
+            .values()
+            .fold(Gas::zero(), |a, b| a + b.total()),
+        traced
+    );
+}
+
 #[test]
 fn syscalls() {
     syscalls_inner(SYSCALL_ACTOR_BINARY)
@@ -615,6 +705,61 @@ fn out_of_gas() {
     )
 }
 
+#[test]
+fn execution_time_limit() {
+    let mut tester = new_tester(
+        NV_FOR_TEST,
+        StateTreeVersion::V5,
+        MemoryBlockstore::default(),
+    )
+    .unwrap();
+
+    let sender: [Account; 1] = tester.create_accounts().unwrap();
+
+    let wasm_bin = wat::parse_str(
+        r#"(module
+             (memory (export "memory") 1)
+             (func (export "invoke") (param $x i32) (result i32)
+               (loop (br 0))
+               (i32.const 1)))"#,
+    )
+    .unwrap();
+
+    let state_cid = tester.set_state(&State { count: 0 }).unwrap();
+    let actor_address = Address::new_id(10000);
+    tester
+        .set_actor_from_bin(&wasm_bin, state_cid, actor_address, TokenAmount::zero())
+        .unwrap();
+
+    tester
+        .instantiate_machine_with_config(
+            DummyExterns,
+            |_| (),
+            |mc| {
+                mc.set_execution_time_limit(Duration::from_millis(50));
+            },
+        )
+        .unwrap();
+
+    // Enough gas to loop for much longer than the time limit.
+    let message = Message {
+        from: sender[0].1,
+        to: actor_address,
+        gas_limit: 10_000_000_000,
+        method_num: 1,
+        ..Message::default()
+    };
+
+    let mut executor = ThreadedExecutor(tester.executor.unwrap());
+    let err = executor
+        .execute_message(message, ApplyKind::Explicit, 100)
+        .expect_err("expected the execution time limit to be exceeded");
+    assert!(
+        DeadlineExceeded::is_cause_of(&err),
+        "unexpected error: {err:#}"
+    );
+}
+
 #[test]
 fn unreachable() {
     test_exitcode(
@@ -952,6 +1097,113 @@ fn test_allocate_many_and_oom() {
     assert_eq!(res.msg_receipt.exit_code, ExitCode::SYS_ILLEGAL_INSTRUCTION);
 }
 
+#[test]
+fn test_block_oom() {
+    const MAX_MEMORY: usize = 64 << 20;
+
+    let mut tester = new_tester(
+        NV_FOR_TEST,
+        StateTreeVersion::V5,
+        MemoryBlockstore::default(),
+    )
+    .unwrap();
+
+    let sender: [Account; 1] = tester.create_accounts().unwrap();
+
+    let wasm_bin = BLOCK_OOM_ACTOR_BINARY;
+
+    // Set actor state
+    let actor_state = State::default();
+    let state_cid = tester.set_state(&actor_state).unwrap();
+
+    // Set actor
+    let actor_address = Address::new_id(10000);
+
+    tester
+        .set_actor_from_bin(wasm_bin, state_cid, actor_address, TokenAmount::zero())
+        .unwrap();
+
+    // Instantiate machine, counting blocks towards a small memory limit.
+    tester
+        .instantiate_machine_with_config(
+            DummyExterns,
+            |nc| {
+                nc.max_memory_bytes = MAX_MEMORY as u64;
+                nc.enable_block_memory_accounting();
+            },
+            |mc| {
+                mc.enable_tracing();
+            },
+        )
+        .unwrap();
+
+    let mut executor = tester.executor.unwrap();
+
+    // Creating blocks until the limit is hit fails gracefully.
+    let message = Message {
+        from: sender[0].1,
+        to: actor_address,
+        gas_limit: i64::MAX as u64,
+        method_num: 1,
+        ..Message::default()
+    };
+
+    let res = executor
+        .execute_message(message, ApplyKind::Explicit, 100)
+        .unwrap();
+
+    assert_eq!(
+        res.msg_receipt.exit_code,
+        ExitCode::OK,
+        "{:?}",
+        res.failure_info
+    );
+
+    // The actor only stops once the next 1MiB block doesn't fit anymore, so the blocks must have
+    // filled the memory up to the limit (give or take the accounting overhead of a block).
+    let (_, peak) = call_memory(&res);
+    assert!(peak > MAX_MEMORY - (2 << 20), "{peak}");
+    assert!(peak <= MAX_MEMORY, "{peak}");
+
+    // The blocks created within the limit show up in the peak memory of the call.
+    let message = Message {
+        from: sender[0].1,
+        to: actor_address,
+        gas_limit: i64::MAX as u64,
+        method_num: 2,
+        sequence: 1,
+        ..Message::default()
+    };
+
+    let res = executor
+        .execute_message(message, ApplyKind::Explicit, 100)
+        .unwrap();
+
+    assert_eq!(
+        res.msg_receipt.exit_code,
+        ExitCode::OK,
+        "{:?}",
+        res.failure_info
+    );
+
+    let (initial, peak) = call_memory(&res);
+    assert!(peak - initial >= 16 << 20, "{initial} {peak}");
+    assert!(peak <= MAX_MEMORY, "{peak}");
+}
+
+/// The initial and peak memory of the first call of a message.
+fn call_memory(res: &ApplyRet) -> (usize, usize) {
+    use fvm::trace::ExecutionEvent;
+
+    res.exec_trace
+        .iter()
+        .find_map(|e| match e {
+            ExecutionEvent::CallMemory { initial, peak } => Some((*initial, *peak)),
+            _ => None,
+        })
+        .expect("no call memory in the trace")
+}
+
 #[test]
 fn test_oom3() {
     // Test Out of Memory Condition 3: Not enough total wasm memory; this uses the hello
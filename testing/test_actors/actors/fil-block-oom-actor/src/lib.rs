@@ -0,0 +1,49 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+/// The size of the blocks created by this actor (the maximum block size).
+#[cfg(target_arch = "wasm32")]
+const BLOCK_SIZE: usize = 1 << 20;
+
+/// The number of blocks created by method 2.
+#[cfg(target_arch = "wasm32")]
+const SOME_BLOCKS: usize = 16;
+
+/// Tries to exhaust the execution memory by creating IPLD blocks.
+#[unsafe(no_mangle)]
+#[cfg(target_arch = "wasm32")]
+pub fn invoke(_blk: u32) -> u32 {
+    use fvm_sdk as sdk;
+    use fvm_shared::error::{ErrorNumber, ExitCode};
+
+    sdk::initialize(); // gives us debug messages on panic
+    let method = sdk::message::method_number();
+
+    // A single buffer, so that only the blocks use up memory.
+    let data = vec![0u8; BLOCK_SIZE];
+
+    match method {
+        // Create blocks until we run out of memory, exiting successfully when we do.
+        1 => loop {
+            match sdk::ipld::put_block(fvm_shared::IPLD_RAW, &data) {
+                Ok(_) => {}
+                Err(ErrorNumber::LimitExceeded) => return 0,
+                Err(e) => sdk::vm::abort(
+                    ExitCode::FIRST_USER_EXIT_CODE,
+                    Some(format!("unexpected error: {}", e).as_str()),
+                ),
+            }
+        },
+        // Create a few blocks.
+        2 => {
+            for _ in 0..SOME_BLOCKS {
+                sdk::ipld::put_block(fvm_shared::IPLD_RAW, &data).expect("failed to create block");
+            }
+            0
+        }
+        _ => sdk::vm::abort(
+            ExitCode::FIRST_USER_EXIT_CODE,
+            Some(format!("bad method {}", method).as_str()),
+        ),
+    }
+}
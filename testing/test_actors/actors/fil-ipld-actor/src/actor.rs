@@ -21,6 +21,7 @@ pub fn invoke(_: u32) -> u32 {
     test_create_block();
     test_stat_block();
     test_link_block();
+    test_close_block();
 
     0
 }
@@ -250,3 +251,30 @@ fn test_link_block() {
         assert_eq!(res, Err(ErrorNumber::IllegalCid));
     }
 }
+
+fn test_close_block() {
+    let bytes = gen_test_bytes(10 << 10);
+
+    unsafe {
+        let block_id =
+            sdk::sys::ipld::block_create(DAG_CBOR, bytes.as_ptr(), bytes.len() as u32).unwrap();
+
+        // Test happy case
+        sdk::ipld::close_block(block_id).expect("should work");
+
+        // Test that the closed block can't be used anymore
+        assert_eq!(
+            sdk::sys::ipld::block_stat(block_id),
+            Err(ErrorNumber::InvalidHandle)
+        );
+        assert_eq!(
+            sdk::ipld::close_block(block_id),
+            Err(ErrorNumber::InvalidHandle)
+        );
+
+        // Test that the handle is reused by the next block
+        let new_block_id =
+            sdk::sys::ipld::block_create(DAG_CBOR, bytes.as_ptr(), bytes.len() as u32).unwrap();
+        assert_eq!(new_block_id, block_id);
+    }
+}
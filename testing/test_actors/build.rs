@@ -30,6 +30,7 @@ const ACTORS: &[(&str, &str)] = &[
     ("READONLY_ACTOR_BINARY", "fil_readonly_actor"),
     ("CREATE_ACTOR_BINARY", "fil_create_actor"),
     ("OOM_ACTOR_BINARY", "fil_oom_actor"),
+    ("BLOCK_OOM_ACTOR_BINARY", "fil_block_oom_actor"),
     ("SSELF_ACTOR_BINARY", "fil_sself_actor"),
     ("UPGRADE_ACTOR_BINARY", "fil_upgrade_actor"),
     ("UPGRADE_RECEIVE_ACTOR_BINARY", "fil_upgrade_receive_actor"),
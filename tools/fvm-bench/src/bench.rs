@@ -0,0 +1,239 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::time::{Duration, Instant};
+
+use fvm::executor::{ApplyKind, ApplyRet, Executor};
+use fvm::trace::ExecutionEvent;
+use fvm_integration_tests::tester::{BasicAccount, BasicTester};
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::MethodNum;
+use fvm_shared::address::Address;
+use fvm_shared::message::Message;
+use serde::Serialize;
+
+/// Name of the gas charge whose elapsed time is recorded by `record_init_time`, covering the
+/// compilation (if not cached) and instantiation of a wasm module.
+const WASM_INIT_CHARGE: &str = "wasm_memory_init";
+
+/// A message that can be applied repeatedly against the same tester.
+pub struct Invocation {
+    pub account: BasicAccount,
+    pub to: Address,
+    pub method: MethodNum,
+    pub params: RawBytes,
+    pub gas_limit: u64,
+}
+
+impl Invocation {
+    /// Applies the invocation as an explicit message, returning the result along with the
+    /// wall time it took to execute.
+    pub fn execute(&mut self, tester: &mut BasicTester) -> anyhow::Result<(ApplyRet, Duration)> {
+        let msg = Message {
+            from: self.account.account.1,
+            to: self.to,
+            sequence: self.account.seqno,
+            gas_limit: self.gas_limit,
+            method_num: self.method,
+            params: self.params.clone(),
+            ..Message::default()
+        };
+        let mlen = msg.params.len();
+
+        let res = tester.with_executor(|e| {
+            let start = Instant::now();
+            let res = e.execute_message(msg, ApplyKind::Explicit, mlen)?;
+            Ok((res, start.elapsed()))
+        })?;
+
+        self.account.seqno += 1;
+        Ok(res)
+    }
+}
+
+/// Measurements of a single invocation.
+#[derive(Serialize, Debug, Clone)]
+pub struct Run {
+    pub gas_used: u64,
+    pub exit_code: u32,
+    pub wall_time_ns: u64,
+    /// Time spent compiling and instantiating wasm modules, if execution was traced.
+    pub init_time_ns: Option<u64>,
+}
+
+impl Run {
+    fn new(res: &ApplyRet, wall_time: Duration) -> Self {
+        let init_time = res
+            .exec_trace
+            .iter()
+            .filter_map(|evt| match evt {
+                ExecutionEvent::GasCharge(charge) if charge.name == WASM_INIT_CHARGE => {
+                    charge.elapsed.get().copied()
+                }
+                _ => None,
+            })
+            .reduce(|a, b| a + b);
+
+        Run {
+            gas_used: res.msg_receipt.gas_used,
+            exit_code: res.msg_receipt.exit_code.value(),
+            wall_time_ns: wall_time.as_nanos() as u64,
+            init_time_ns: init_time.map(|d| d.as_nanos() as u64),
+        }
+    }
+}
+
+/// Aggregated statistics over a set of samples.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub median: u64,
+    pub p95: u64,
+    pub stddev: f64,
+}
+
+impl Stats {
+    /// Computes the statistics of the given samples, returning `None` if there are none.
+    pub fn new(samples: impl IntoIterator<Item = u64>) -> Option<Self> {
+        let mut samples: Vec<u64> = samples.into_iter().collect();
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+
+        let n = samples.len() as f64;
+        let mean = samples.iter().map(|s| *s as f64).sum::<f64>() / n;
+        let variance = samples
+            .iter()
+            .map(|s| (*s as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        Some(Stats {
+            min: samples[0],
+            max: samples[samples.len() - 1],
+            mean,
+            median: percentile(&samples, 50),
+            p95: percentile(&samples, 95),
+            stddev: variance.sqrt(),
+        })
+    }
+}
+
+// Nearest-rank percentile over sorted, non-empty samples.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// The result of benchmarking an invocation.
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub warmup: usize,
+    pub iterations: usize,
+    pub runs: Vec<Run>,
+    pub gas_used: Option<Stats>,
+    pub wall_time_ns: Option<Stats>,
+    pub init_time_ns: Option<Stats>,
+}
+
+impl Report {
+    /// Returns the number of measured runs that did not exit successfully.
+    pub fn failures(&self) -> usize {
+        self.runs.iter().filter(|r| r.exit_code != 0).count()
+    }
+
+    pub fn print_summary(&self) {
+        println!();
+        println!(
+            "Iterations: {} (warmup: {}, failed: {})",
+            self.iterations,
+            self.warmup,
+            self.failures()
+        );
+        let rows = [
+            ("Gas Used", &self.gas_used),
+            ("Wall Time (ns)", &self.wall_time_ns),
+            ("Init Time (ns)", &self.init_time_ns),
+        ];
+        for (name, stats) in rows {
+            if let Some(s) = stats {
+                println!(
+                    "{name}: min={} median={} p95={} max={} mean={:.1} stddev={:.1}",
+                    s.min, s.median, s.p95, s.max, s.mean, s.stddev
+                );
+            }
+        }
+    }
+}
+
+/// Executes the invocation `warmup` times without recording anything, then `iterations` times
+/// recording each run. Returns the report along with the result of the last measured run.
+pub fn run(
+    tester: &mut BasicTester,
+    invocation: &mut Invocation,
+    warmup: usize,
+    iterations: usize,
+) -> anyhow::Result<(Report, Option<ApplyRet>)> {
+    for _ in 0..warmup {
+        invocation.execute(tester)?;
+    }
+
+    let mut runs = Vec::with_capacity(iterations);
+    let mut last = None;
+    for _ in 0..iterations {
+        let (res, wall_time) = invocation.execute(tester)?;
+        runs.push(Run::new(&res, wall_time));
+        last = Some(res);
+    }
+
+    let report = Report {
+        warmup,
+        iterations,
+        gas_used: Stats::new(runs.iter().map(|r| r.gas_used)),
+        wall_time_ns: Stats::new(runs.iter().map(|r| r.wall_time_ns)),
+        init_time_ns: Stats::new(runs.iter().filter_map(|r| r.init_time_ns)),
+        runs,
+    };
+    Ok((report, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_empty() {
+        assert_eq!(Stats::new([]), None);
+    }
+
+    #[test]
+    fn test_stats_single() {
+        let stats = Stats::new([7]).unwrap();
+        assert_eq!(stats.min, 7);
+        assert_eq!(stats.max, 7);
+        assert_eq!(stats.median, 7);
+        assert_eq!(stats.p95, 7);
+        assert_eq!(stats.mean, 7.0);
+        assert_eq!(stats.stddev, 0.0);
+    }
+
+    #[test]
+    fn test_stats_unsorted() {
+        let stats = Stats::new((1..=20).rev()).unwrap();
+        assert_eq!(stats.min, 1);
+        assert_eq!(stats.max, 20);
+        assert_eq!(stats.median, 10);
+        assert_eq!(stats.p95, 19);
+        assert_eq!(stats.mean, 10.5);
+    }
+
+    #[test]
+    fn test_stats_stddev() {
+        let stats = Stats::new([2, 4, 4, 4, 5, 5, 7, 9]).unwrap();
+        assert_eq!(stats.mean, 5.0);
+        assert_eq!(stats.stddev, 2.0);
+    }
+}
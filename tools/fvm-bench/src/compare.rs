@@ -0,0 +1,162 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use fvm::executor::ApplyRet;
+use fvm::gas::MILLIGAS_PRECISION;
+use fvm::trace::ExecutionEvent;
+use serde::Serialize;
+
+/// The gas charged under a single charge name during an execution.
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChargeTotal {
+    pub count: u64,
+    pub compute_milligas: u64,
+    pub other_milligas: u64,
+}
+
+impl ChargeTotal {
+    pub fn total_milligas(&self) -> u64 {
+        self.compute_milligas.saturating_add(self.other_milligas)
+    }
+}
+
+/// Aggregates the gas charges in the execution trace by charge name.
+pub fn gas_profile(res: &ApplyRet) -> BTreeMap<String, ChargeTotal> {
+    let mut profile = BTreeMap::<String, ChargeTotal>::new();
+    for evt in &res.exec_trace {
+        if let ExecutionEvent::GasCharge(charge) = evt {
+            let total = profile.entry(charge.name.to_string()).or_default();
+            total.count += 1;
+            total.compute_milligas += charge.compute_gas.as_milligas();
+            total.other_milligas += charge.other_gas.as_milligas();
+        }
+    }
+    profile
+}
+
+/// The difference in gas charged under a single charge name.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChargeDelta {
+    pub name: String,
+    pub a: ChargeTotal,
+    pub b: ChargeTotal,
+    pub delta_milligas: i64,
+}
+
+/// A side by side comparison of two executions of the same invocation.
+#[derive(Serialize, Debug)]
+pub struct Comparison {
+    pub exit_code_a: u32,
+    pub exit_code_b: u32,
+    pub gas_used_a: u64,
+    pub gas_used_b: u64,
+    pub gas_used_delta: i64,
+    pub charges: Vec<ChargeDelta>,
+}
+
+impl Comparison {
+    pub fn new(a: &ApplyRet, b: &ApplyRet) -> Self {
+        let mut profile_a = gas_profile(a);
+        let mut profile_b = gas_profile(b);
+
+        let names: BTreeSet<String> = profile_a.keys().chain(profile_b.keys()).cloned().collect();
+        let charges = names
+            .into_iter()
+            .map(|name| {
+                let a = profile_a.remove(&name).unwrap_or_default();
+                let b = profile_b.remove(&name).unwrap_or_default();
+                ChargeDelta {
+                    delta_milligas: delta(a.total_milligas(), b.total_milligas()),
+                    name,
+                    a,
+                    b,
+                }
+            })
+            .collect();
+
+        Comparison {
+            exit_code_a: a.msg_receipt.exit_code.value(),
+            exit_code_b: b.msg_receipt.exit_code.value(),
+            gas_used_a: a.msg_receipt.gas_used,
+            gas_used_b: b.msg_receipt.gas_used,
+            gas_used_delta: delta(a.msg_receipt.gas_used, b.msg_receipt.gas_used),
+            charges,
+        }
+    }
+
+    pub fn print_table(&self) {
+        println!("Exit Code: {} -> {}", self.exit_code_a, self.exit_code_b);
+        println!(
+            "Gas Used: {} -> {} ({:+})",
+            self.gas_used_a, self.gas_used_b, self.gas_used_delta
+        );
+        println!();
+
+        let width = self
+            .charges
+            .iter()
+            .map(|c| c.name.len())
+            .chain(["Charge".len()])
+            .max()
+            .unwrap_or_default();
+        println!(
+            "{:<width$}  {:>8}  {:>8}  {:>16}  {:>16}  {:>16}",
+            "Charge", "Count A", "Count B", "Gas A", "Gas B", "Delta"
+        );
+        for c in &self.charges {
+            println!(
+                "{:<width$}  {:>8}  {:>8}  {:>16}  {:>16}  {:>16}",
+                c.name,
+                c.a.count,
+                c.b.count,
+                format_milligas(c.a.total_milligas() as i128),
+                format_milligas(c.b.total_milligas() as i128),
+                format_milligas_delta(c.delta_milligas),
+            );
+        }
+    }
+}
+
+fn delta(a: u64, b: u64) -> i64 {
+    (b as i128 - a as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn format_milligas(milligas: i128) -> String {
+    let precision = MILLIGAS_PRECISION as i128;
+    let sign = if milligas < 0 { "-" } else { "" };
+    let milligas = milligas.abs();
+    format!("{sign}{}.{:03}", milligas / precision, milligas % precision)
+}
+
+fn format_milligas_delta(milligas: i64) -> String {
+    let formatted = format_milligas(milligas as i128);
+    if milligas > 0 {
+        format!("+{formatted}")
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta() {
+        assert_eq!(delta(10, 15), 5);
+        assert_eq!(delta(15, 10), -5);
+        assert_eq!(delta(0, u64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn test_format_milligas() {
+        assert_eq!(format_milligas(0), "0.000");
+        assert_eq!(format_milligas(1_500), "1.500");
+        assert_eq!(format_milligas(-42), "-0.042");
+        assert_eq!(format_milligas_delta(2_001), "+2.001");
+        assert_eq!(format_milligas_delta(-2_001), "-2.001");
+        assert_eq!(format_milligas_delta(0), "0.000");
+    }
+}
@@ -3,10 +3,14 @@
 
 use anyhow::anyhow;
 use fvm::executor::ApplyRet;
+use fvm_integration_tests::testkit::fevm::EVMMethod;
 use fvm_integration_tests::{tester, testkit};
-use fvm_ipld_encoding::BytesDe;
+use fvm_ipld_encoding::{BytesDe, BytesSer, RawBytes};
 use fvm_shared::address::Address;
 
+use crate::Output;
+use crate::bench::Invocation;
+
 // Eth ABI (solidity) panic codes.
 const PANIC_ERROR_CODES: [(u64, &str); 10] = [
     (0x00, "Panic()"),
@@ -25,70 +29,31 @@ const PANIC_ERROR_CODES: [(u64, &str); 10] = [
 const ERROR_FUNCTION_SELECTOR: &[u8] = b"\x08\xc3\x79\xa0"; // Error(string)
 const PANIC_FUNCTION_SELECTOR: &[u8] = b"\x4e\x48\x7b\x71"; // Panic(uint256)
 
-fn handle_result(tester: &tester::BasicTester, name: &str, res: &ApplyRet) -> anyhow::Result<()> {
-    let (trace, events) = tester
-        .options
-        .as_ref()
-        .map(|o| (o.trace, o.events))
-        .unwrap_or_default();
-
-    if trace && !res.exec_trace.is_empty() {
-        println!();
-        println!("**");
-        println!("* BEGIN {name} execution trace");
-        println!("**");
-        println!();
-        for tr in &res.exec_trace {
-            println!("{:?}", tr)
-        }
-        println!();
-        println!("**");
-        println!("* END {name} execution trace");
-        println!("**");
-        println!();
-    }
-    if events && !res.events.is_empty() {
-        println!();
-        println!("**");
-        println!("* BEGIN {name} events");
-        println!("**");
-        println!();
-        for evt in &res.events {
-            println!("{:?}", evt)
-        }
-        println!();
-        println!("**");
-        println!("* END {name} events");
-        println!("**");
-        println!();
-    }
-
-    if let Some(bt) = &res.failure_info {
-        println!("{bt}");
-    }
-
-    if res.msg_receipt.exit_code.is_success() {
-        Ok(())
-    } else {
+fn handle_result(output: Output, name: &str, res: &ApplyRet) -> anyhow::Result<()> {
+    crate::handle_result(output, name, res, "execution trace", |tr| {
+        Some(format!("{:?}", tr))
+    })
+    .inspect_err(|_| {
         if res.msg_receipt.exit_code == 33.into() {
             let BytesDe(returnval) = res.msg_receipt.return_data.deserialize().unwrap();
             println!("Revert Reason: {}", parse_eth_revert(&returnval).unwrap());
         }
-        Err(anyhow!("{name} failed"))
-    }
+    })
 }
 
-pub fn run(
+/// Deploys the contract, returning an invocation of the given entrypoint on it.
+pub fn prepare(
     tester: &mut tester::BasicTester,
+    output: Output,
     contract: &[u8],
     entrypoint: &[u8],
     params: &[u8],
     gas: u64,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Invocation> {
     let mut account = tester.create_basic_account()?;
 
     let create_res = testkit::fevm::create_contract(tester, &mut account, contract)?;
-    handle_result(tester, "contract creation", &create_res)?;
+    handle_result(output, "contract creation", &create_res)?;
 
     let create_return: testkit::fevm::CreateReturn =
         create_res.msg_receipt.return_data.deserialize().unwrap();
@@ -99,13 +64,23 @@ pub fn run(
     let mut input_params = Vec::from(params);
     input_data.append(&mut input_params);
 
-    let invoke_res = testkit::fevm::invoke_contract(tester, &mut account, actor, &input_data, gas)?;
+    Ok(Invocation {
+        account,
+        to: actor,
+        method: EVMMethod::InvokeContract as u64,
+        params: RawBytes::serialize(BytesSer(&input_data))?,
+        gas_limit: gas,
+    })
+}
+
+/// Prints the result of a contract invocation.
+pub fn report(output: Output, invoke_res: &ApplyRet) -> anyhow::Result<()> {
     let BytesDe(returnval) = invoke_res.msg_receipt.return_data.deserialize().unwrap();
     println!("Exit Code: {}", invoke_res.msg_receipt.exit_code);
     println!("Result: {}", hex::encode(returnval));
     println!("Gas Used: {}", invoke_res.msg_receipt.gas_used);
 
-    handle_result(tester, "contract invocation", &invoke_res)
+    handle_result(output, "contract invocation", invoke_res)
 }
 
 // Parses the error message from a revert reason of type Error(string) or Panic(uint256)
@@ -1,16 +1,40 @@
 // Copyright 2021-2023 Protocol Labs
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod bench;
+mod compare;
 mod fevm;
+mod wasm;
 
 use std::fs;
 
 use anyhow::{Context, anyhow};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use fvm::executor::ApplyRet;
+use fvm::trace::ExecutionEvent;
 use fvm_integration_tests::tester;
 
+use crate::bench::Invocation;
+use crate::compare::Comparison;
+
 /// Run a contract invocation for benchmarking purposes
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    args: Option<Args>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the same invocation against two bundles or two contracts and compare the gas charges
+    Compare(CompareArgs),
+}
+
+#[derive(clap::Args, Debug)]
 struct Args {
     /// Execution mode: wasm or fevm
     #[arg(short, long, default_value = "fevm")]
@@ -32,46 +56,281 @@ struct Args {
     #[arg(short, long)]
     bundle: String,
 
-    /// Contract file.
+    /// Contract file; hex bytecode for fevm, a wasm binary for wasm.
     contract: String,
 
     /// Invocation method; solidity entry point for fevm, actor method for wasm.
     method: String,
 
-    /// Invocation parameters, in hex.
+    /// Invocation parameters, in hex; CBOR encoded for wasm.
     params: String,
 
     #[arg(short, long, default_value = "10000000000")]
     /// Gas limit in atto precision to use during invocation.
     /// Default: 10 billion gas
     gas_limit: u64,
+
+    /// Number of measured invocations.
+    #[arg(long, default_value = "1")]
+    iterations: usize,
+
+    /// Number of invocations to run before measuring, e.g. to warm up the module cache.
+    #[arg(long, default_value = "0")]
+    warmup: usize,
+
+    /// Measure the time spent compiling and instantiating wasm modules. This relies on execution
+    /// tracing (also enabled by `--trace`), which adds overhead to the measured wall times.
+    #[arg(long, default_value = "false")]
+    init_time: bool,
+
+    /// Output format.
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: Format,
 }
 
-fn run() -> anyhow::Result<()> {
-    env_logger::init();
-    let args = Args::parse();
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Human readable output.
+    Text,
+    /// A machine readable report with per-run measurements and aggregated statistics.
+    Json,
+}
+
+#[derive(clap::Args, Debug)]
+struct CompareArgs {
+    /// Execution mode: wasm or fevm
+    #[arg(short, long, default_value = "fevm")]
+    mode: String,
+
+    /// Emit debug logs
+    #[arg(short, long, default_value = "false")]
+    debug: bool,
+
+    /// Builtin actors bundle to use.
+    #[arg(short, long)]
+    bundle: String,
+
+    /// Builtin actors bundle to compare against; defaults to the one passed with `--bundle`.
+    #[arg(long)]
+    other_bundle: Option<String>,
+
+    /// Contract file; hex bytecode for fevm, a wasm binary for wasm.
+    contract: String,
+
+    /// Contract file to compare against; defaults to `<CONTRACT>`.
+    #[arg(long)]
+    other_contract: Option<String>,
+
+    /// Invocation method; solidity entry point for fevm, actor method for wasm.
+    method: String,
+
+    /// Invocation parameters, in hex; CBOR encoded for wasm.
+    params: String,
+
+    #[arg(short, long, default_value = "10000000000")]
+    /// Gas limit in atto precision to use during invocation.
+    /// Default: 10 billion gas
+    gas_limit: u64,
+
+    /// Output format.
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: Format,
+}
+
+/// What to print alongside the invocation results.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Output {
+    pub trace: bool,
+    pub events: bool,
+}
+
+type PrintResult = fn(Output, &ApplyRet) -> anyhow::Result<()>;
+
+/// Prints the execution trace (the entries `format_trace` formats, under the given title) and the
+/// events of an execution if requested, and its failure backtrace if any. Fails if the execution
+/// failed.
+fn handle_result(
+    output: Output,
+    name: &str,
+    res: &ApplyRet,
+    trace_title: &str,
+    format_trace: impl Fn(&ExecutionEvent) -> Option<String>,
+) -> anyhow::Result<()> {
+    let Output { trace, events } = output;
+
+    if trace && !res.exec_trace.is_empty() {
+        print_section(
+            &format!("{name} {trace_title}"),
+            res.exec_trace.iter().filter_map(format_trace),
+        );
+    }
+    if events && !res.events.is_empty() {
+        print_section(
+            &format!("{name} events"),
+            res.events.iter().map(|evt| format!("{:?}", evt)),
+        );
+    }
+
+    if let Some(bt) = &res.failure_info {
+        println!("{bt}");
+    }
+
+    if res.msg_receipt.exit_code.is_success() {
+        Ok(())
+    } else {
+        Err(anyhow!("{name} failed"))
+    }
+}
+
+fn print_section(title: &str, lines: impl Iterator<Item = String>) {
+    println!();
+    println!("**");
+    println!("* BEGIN {title}");
+    println!("**");
+    println!();
+    for line in lines {
+        println!("{line}");
+    }
+    println!();
+    println!("**");
+    println!("* END {title}");
+    println!("**");
+    println!();
+}
+
+/// Reads the contract and deploys it according to the execution mode, returning the invocation
+/// to benchmark along with the function printing its result.
+fn prepare(
+    tester: &mut tester::BasicTester,
+    output: Output,
+    mode: &str,
+    contract: &str,
+    method: &str,
+    params: &str,
+    gas_limit: u64,
+) -> anyhow::Result<(Invocation, PrintResult)> {
+    match mode {
+        "fevm" => {
+            let contract_hex = fs::read_to_string(contract).context("error reading contract")?;
+            let contract = hex::decode(contract_hex).context("error decoding contract")?;
+            let entrypoint = hex::decode(method).context("error decoding contract entrypoint")?;
+            let params = hex::decode(params).context("error decoding contract params")?;
+
+            let invocation =
+                fevm::prepare(tester, output, &contract, &entrypoint, &params, gas_limit)
+                    .context("contract creation failed")?;
+            Ok((invocation, fevm::report))
+        }
+        "wasm" => {
+            let wasm_bin = fs::read(contract).context("error reading actor")?;
+            let method = method
+                .parse()
+                .context("error parsing actor method number")?;
+            let params = hex::decode(params).context("error decoding actor params")?;
+
+            let invocation = wasm::prepare(tester, &wasm_bin, method, &params, gas_limit)
+                .context("actor deployment failed")?;
+            Ok((invocation, wasm::report))
+        }
+        _ => Err(anyhow!("unknown mode {mode}")),
+    }
+}
+
+fn bench(args: Args) -> anyhow::Result<()> {
+    if args.iterations == 0 {
+        return Err(anyhow!("at least one iteration is required"));
+    }
+
+    // Tracing slows down execution, so only enable it when asked to.
     let options = tester::ExecutionOptions {
         debug: args.debug,
-        trace: args.trace,
+        trace: args.trace || args.init_time,
         events: args.events,
     };
+    let output = match args.format {
+        Format::Text => Output {
+            trace: args.trace,
+            events: args.events,
+        },
+        Format::Json => Output::default(),
+    };
     let mut tester = tester::BasicTester::new_basic_tester(args.bundle, options)?;
 
-    match args.mode.as_str() {
-        "fevm" => {
-            let contract_hex =
-                fs::read_to_string(args.contract).context("error reading contract")?;
-            let contract = hex::decode(contract_hex).context("error decoding contract")?;
-            let entrypoint =
-                hex::decode(args.method).context("error decoding contract entrypoint")?;
-            let params = hex::decode(args.params).context("error decoding contract params")?;
+    let (mut invocation, print_result) = prepare(
+        &mut tester,
+        output,
+        &args.mode,
+        &args.contract,
+        &args.method,
+        &args.params,
+        args.gas_limit,
+    )?;
 
-            fevm::run(&mut tester, &contract, &entrypoint, &params, args.gas_limit)
-                .context("contract execution failed")
+    let (report, last) = bench::run(&mut tester, &mut invocation, args.warmup, args.iterations)?;
+    match args.format {
+        Format::Text => {
+            let last = last.expect("at least one iteration");
+            print_result(output, &last).context("execution failed")?;
+            if args.iterations > 1 {
+                report.print_summary();
+            }
         }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    match report.failures() {
+        0 => Ok(()),
+        n => Err(anyhow!("{n} of {} runs failed", args.iterations)),
+    }
+}
 
-        "wasm" => Err(anyhow!("wasm actors not supported yet")),
-        _ => Err(anyhow!("unknown mode {}", args.mode)),
+fn compare(args: CompareArgs) -> anyhow::Result<()> {
+    let other_bundle = args.other_bundle.as_deref().unwrap_or(&args.bundle);
+    let other_contract = args.other_contract.as_deref().unwrap_or(&args.contract);
+    if other_bundle == args.bundle && other_contract == args.contract {
+        return Err(anyhow!(
+            "nothing to compare; pass --other-bundle and/or --other-contract"
+        ));
+    }
+
+    let execute = |bundle: &str, contract: &str| -> anyhow::Result<ApplyRet> {
+        let options = tester::ExecutionOptions {
+            debug: args.debug,
+            trace: true,
+            events: false,
+        };
+        let mut tester = tester::BasicTester::new_basic_tester(bundle.to_owned(), options)?;
+        let (mut invocation, _) = prepare(
+            &mut tester,
+            Output::default(),
+            &args.mode,
+            contract,
+            &args.method,
+            &args.params,
+            args.gas_limit,
+        )?;
+        let (res, _) = invocation.execute(&mut tester)?;
+        Ok(res)
+    };
+
+    let a = execute(&args.bundle, &args.contract).context("baseline execution failed")?;
+    let b = execute(other_bundle, other_contract).context("comparison execution failed")?;
+
+    let comparison = Comparison::new(&a, &b);
+    match args.format {
+        Format::Text => comparison.print_table(),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&comparison)?),
+    }
+    Ok(())
+}
+
+fn run() -> anyhow::Result<()> {
+    env_logger::init();
+    let cli = Cli::parse();
+    match (cli.command, cli.args) {
+        (Some(Command::Compare(args)), _) => compare(args),
+        (None, Some(args)) => bench(args),
+        (None, None) => unreachable!("clap requires either a command or the arguments"),
     }
 }
 
@@ -0,0 +1,66 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use fvm::executor::ApplyRet;
+use fvm::trace::ExecutionEvent;
+use fvm_integration_tests::tester;
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::MethodNum;
+use fvm_shared::address::Address;
+use fvm_shared::bigint::Zero;
+use fvm_shared::econ::TokenAmount;
+
+use crate::Output;
+use crate::bench::Invocation;
+
+/// The ID at which the benchmarked actor is deployed.
+const ACTOR_ID: u64 = 10000;
+
+fn handle_result(output: Output, name: &str, res: &ApplyRet) -> anyhow::Result<()> {
+    crate::handle_result(output, name, res, "gas charges", |tr| match tr {
+        ExecutionEvent::GasCharge(charge) => Some(format!(
+            "{}: compute={} other={} total={}",
+            charge.name,
+            charge.compute_gas,
+            charge.other_gas,
+            charge.total()
+        )),
+        _ => None,
+    })
+}
+
+/// Deploys the actor, returning an invocation of the given method on it.
+pub fn prepare(
+    tester: &mut tester::BasicTester,
+    wasm_bin: &[u8],
+    method: MethodNum,
+    params: &[u8],
+    gas: u64,
+) -> anyhow::Result<Invocation> {
+    let account = tester.create_basic_account()?;
+
+    // deploy the actor with an empty state; actors are expected to initialize their own state.
+    let state_cid = tester.set_state(&[(); 0])?;
+    let actor = Address::new_id(ACTOR_ID);
+    tester.set_actor_from_bin(wasm_bin, state_cid, actor, TokenAmount::zero())?;
+
+    Ok(Invocation {
+        account,
+        to: actor,
+        method,
+        params: RawBytes::new(params.to_vec()),
+        gas_limit: gas,
+    })
+}
+
+/// Prints the result of an actor invocation.
+pub fn report(output: Output, invoke_res: &ApplyRet) -> anyhow::Result<()> {
+    println!("Exit Code: {}", invoke_res.msg_receipt.exit_code);
+    println!(
+        "Result: {}",
+        hex::encode(invoke_res.msg_receipt.return_data.bytes())
+    );
+    println!("Gas Used: {}", invoke_res.msg_receipt.gas_used);
+
+    handle_result(output, "actor invocation", invoke_res)
+}
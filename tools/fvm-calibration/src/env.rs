@@ -0,0 +1,146 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use anyhow::{Context, anyhow};
+use fvm::executor::{ApplyKind, ApplyRet, Executor};
+use fvm::gas::{Gas, PriceList};
+use fvm_integration_tests::bundle;
+use fvm_integration_tests::dummy::DummyExterns;
+use fvm_integration_tests::tester::{Account, Tester};
+use fvm_ipld_blockstore::MemoryBlockstore;
+use fvm_ipld_encoding::RawBytes;
+use fvm_ipld_encoding::tuple::*;
+use fvm_shared::address::Address;
+use fvm_shared::econ::TokenAmount;
+use fvm_shared::message::Message;
+use fvm_shared::state::StateTreeVersion;
+use fvm_shared::version::NetworkVersion;
+use fvm_test_actors::wasm_bin::GAS_CALIBRATION_ACTOR_BINARY;
+use num_traits::Zero;
+use serde::Serialize;
+
+/// The gas limit of the calibration messages; enough for any of the scenarios.
+pub const ENOUGH_GAS: Gas = Gas::new(1_000_000_000);
+
+/// The ID at which the gas calibration actor is deployed.
+pub const CALIBRATION_ACTOR_ID: u64 = 10000;
+
+/// The ID at which the no-op actor, the target of sends, is deployed.
+pub const NOP_ACTOR_ID: u64 = 10001;
+
+const NOP_ACTOR: &str = r#"
+(module
+  (memory (export "memory") 1)
+  (func (export "invoke") (param $x i32) (result i32)
+    (i32.const 0)
+  )
+)
+"#;
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, Default)]
+struct State {
+    count: u64,
+}
+
+/// A machine with the gas calibration actor deployed, executing messages with tracing enabled so
+/// that the timed gas charges can be collected.
+pub struct CalibrationEnv {
+    tester: Tester<MemoryBlockstore, DummyExterns>,
+    price_list: &'static PriceList,
+    sender: Account,
+    actor_address: Address,
+    actor_sequence: u64,
+}
+
+impl CalibrationEnv {
+    /// Create a new environment from a builtin-actors bundle, charging gas according to the given
+    /// price list.
+    pub fn new(bundle: &[u8], price_list: &'static PriceList) -> anyhow::Result<Self> {
+        let blockstore = MemoryBlockstore::default();
+        let root = bundle::import_bundle(&blockstore, bundle).context("failed to import bundle")?;
+
+        let mut tester = Tester::new(NetworkVersion::V21, StateTreeVersion::V5, root, blockstore)?;
+
+        let [sender] = tester.create_accounts()?;
+
+        let state_cid = tester.set_state(&State::default())?;
+
+        let actor_address = Address::new_id(CALIBRATION_ACTOR_ID);
+        tester.set_actor_from_bin(
+            GAS_CALIBRATION_ACTOR_BINARY,
+            state_cid,
+            actor_address,
+            TokenAmount::from_whole(100),
+        )?;
+
+        let nop_actor_bin = wat::parse_str(NOP_ACTOR)?;
+        tester.set_actor_from_bin(
+            &nop_actor_bin,
+            state_cid,
+            Address::new_id(NOP_ACTOR_ID),
+            TokenAmount::zero(),
+        )?;
+
+        tester.instantiate_machine_with_config(
+            DummyExterns,
+            |nc| {
+                nc.override_price_list(price_list);
+            },
+            |mc| {
+                mc.enable_tracing();
+            },
+        )?;
+
+        Ok(CalibrationEnv {
+            tester,
+            price_list,
+            sender,
+            actor_address,
+            actor_sequence: 0,
+        })
+    }
+
+    /// The price list the environment charges gas with.
+    pub fn price_list(&self) -> &'static PriceList {
+        self.price_list
+    }
+
+    /// Call a method of the calibration actor with some parameters and return the results.
+    ///
+    /// Fails if the message hasn't executed successfully.
+    pub fn execute<P: Serialize>(
+        &mut self,
+        method_num: u64,
+        params: &P,
+    ) -> anyhow::Result<ApplyRet> {
+        let message = Message {
+            from: self.sender.1,
+            to: self.actor_address,
+            sequence: self.actor_sequence,
+            gas_limit: ENOUGH_GAS.as_milligas(),
+            method_num,
+            params: RawBytes::serialize(params)?,
+            ..Message::default()
+        };
+
+        self.actor_sequence += 1;
+
+        let ret = self
+            .tester
+            .executor
+            .as_mut()
+            .ok_or_else(|| anyhow!("machine not instantiated"))?
+            .execute_message(message, ApplyKind::Explicit, 100)?;
+
+        if let Some(failure) = &ret.failure_info {
+            return Err(anyhow!("message execution failed: {failure}"));
+        }
+        if !ret.msg_receipt.exit_code.is_success() {
+            return Err(anyhow!(
+                "message execution failed with exit code {}",
+                ret.msg_receipt.exit_code
+            ));
+        }
+
+        Ok(ret)
+    }
+}
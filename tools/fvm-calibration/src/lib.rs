@@ -0,0 +1,18 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+//! Gas calibration: runs scenarios exercising the syscalls of the gas calibration actor with
+//! varying inputs, collects the time measured for each gas charge, fits linear models of the time
+//! in terms of the inputs, and compares them with the price list charging the gas.
+//!
+//! The charges are timed by their [`GasTimer`](fvm::gas::GasTimer)s, as the messages are executed
+//! with tracing enabled.
+
+pub mod env;
+pub mod model;
+pub mod report;
+pub mod scenarios;
+
+pub use env::CalibrationEnv;
+pub use model::{Fit, Measurement, Obs, Regression};
+pub use report::{Report, ReportEntry};
+pub use scenarios::{Options, Scenario};
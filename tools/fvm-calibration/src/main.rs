@@ -0,0 +1,116 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, anyhow};
+use clap::{Parser, ValueEnum};
+use fvm::gas::{PriceList, price_list_by_network_version};
+use fvm_calibration::report::{self, DEFAULT_GAS_PER_NS};
+use fvm_calibration::{CalibrationEnv, Options, Report, Scenario};
+use fvm_shared::version::NetworkVersion;
+
+/// Run gas calibration scenarios and compare the fitted models with the price list
+#[derive(Parser, Debug)]
+struct Args {
+    /// Builtin actors bundle to use.
+    #[arg(short, long)]
+    bundle: String,
+
+    /// Scenarios to run; all of them by default. One of: hashing, blocks, events, utf8,
+    /// signatures, bls-aggregate, secp-recover, send, cbor-fields, cbor-links.
+    #[arg(short, long)]
+    scenario: Vec<Scenario>,
+
+    /// Number of iterations per input, overriding the default of each scenario.
+    #[arg(short, long)]
+    iterations: Option<usize>,
+
+    /// Upper bound of the inputs swept by the scenarios, e.g. to make a quick run.
+    #[arg(long)]
+    max_input: Option<usize>,
+
+    /// Fraction of the slowest observations of each input to drop as outliers.
+    #[arg(long, default_value = "0.02")]
+    outliers: f32,
+
+    /// Gas per nanosecond to convert the fitted time coefficients with.
+    #[arg(long, default_value_t = DEFAULT_GAS_PER_NS)]
+    gas_per_ns: f64,
+
+    /// Price list to charge gas with and compare against, as JSON; defaults to the price list of
+    /// the calibrated network version.
+    #[arg(short, long)]
+    price_list: Option<PathBuf>,
+
+    /// Directory to export the observations and regressions to, for visualization.
+    #[arg(short, long)]
+    output_dir: Option<PathBuf>,
+
+    /// Output format.
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: Format,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Human readable table.
+    Text,
+    /// The report as JSON.
+    Json,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    if !(0.0..0.5).contains(&args.outliers) {
+        return Err(anyhow!("the outlier fraction must be in [0, 0.5)"));
+    }
+
+    let bundle = fs::read(&args.bundle).context("error reading bundle")?;
+
+    let price_list: &'static PriceList = match &args.price_list {
+        Some(path) => {
+            let file = fs::File::open(path).context("error reading price list")?;
+            let price_list: PriceList =
+                serde_json::from_reader(file).context("error parsing price list")?;
+            Box::leak(Box::new(price_list))
+        }
+        None => price_list_by_network_version(NetworkVersion::V21),
+    };
+
+    let scenarios = if args.scenario.is_empty() {
+        Scenario::ALL.to_vec()
+    } else {
+        args.scenario
+    };
+
+    let opts = Options {
+        iterations: args.iterations,
+        max_input: args.max_input,
+        outliers: args.outliers,
+    };
+
+    let mut measurements = Vec::new();
+    for scenario in scenarios {
+        eprintln!("running {scenario}");
+        // Use a fresh machine for each scenario so they don't affect each other.
+        let mut env = CalibrationEnv::new(&bundle, price_list)?;
+        let ms = scenario
+            .run(&mut env, &opts)
+            .with_context(|| format!("scenario {scenario} failed"))?;
+        measurements.extend(ms);
+    }
+
+    if let Some(dir) = &args.output_dir {
+        report::export(dir, &measurements).context("error exporting measurements")?;
+    }
+
+    let report = Report::new(args.gas_per_ns, &measurements);
+    match args.format {
+        Format::Text => report.print(std::io::stdout().lock())?,
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(())
+}
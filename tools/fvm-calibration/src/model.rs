@@ -0,0 +1,270 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::BTreeMap;
+
+use fvm::executor::ApplyRet;
+use fvm::gas::GasCharge;
+use fvm::trace::ExecutionEvent;
+use fvm_gas_calibration_shared::LinearFit;
+use serde::{Deserialize, Serialize};
+
+/// An observation that we can use to estimate coefficients
+/// to model time in terms of some variables.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Obs {
+    pub charge: String,
+    pub label: String,
+    pub elapsed_nanos: u128,
+    pub variables: Vec<usize>,
+    /// The compute gas charged by the price list, in milligas.
+    pub compute_gas: u64,
+}
+
+impl Obs {
+    /// Create an observation from a timed gas charge. Returns `None` if the charge wasn't timed.
+    pub fn from_charge(charge: &GasCharge, label: &str, variables: Vec<usize>) -> Option<Self> {
+        let elapsed = charge.elapsed.get()?;
+        Some(Obs {
+            charge: charge.name.to_string(),
+            label: label.to_owned(),
+            elapsed_nanos: elapsed.as_nanos(),
+            variables,
+            compute_gas: charge.compute_gas.as_milligas(),
+        })
+    }
+}
+
+/// A linear model `y = intercept + slope * x`, along with its coefficient of determination.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Regression {
+    pub label: String,
+    pub intercept: f64,
+    pub slope: f64,
+    pub r_squared: f64,
+}
+
+/// The models fitted over a series of observations: the time it took to execute, and the gas the
+/// price list charged for it, both in terms of the same variable.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub label: String,
+    pub samples: usize,
+    /// Nanoseconds in terms of the variable.
+    pub time: Regression,
+    /// Charged compute milligas in terms of the variable.
+    pub gas: Regression,
+}
+
+impl Fit {
+    /// Fit the models on one of the variables of the observations.
+    pub fn new(label: &str, obs: &[Obs], var_idx: usize) -> Self {
+        let x = |obs: &Obs| obs.variables[var_idx] as f64;
+        Fit {
+            label: label.to_owned(),
+            samples: obs.len(),
+            time: regression(
+                label,
+                obs.iter().map(|obs| (x(obs), obs.elapsed_nanos as f64)),
+            ),
+            gas: regression(
+                label,
+                obs.iter().map(|obs| (x(obs), obs.compute_gas as f64)),
+            ),
+        }
+    }
+}
+
+/// The observations collected for one model, e.g. a gas charge, and the models fitted on them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Measurement {
+    pub name: String,
+    pub obs: Vec<Obs>,
+    pub fits: Vec<Fit>,
+}
+
+impl Measurement {
+    /// Fit a separate model on each label of the observations.
+    pub fn by_label(name: &str, obs: Vec<Obs>) -> Self {
+        let mut obs_by_label: BTreeMap<&str, Vec<Obs>> = BTreeMap::new();
+        for ob in &obs {
+            obs_by_label
+                .entry(ob.label.as_str())
+                .or_default()
+                .push(ob.clone());
+        }
+        let fits = obs_by_label
+            .iter()
+            .map(|(label, obs)| Fit::new(label, obs, 0))
+            .collect();
+
+        Measurement {
+            name: name.to_owned(),
+            obs,
+            fits,
+        }
+    }
+
+    /// Fit a single model on all the observations, regardless of their labels.
+    pub fn pooled(name: &str, obs: Vec<Obs>) -> Self {
+        let fits = if obs.is_empty() {
+            Vec::new()
+        } else {
+            vec![Fit::new("", &obs, 0)]
+        };
+
+        Measurement {
+            name: name.to_owned(),
+            obs,
+            fits,
+        }
+    }
+}
+
+/// Linear regression between one of the variables and time.
+pub fn least_squares(label: String, obs: &[Obs], var_idx: usize) -> Regression {
+    regression(
+        &label,
+        obs.iter().map(|obs| {
+            let x = obs.variables[var_idx] as f64;
+            let y = obs.elapsed_nanos as f64;
+            (x, y)
+        }),
+    )
+}
+
+fn regression(label: &str, xys: impl Iterator<Item = (f64, f64)>) -> Regression {
+    let LinearFit {
+        intercept,
+        slope,
+        r_squared,
+    } = fvm_gas_calibration_shared::least_squares(xys);
+
+    Regression {
+        label: label.to_owned(),
+        intercept,
+        slope,
+        r_squared,
+    }
+}
+
+/// Collect the timed charges with a given name from the execution trace.
+pub fn collect_obs(ret: &ApplyRet, name: &str, label: &str, size: usize) -> Vec<Obs> {
+    ret.exec_trace
+        .iter()
+        .filter_map(|t| match t {
+            ExecutionEvent::GasCharge(charge) if charge.name == name => {
+                Obs::from_charge(charge, label, vec![size])
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Drop a certain fraction of the observations with the highest time as outliers.
+pub fn eliminate_outliers(mut obs: Vec<Obs>, drop: f32, eliminate: Eliminate) -> Vec<Obs> {
+    obs.sort_by_key(|obs| obs.elapsed_nanos);
+    let size = obs.len();
+    let drop = (size as f32 * drop) as usize;
+    match eliminate {
+        Eliminate::Top => obs.into_iter().take(size - drop).collect(),
+        Eliminate::Bottom => obs.into_iter().skip(drop).collect(),
+        Eliminate::Both => obs.into_iter().skip(drop).take(size - 2 * drop).collect(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eliminate {
+    Top,
+    Bottom,
+    Both,
+}
+
+/// Input sizes between 0 and 1MB, denser towards the low end.
+pub fn common_sizes() -> Vec<usize> {
+    let mut sizes: Vec<usize> = vec![0];
+    sizes.extend(
+        [10, 100, 1_000, 10_000, 100_000]
+            .into_iter()
+            .flat_map(|i| (1..10).map(move |m| m * i)),
+    );
+    sizes.push(1_000_000);
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obs(label: &str, x: usize, nanos: u128, gas: u64) -> Obs {
+        Obs {
+            charge: "OnTest".into(),
+            label: label.into(),
+            elapsed_nanos: nanos,
+            variables: vec![x],
+            compute_gas: gas,
+        }
+    }
+
+    #[test]
+    fn least_squares_fits_line() {
+        let obs: Vec<_> = (0..10)
+            .map(|x| obs("", x, 100 + 3 * x as u128, 0))
+            .collect();
+        let reg = least_squares("".into(), &obs, 0);
+        assert!((reg.intercept - 100.0).abs() < 1e-9);
+        assert!((reg.slope - 3.0).abs() < 1e-9);
+        assert!((reg.r_squared - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn constant_variable() {
+        let obs = vec![obs("", 1, 10, 5), obs("", 1, 20, 5), obs("", 1, 30, 5)];
+        let fit = Fit::new("", &obs, 0);
+        assert_eq!(fit.time.slope, 0.0);
+        assert!((fit.time.intercept - 20.0).abs() < 1e-9);
+        assert_eq!(fit.gas.slope, 0.0);
+        assert_eq!(fit.gas.intercept, 5.0);
+        assert_eq!(fit.gas.r_squared, 1.0);
+    }
+
+    #[test]
+    fn fits_by_label() {
+        let obs = vec![
+            obs("b", 0, 10, 0),
+            obs("a", 0, 0, 0),
+            obs("a", 10, 20, 1000),
+            obs("b", 10, 60, 0),
+        ];
+        let m = Measurement::by_label("OnTest", obs);
+        assert_eq!(m.obs.len(), 4);
+        let fits: Vec<_> = m
+            .fits
+            .iter()
+            .map(|f| (f.label.as_str(), f.samples, f.time.slope, f.gas.slope))
+            .collect();
+        assert_eq!(fits, vec![("a", 2, 2.0, 100.0), ("b", 2, 5.0, 0.0)]);
+
+        assert!(Measurement::pooled("OnTest", Vec::new()).fits.is_empty());
+    }
+
+    #[test]
+    fn eliminates_outliers() {
+        let all: Vec<_> = (0..100).map(|x| obs("", x, x as u128, 0)).collect();
+        let nanos = |obs: Vec<Obs>| {
+            let nanos: Vec<_> = obs.iter().map(|o| o.elapsed_nanos).collect();
+            (nanos.len(), nanos[0], nanos[nanos.len() - 1])
+        };
+        assert_eq!(
+            nanos(eliminate_outliers(all.clone(), 0.05, Eliminate::Top)),
+            (95, 0, 94)
+        );
+        assert_eq!(
+            nanos(eliminate_outliers(all.clone(), 0.05, Eliminate::Bottom)),
+            (95, 5, 99)
+        );
+        assert_eq!(
+            nanos(eliminate_outliers(all, 0.05, Eliminate::Both)),
+            (90, 5, 94)
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Fit, Measurement};
+
+/// The gas charged per nanosecond of execution time the price list targets.
+pub const DEFAULT_GAS_PER_NS: f64 = 10.0;
+
+/// Compares the models fitted on the measured execution times with the price list that charged
+/// the gas, converting the time coefficients to gas at a fixed rate.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Report {
+    pub gas_per_ns: f64,
+    pub entries: Vec<ReportEntry>,
+}
+
+/// The fitted and the current gas coefficients of one model. Gas amounts are in whole gas, as
+/// fractions.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReportEntry {
+    pub name: String,
+    pub label: String,
+    pub samples: usize,
+    /// Coefficient of determination of the time model.
+    pub r_squared: f64,
+    /// The fitted constant cost.
+    pub fitted_base: f64,
+    /// The fitted cost per unit of the input variable.
+    pub fitted_per_unit: f64,
+    /// The constant cost of the active price list.
+    pub current_base: f64,
+    /// The cost per unit of the input variable of the active price list.
+    pub current_per_unit: f64,
+}
+
+impl ReportEntry {
+    fn new(name: &str, fit: &Fit, gas_per_ns: f64) -> Self {
+        ReportEntry {
+            name: name.to_owned(),
+            label: fit.label.clone(),
+            samples: fit.samples,
+            r_squared: fit.time.r_squared,
+            fitted_base: fit.time.intercept * gas_per_ns,
+            fitted_per_unit: fit.time.slope * gas_per_ns,
+            // The charged gas is in milligas.
+            current_base: fit.gas.intercept / 1000.0,
+            current_per_unit: fit.gas.slope / 1000.0,
+        }
+    }
+
+    /// How many times the price list overcharges the constant cost, compared to the fitted one.
+    pub fn base_ratio(&self) -> f64 {
+        self.current_base / self.fitted_base
+    }
+
+    /// How many times the price list overcharges per unit, compared to the fitted cost.
+    pub fn per_unit_ratio(&self) -> f64 {
+        self.current_per_unit / self.fitted_per_unit
+    }
+}
+
+impl Report {
+    pub fn new(gas_per_ns: f64, measurements: &[Measurement]) -> Self {
+        let entries = measurements
+            .iter()
+            .flat_map(|m| {
+                m.fits
+                    .iter()
+                    .map(|fit| ReportEntry::new(&m.name, fit, gas_per_ns))
+            })
+            .collect();
+        Report {
+            gas_per_ns,
+            entries,
+        }
+    }
+
+    /// Print the report as a table.
+    pub fn print(&self, mut w: impl Write) -> std::io::Result<()> {
+        writeln!(w, "fitted at {} gas/ns", self.gas_per_ns)?;
+        writeln!(
+            w,
+            "{:<30} {:<20} {:>8} {:>6} {:>14} {:>14} {:>8} {:>14} {:>14} {:>8}",
+            "name",
+            "label",
+            "samples",
+            "r2",
+            "fitted base",
+            "current base",
+            "ratio",
+            "fitted/unit",
+            "current/unit",
+            "ratio"
+        )?;
+        for e in &self.entries {
+            writeln!(
+                w,
+                "{:<30} {:<20} {:>8} {:>6.3} {:>14.3} {:>14.3} {:>8.2} {:>14.3} {:>14.3} {:>8.2}",
+                e.name,
+                e.label,
+                e.samples,
+                e.r_squared,
+                e.fitted_base,
+                e.current_base,
+                e.base_ratio(),
+                e.fitted_per_unit,
+                e.current_per_unit,
+                e.per_unit_ratio()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Export the observations and the time regressions as `observations/<name>.jsonline` and
+/// `regressions/<name>.jsonline` files under a directory, for visualization.
+pub fn export(dir: &Path, measurements: &[Measurement]) -> std::io::Result<()> {
+    for m in measurements {
+        let file_name = format!("{}.jsonline", m.name);
+        let regs: Vec<_> = m.fits.iter().map(|fit| &fit.time).collect();
+        export_json(&dir.join("regressions").join(&file_name), &regs)?;
+        export_json(&dir.join("observations").join(&file_name), &m.obs)?;
+    }
+    Ok(())
+}
+
+fn export_json<T: Serialize>(path: &Path, values: &[T]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut output = std::io::BufWriter::new(std::fs::File::create(path)?);
+
+    for value in values {
+        serde_json::to_writer(&mut output, value)?;
+        writeln!(&mut output)?;
+    }
+
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Obs;
+
+    #[test]
+    fn compares_to_price_list() {
+        // 2ns + 1ns per byte, charged at 20 + 10 gas per byte.
+        let obs = (0..10)
+            .step_by(2)
+            .map(|x| Obs {
+                charge: "OnTest".into(),
+                label: "test".into(),
+                elapsed_nanos: 2 + x as u128,
+                variables: vec![x],
+                compute_gas: 20_000 + 10_000 * x as u64,
+            })
+            .collect();
+        let report = Report::new(10.0, &[Measurement::by_label("OnTest", obs)]);
+
+        let [e] = &report.entries[..] else {
+            panic!("expected one entry")
+        };
+        assert_eq!(e.samples, 5);
+        assert!((e.fitted_base - 20.0).abs() < 1e-9);
+        assert!((e.fitted_per_unit - 10.0).abs() < 1e-9);
+        assert!((e.current_base - 20.0).abs() < 1e-9);
+        assert!((e.current_per_unit - 10.0).abs() < 1e-9);
+        assert!((e.per_unit_ratio() - 1.0).abs() < 1e-9);
+
+        let mut out = Vec::new();
+        report.print(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("OnTest"));
+    }
+}
@@ -0,0 +1,691 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use bls_signatures::Serialize as _;
+use fvm::trace::ExecutionEvent;
+use fvm_gas_calibration_shared::*;
+use fvm_shared::address::Address;
+use fvm_shared::crypto::hash::SupportedHashes;
+use fvm_shared::crypto::signature::{SECP_SIG_LEN, SignatureType, signing};
+use fvm_shared::event::Flags;
+use rand::distributions::Standard;
+use rand::{Rng, RngCore, thread_rng};
+
+use crate::env::CalibrationEnv;
+use crate::model::{Eliminate, Measurement, Obs, collect_obs, common_sizes, eliminate_outliers};
+
+/// A calibration scenario, exercising one or more related gas charges with varying inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scenario {
+    /// `OnHashing` with each supported hash function, by input size.
+    Hashing,
+    /// `OnBlock*` charges, by block size.
+    Blocks,
+    /// `OnActorEvent`, by total value size and by entry count.
+    Events,
+    /// `OnUtf8Validation`, by string length. Measured on the host rather than in an actor.
+    Utf8,
+    /// `OnVerifySignature` with each signature type, by input size.
+    Signatures,
+    /// `OnVerifyBlsAggregateSignature`, by number of signers.
+    BlsAggregate,
+    /// `OnRecoverSecpPublicKey`, by input size.
+    SecpRecover,
+    /// `OnValueTransfer` and `OnMethodInvocation`.
+    Send,
+    /// `OnScanIpldLinks` by number of CBOR fields, without links.
+    CborFields,
+    /// `OnScanIpldLinks`, and link tracking and checking, by number of CBOR links.
+    CborLinks,
+}
+
+impl Scenario {
+    /// All the scenarios.
+    pub const ALL: &'static [Scenario] = &[
+        Scenario::Hashing,
+        Scenario::Blocks,
+        Scenario::Events,
+        Scenario::Utf8,
+        Scenario::Signatures,
+        Scenario::BlsAggregate,
+        Scenario::SecpRecover,
+        Scenario::Send,
+        Scenario::CborFields,
+        Scenario::CborLinks,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scenario::Hashing => "hashing",
+            Scenario::Blocks => "blocks",
+            Scenario::Events => "events",
+            Scenario::Utf8 => "utf8",
+            Scenario::Signatures => "signatures",
+            Scenario::BlsAggregate => "bls-aggregate",
+            Scenario::SecpRecover => "secp-recover",
+            Scenario::Send => "send",
+            Scenario::CborFields => "cbor-fields",
+            Scenario::CborLinks => "cbor-links",
+        }
+    }
+
+    /// Run the scenario, returning one measurement per modelled charge.
+    pub fn run(
+        &self,
+        env: &mut CalibrationEnv,
+        opts: &Options,
+    ) -> anyhow::Result<Vec<Measurement>> {
+        match self {
+            Scenario::Hashing => on_hashing(env, opts),
+            Scenario::Blocks => on_block(env, opts),
+            Scenario::Events => on_event(env, opts),
+            Scenario::Utf8 => utf8_validation(env, opts),
+            Scenario::Signatures => on_verify_signature(env, opts),
+            Scenario::BlsAggregate => on_verify_bls_aggregate(env, opts),
+            Scenario::SecpRecover => on_recover_secp_public_key(env, opts),
+            Scenario::Send => on_send(env, opts),
+            Scenario::CborFields => on_scan_cbor_fields(env, opts),
+            Scenario::CborLinks => on_scan_cbor_links(env, opts),
+        }
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Scenario {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scenario::ALL
+            .iter()
+            .find(|sc| sc.name() == s)
+            .copied()
+            .ok_or_else(|| anyhow!("unknown scenario {s}"))
+    }
+}
+
+/// Options shared by the scenarios.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Number of iterations per input, overriding the default of each scenario.
+    pub iterations: Option<usize>,
+    /// Upper bound of the input variable (data size, entry count, signers, etc.) swept by the
+    /// scenarios, to make quick runs possible.
+    pub max_input: Option<usize>,
+    /// Fraction of the slowest observations of each input to drop as outliers.
+    pub outliers: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            iterations: None,
+            max_input: None,
+            outliers: 0.02,
+        }
+    }
+}
+
+impl Options {
+    fn iterations(&self, default: usize) -> usize {
+        self.iterations.unwrap_or(default)
+    }
+
+    fn inputs(&self, inputs: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        inputs
+            .into_iter()
+            .filter(|i| self.max_input.is_none_or(|max| *i <= max))
+            .collect()
+    }
+
+    fn eliminate_outliers(&self, obs: Vec<Obs>, eliminate: Eliminate) -> Vec<Obs> {
+        eliminate_outliers(obs, self.outliers, eliminate)
+    }
+}
+
+fn on_hashing(env: &mut CalibrationEnv, opts: &Options) -> anyhow::Result<Vec<Measurement>> {
+    const CHARGE_NAME: &str = "OnHashing";
+    const METHOD: Method = Method::OnHashing;
+
+    let hashers = [
+        SupportedHashes::Sha2_256,
+        SupportedHashes::Blake2b256,
+        SupportedHashes::Blake2b512,
+        SupportedHashes::Keccak256,
+        SupportedHashes::Ripemd160,
+    ];
+
+    let sizes = opts.inputs(common_sizes());
+    let iterations = opts.iterations(100);
+
+    let mut obs = Vec::new();
+    let mut rng = thread_rng();
+
+    for hasher in hashers.iter() {
+        let label = format!("{hasher:?}");
+        for size in sizes.iter() {
+            let params = OnHashingParams {
+                hasher: *hasher as u64,
+                size: *size,
+                iterations,
+                seed: rng.r#gen(),
+            };
+
+            let ret = env.execute(METHOD as u64, &params)?;
+
+            let iter_obs = collect_obs(&ret, CHARGE_NAME, &label, *size);
+
+            // According to the charts there is always an outlier with 10x runtime,
+            // which can throw off the model. Maybe it's while some things are warming up.
+            // Seems to be present at each call, so once per size. I'll just throw these away.
+            obs.extend(opts.eliminate_outliers(iter_obs, Eliminate::Top));
+        }
+    }
+
+    Ok(vec![Measurement::by_label(CHARGE_NAME, obs)])
+}
+
+fn on_block(env: &mut CalibrationEnv, opts: &Options) -> anyhow::Result<Vec<Measurement>> {
+    let sizes = opts.inputs(common_sizes());
+    let iterations = opts.iterations(100);
+
+    let mut all_obs: BTreeMap<String, Vec<Obs>> = Default::default();
+
+    // NOTE: For actually modeling the effect of IO, we shouldn't be using the memory blockstore.
+    // But at the same time when the contracts are executed the changes are buffered in mory,
+    // not everything actually gets written to the disk.
+    let mut rng = thread_rng();
+
+    // NOTE: The order of sizes (doing them ascending, descending, or shuffled),
+    // and whether we reuse the same tester or make a new one for each, does make a difference.
+
+    for size in sizes.iter() {
+        let params = OnBlockParams {
+            size: *size,
+            iterations,
+            seed: rng.r#gen(),
+        };
+
+        let ret = env.execute(Method::OnBlock as u64, &params)?;
+
+        let mut iter_obs: BTreeMap<String, Vec<Obs>> = Default::default();
+
+        for event in ret.exec_trace {
+            if let ExecutionEvent::GasCharge(charge) = event
+                && charge.name.starts_with("OnBlock")
+                && let Some(ob) = Obs::from_charge(&charge, "n/a", vec![*size])
+            {
+                iter_obs.entry(charge.name.into()).or_default().push(ob);
+            }
+        }
+        // The first OnBlockRead is for reading the parameters. From OnBlockStat that's the only record.
+        for name in ["OnBlockRead", "OnBlockStat"] {
+            if let Some(obs) = iter_obs.get_mut(name)
+                && !obs.is_empty()
+            {
+                obs.remove(0);
+            }
+        }
+
+        for (name, obs) in iter_obs {
+            if !obs.is_empty() {
+                // According to the charts, there are odd outliers.
+                let obs = opts.eliminate_outliers(obs, Eliminate::Top);
+                all_obs.entry(name).or_default().extend(obs);
+            }
+        }
+    }
+
+    Ok(all_obs
+        .into_iter()
+        .map(|(name, obs)| Measurement::pooled(&name, obs))
+        .collect())
+}
+
+fn on_event(env: &mut CalibrationEnv, opts: &Options) -> anyhow::Result<Vec<Measurement>> {
+    const CHARGE: &str = "OnActorEvent";
+    const METHOD: Method = Method::OnEvent;
+
+    let iterations = opts.iterations(500);
+    let mut rng = thread_rng();
+
+    // By value size, with a fixed number of entries per series.
+    let mut value_obs = Vec::new();
+    for entries in opts.inputs([1usize, 16, 127, 255]) {
+        for total_value_size in opts.inputs((8..=13).map(|x| usize::pow(2, x))) {
+            let label = format!("{entries}-entries");
+            let params = OnEventParams {
+                iterations,
+                // number of entries to emit
+                entries,
+                total_value_size,
+                flags: Flags::FLAG_INDEXED_ALL,
+                seed: rng.r#gen(),
+            };
+
+            let ret = env.execute(METHOD as u64, &params)?;
+
+            let series = collect_obs(&ret, CHARGE, &label, total_value_size);
+            value_obs.extend(opts.eliminate_outliers(series, Eliminate::Top));
+        }
+    }
+
+    // By entry count, with a fixed total value size per series.
+    let mut entry_obs = Vec::new();
+    for total_value_size in opts.inputs([255, 1024, 4096, 8192]) {
+        for entries in opts.inputs((1..=8).map(|x| usize::pow(2, x) - 1)) {
+            let label = format!("{total_value_size}-size");
+            let params = OnEventParams {
+                iterations,
+                // number of entries to emit
+                entries,
+                total_value_size,
+                flags: Flags::FLAG_INDEXED_ALL,
+                seed: rng.r#gen(),
+            };
+
+            let ret = env.execute(METHOD as u64, &params)?;
+
+            let series = collect_obs(&ret, CHARGE, &label, entries);
+            entry_obs.extend(opts.eliminate_outliers(series, Eliminate::Top));
+        }
+    }
+
+    Ok(vec![
+        Measurement::by_label("OnActorEventValue", value_obs),
+        Measurement::by_label("OnActorEventEntries", entry_obs),
+    ])
+}
+
+fn utf8_validation(env: &mut CalibrationEnv, opts: &Options) -> anyhow::Result<Vec<Measurement>> {
+    const CHARGE: &str = "OnUtf8Validate";
+
+    let mut chars = thread_rng().sample_iter(Standard);
+
+    let iterations = opts.iterations(500);
+    let price_list = env.price_list();
+
+    let mut obs = Vec::new();
+    #[derive(Debug, Copy, Clone)]
+    enum Kind {
+        Ascii,
+        MaxUtf8,
+        RandomUtf8,
+    }
+    use Kind::*;
+    for size in opts.inputs((0..=8).map(|x| usize::pow(2, x))) {
+        for kind in [Ascii, RandomUtf8, MaxUtf8] {
+            let mut series = Vec::new();
+            for _ in 0..iterations {
+                let rand_str: String = match kind {
+                    Ascii => "a".repeat(size),
+                    MaxUtf8 => char::REPLACEMENT_CHARACTER.to_string().repeat(size / 2),
+                    RandomUtf8 => chars
+                        .by_ref()
+                        .take_while({
+                            let mut total: usize = 0;
+                            move |c: &char| {
+                                total += c.len_utf8();
+                                total < size
+                            }
+                        })
+                        .collect(),
+                };
+                let charge = price_list.on_utf8_validation(rand_str.len());
+                let start = minstant::Instant::now();
+                let _ = std::hint::black_box(std::str::from_utf8(std::hint::black_box(
+                    rand_str.as_bytes(),
+                )));
+                let time = start.elapsed();
+                series.push(Obs {
+                    charge: CHARGE.into(),
+                    label: format!("{:?}-validate", kind),
+                    elapsed_nanos: time.as_nanos(),
+                    variables: vec![rand_str.len()],
+                    compute_gas: charge.compute_gas.as_milligas(),
+                })
+            }
+            obs.extend(opts.eliminate_outliers(series, Eliminate::Both));
+        }
+    }
+
+    Ok(vec![Measurement::by_label(CHARGE, obs)])
+}
+
+fn on_recover_secp_public_key(
+    env: &mut CalibrationEnv,
+    opts: &Options,
+) -> anyhow::Result<Vec<Measurement>> {
+    const CHARGE_NAME: &str = "OnRecoverSecpPublicKey";
+    const METHOD: Method = Method::OnRecoverSecpPublicKey;
+
+    // Just doing it for uniformity.
+    let sizes = opts.inputs(common_sizes());
+    let iterations = opts.iterations(10);
+
+    let mut obs = Vec::new();
+    let mut rng = thread_rng();
+
+    // Generate a signature over some data to ensure it's not complete rubbish.
+    let mut data = vec![0u8; 100];
+    rng.fill_bytes(&mut data);
+
+    let sk = k256::ecdsa::SigningKey::random(&mut rng);
+    let sig = secp_sign(&sk, &data)?;
+
+    for size in sizes.iter() {
+        let params = OnRecoverSecpPublicKeyParams {
+            iterations,
+            size: *size,
+            signature: sig.to_vec(),
+            seed: rng.r#gen(),
+        };
+
+        let ret = env.execute(METHOD as u64, &params)?;
+
+        obs.extend(collect_obs(&ret, CHARGE_NAME, "n/a", *size));
+    }
+
+    Ok(vec![Measurement::pooled(CHARGE_NAME, obs)])
+}
+
+fn on_send(env: &mut CalibrationEnv, opts: &Options) -> anyhow::Result<Vec<Measurement>> {
+    const TRANSFER_CHARGE_NAME: &str = "OnValueTransfer";
+    const INVOKE_CHARGE_NAME: &str = "OnMethodInvocation";
+    const METHOD: Method = Method::OnSend;
+
+    let iterations = opts.iterations(100);
+
+    let mut invoke_obs = Vec::new();
+    let mut transfer_obs = Vec::new();
+
+    for invoke in [true, false] {
+        for value_transfer in [true, false] {
+            let label = match (invoke, value_transfer) {
+                (true, true) => "invoke-and-transfer",
+                (false, true) => "transfer-only",
+                (true, false) => "invoke-only",
+                (false, false) => continue,
+            };
+            let params = OnSendParams {
+                iterations,
+                value_transfer,
+                invoke,
+            };
+
+            let ret = env.execute(METHOD as u64, &params)?;
+
+            let both = (value_transfer == invoke) as usize;
+
+            if value_transfer {
+                let iter_obs = collect_obs(&ret, TRANSFER_CHARGE_NAME, label, both);
+                transfer_obs.extend(opts.eliminate_outliers(iter_obs, Eliminate::Top));
+            }
+
+            if invoke {
+                let iter_obs = collect_obs(&ret, INVOKE_CHARGE_NAME, label, both);
+                invoke_obs.extend(opts.eliminate_outliers(iter_obs, Eliminate::Top));
+            }
+        }
+    }
+
+    Ok(vec![
+        Measurement::pooled(TRANSFER_CHARGE_NAME, transfer_obs),
+        Measurement::pooled(INVOKE_CHARGE_NAME, invoke_obs),
+    ])
+}
+
+fn on_verify_signature(
+    env: &mut CalibrationEnv,
+    opts: &Options,
+) -> anyhow::Result<Vec<Measurement>> {
+    const CHARGE_NAME: &str = "OnVerifySignature";
+    const METHOD: Method = Method::OnVerifySignature;
+
+    let sig_types = [
+        SignatureType::BLS,
+        SignatureType::Secp256k1,
+        #[cfg(feature = "delegated-signatures")]
+        SignatureType::Delegated,
+    ];
+
+    let sizes = opts.inputs(common_sizes());
+    let iterations = opts.iterations(100);
+
+    let mut obs = Vec::new();
+    let mut rng = thread_rng();
+
+    // Just some random data over which we can generate an example signature.
+    // Having a valid BLS signature is important otherwise verification is
+    // an instant rejection without hasing the input data.
+    let mut data = vec![0u8; 100];
+    rng.fill_bytes(&mut data);
+
+    for sig_type in sig_types.iter() {
+        let label = format!("{sig_type:?}");
+
+        let (signer, signature) = match sig_type {
+            SignatureType::Secp256k1 => {
+                let sk = k256::ecdsa::SigningKey::random(&mut rng);
+                let pk = sk.verifying_key();
+                let addr = Address::new_secp256k1(pk.to_encoded_point(false).as_bytes())?;
+                let sig = secp_sign(&sk, &data)?.into();
+                (addr, sig)
+            }
+            SignatureType::BLS => {
+                let sk = bls_signatures::PrivateKey::generate(&mut rng);
+                let pk = sk.public_key();
+                let addr = Address::new_bls(&pk.as_bytes())?;
+                let sig = sk.sign(&data).as_bytes();
+                (addr, sig)
+            }
+            SignatureType::Delegated => {
+                let sk = k256::ecdsa::SigningKey::random(&mut rng);
+                let addr = signing::delegated_address(&sk);
+                let sig = signing::sign_delegated(&sk, &data)?.bytes;
+                (addr, sig)
+            }
+        };
+
+        for size in sizes.iter() {
+            let params = OnVerifySignatureParams {
+                iterations,
+                size: *size,
+                signer,
+                signature: signature.clone(),
+                seed: rng.r#gen(),
+            };
+
+            let ret = env.execute(METHOD as u64, &params)?;
+
+            let iter_obs = collect_obs(&ret, CHARGE_NAME, &label, *size);
+            obs.extend(opts.eliminate_outliers(iter_obs, Eliminate::Top));
+        }
+    }
+
+    Ok(vec![Measurement::by_label(CHARGE_NAME, obs)])
+}
+
+fn on_verify_bls_aggregate(
+    env: &mut CalibrationEnv,
+    opts: &Options,
+) -> anyhow::Result<Vec<Measurement>> {
+    const CHARGE_NAME: &str = "OnVerifyBlsAggregateSignature";
+    const METHOD: Method = Method::OnVerifyBlsAggregate;
+
+    let iterations = opts.iterations(100);
+
+    let mut obs = Vec::new();
+    let mut rng = thread_rng();
+
+    for n in opts.inputs([1, 4, 8, 20, 50, 200, 1000]) {
+        let mut keys = Vec::new();
+        let mut sigs = Vec::new();
+        let mut messages = Vec::new();
+        for _ in 0..n {
+            let mut data = vec![0u8; 100];
+            rng.fill_bytes(&mut data);
+            let sk = bls_signatures::PrivateKey::generate(&mut rng);
+            let pk = sk.public_key();
+            let sig = sk.sign(&data);
+
+            keys.push(pk.as_bytes());
+            messages.push(data);
+            sigs.push(sig);
+        }
+        let signature = bls_signatures::aggregate(&sigs)?.as_bytes();
+        let params = OnVerifyBlsAggregateParams {
+            iterations,
+            signature,
+            keys,
+            messages,
+        };
+
+        let ret = env.execute(METHOD as u64, &params)?;
+
+        let iter_obs = collect_obs(&ret, CHARGE_NAME, "signers", n);
+        obs.extend(opts.eliminate_outliers(iter_obs, Eliminate::Top));
+    }
+
+    Ok(vec![Measurement::by_label(CHARGE_NAME, obs)])
+}
+
+// Scan CBOR Fields with no links.
+fn on_scan_cbor_fields(
+    env: &mut CalibrationEnv,
+    opts: &Options,
+) -> anyhow::Result<Vec<Measurement>> {
+    let field_counts = opts.inputs([2, 5, 10, 50, 100, 1000, 2500, 5000, 7500, 10_000]);
+    let iterations = opts.iterations(500);
+
+    let mut obs = Vec::new();
+    let mut rng = thread_rng();
+
+    for fc in field_counts {
+        let params = OnScanIpldLinksParams {
+            cbor_link_count: 0,
+            cbor_field_count: fc,
+            iterations,
+            seed: rng.r#gen(),
+        };
+
+        let ret = env.execute(Method::OnScanIpldLinks as u64, &params)?;
+
+        let iter_obs = collect_obs(&ret, "OnScanIpldLinks", "n/a", fc);
+        if !iter_obs.is_empty() {
+            // According to the charts, there are odd outliers.
+            obs.extend(opts.eliminate_outliers(iter_obs, Eliminate::Top));
+        }
+    }
+
+    Ok(vec![Measurement::pooled("OnScanCborFields", obs)])
+}
+
+// Scan CBOR Links, keeping the fields constant (10,000).
+fn on_scan_cbor_links(
+    env: &mut CalibrationEnv,
+    opts: &Options,
+) -> anyhow::Result<Vec<Measurement>> {
+    let field_count = 10_000;
+    let link_counts = opts.inputs([1, 10, 20, 50, 100, 500, 1000, 2500]);
+    let iterations = opts.iterations(250);
+
+    let mut all_obs: BTreeMap<&str, Vec<Obs>> = Default::default();
+    let mut rng = thread_rng();
+
+    for lc in link_counts {
+        let params = OnScanIpldLinksParams {
+            cbor_link_count: lc,
+            cbor_field_count: field_count,
+            iterations,
+            seed: rng.r#gen(),
+        };
+
+        let ret = env.execute(Method::OnScanIpldLinks as u64, &params)?;
+
+        let mut iter_obs: BTreeMap<&str, Vec<Obs>> = Default::default();
+
+        for event in ret.exec_trace {
+            let ExecutionEvent::GasCharge(charge) = event else {
+                continue;
+            };
+            for (key, name) in [
+                ("OnScanIpldLinks", "OnScanIpldLinks"),
+                ("OnTrackLinks", "OnBlockOpen"),
+                ("OnCheckLinks", "OnBlockCreate"),
+            ] {
+                if charge.name != name {
+                    continue;
+                }
+                if let Some(ob) = Obs::from_charge(&charge, "n/a", vec![lc]) {
+                    iter_obs.entry(key).or_default().push(ob);
+                }
+                break;
+            }
+        }
+
+        for (name, obs) in iter_obs {
+            if !obs.is_empty() {
+                // According to the charts, there are odd outliers.
+                let obs = opts.eliminate_outliers(obs, Eliminate::Top);
+                all_obs.entry(name).or_default().extend(obs);
+            }
+        }
+    }
+
+    Ok(all_obs
+        .into_iter()
+        .map(|(name, obs)| Measurement::pooled(name, obs))
+        .collect())
+}
+
+fn secp_sign(sk: &k256::ecdsa::SigningKey, data: &[u8]) -> anyhow::Result<[u8; SECP_SIG_LEN]> {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .to_state()
+        .update(data)
+        .finalize();
+    let (sig, recovery_id) = sk
+        .sign_prehash_recoverable(hash.as_bytes())
+        .map_err(|e| anyhow!("failed to sign: {e}"))?;
+
+    let mut signature = [0u8; SECP_SIG_LEN];
+    signature[..64].copy_from_slice(&sig.to_bytes());
+    signature[64] = recovery_id.to_byte();
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_names() {
+        for sc in Scenario::ALL {
+            assert_eq!(sc.name().parse::<Scenario>().unwrap(), *sc);
+        }
+        assert!("nope".parse::<Scenario>().is_err());
+    }
+
+    #[test]
+    fn limits_inputs() {
+        let opts = Options {
+            max_input: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(opts.inputs([1, 100, 1000]), vec![1, 100]);
+        assert_eq!(
+            Options::default().inputs([1, 100, 1000]),
+            vec![1, 100, 1000]
+        );
+        assert_eq!(opts.iterations(5), 5);
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use fvm::gas::price_list_by_network_version;
+use fvm_calibration::{CalibrationEnv, Options, Report, Scenario};
+use fvm_shared::version::NetworkVersion;
+
+#[test]
+fn run_all_scenarios() {
+    let price_list = price_list_by_network_version(NetworkVersion::V21);
+    let opts = Options {
+        iterations: Some(2),
+        max_input: Some(500),
+        outliers: 0.0,
+    };
+
+    for scenario in Scenario::ALL {
+        let mut env = CalibrationEnv::new(actors::BUNDLE_CAR, price_list).unwrap();
+        let measurements = scenario.run(&mut env, &opts).unwrap();
+        assert!(!measurements.is_empty(), "{scenario} measured nothing");
+
+        for m in &measurements {
+            assert!(
+                !m.obs.is_empty(),
+                "{scenario} has no {} observations",
+                m.name
+            );
+            assert!(!m.fits.is_empty(), "{scenario} has no {} fits", m.name);
+        }
+
+        let report = Report::new(10.0, &measurements);
+        let fits: usize = measurements.iter().map(|m| m.fits.len()).sum();
+        assert_eq!(report.entries.len(), fits);
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2021-2023 Protocol Labs
+// SPDX-License-Identifier: Apache-2.0, MIT
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{Context, anyhow};
+use cid::Cid;
+use clap::Parser;
+use fvm::engine::{EnginePool, PrecompiledWriter};
+use fvm::gas::try_price_list_by_network_version;
+use fvm::machine::{Manifest, NetworkConfig};
+use fvm_integration_tests::bundle;
+use fvm_ipld_blockstore::{Blockstore, MemoryBlockstore};
+use fvm_ipld_encoding::CborStore;
+use fvm_shared::version::NetworkVersion;
+
+/// Validate, instrument and compile the actors of a builtin-actors bundle ahead of time, for
+/// engines to load at startup
+#[derive(Parser, Debug)]
+struct Args {
+    /// Builtin actors bundle to compile.
+    #[arg(short, long)]
+    bundle: PathBuf,
+
+    /// Network version whose Wasm gas prices to instrument the actors with.
+    #[arg(short, long)]
+    network_version: u32,
+
+    /// Directory to write the precompiled actors to.
+    #[arg(short, long)]
+    output_dir: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let network_version = NetworkVersion::from(args.network_version);
+    if try_price_list_by_network_version(network_version).is_none() {
+        return Err(anyhow!("unsupported network version {network_version}"));
+    }
+
+    let blockstore = MemoryBlockstore::default();
+    let bundle = std::fs::read(&args.bundle).context("error reading bundle")?;
+    let bundle_cid =
+        bundle::import_bundle(&blockstore, &bundle).context("error importing bundle")?;
+    let actors = manifest_actors(&blockstore, &bundle_cid)?;
+
+    let nc = NetworkConfig::new(network_version);
+    let engine = EnginePool::new((&nc).into())?.acquire();
+    let mut writer = PrecompiledWriter::new(&engine, &args.output_dir)?;
+
+    let mut failures = 0;
+    for code_cid in &actors {
+        let start = Instant::now();
+        let res = blockstore
+            .get(code_cid)?
+            .ok_or_else(|| anyhow!("code not found in the bundle"))
+            .and_then(|wasm| writer.add(code_cid, &wasm));
+        match res {
+            Ok(size) => println!("{code_cid} {size:>10} bytes {:>8.2?}", start.elapsed()),
+            Err(e) => {
+                eprintln!("{code_cid} failed: {e:#}");
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(anyhow!(
+            "failed to precompile {failures} of {} actors",
+            actors.len()
+        ));
+    }
+    writer.finish()?;
+
+    println!(
+        "precompiled {} actors into {}",
+        actors.len(),
+        args.output_dir.display()
+    );
+    Ok(())
+}
+
+/// Read the code CIDs of the builtin actors in the manifest of a bundle, in manifest order.
+fn manifest_actors(blockstore: &impl Blockstore, bundle_cid: &Cid) -> anyhow::Result<Vec<Cid>> {
+    let (version, manifest_cid): (u32, Cid) = blockstore
+        .get_cbor(bundle_cid)?
+        .context("bundle root not found")?;
+    let manifest = Manifest::load(blockstore, &manifest_cid, version)?;
+    // Builtin actors are numbered sequentially from 1, in manifest order.
+    Ok((1..)
+        .map_while(|id| manifest.code_by_id(id).copied())
+        .collect())
+}